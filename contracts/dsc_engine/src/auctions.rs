@@ -0,0 +1,402 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_asset::AssetInfo;
+
+use crate::contract::exec::{
+    accrue_debt_rate, assert_owner, burn_msg, revert_if_shutdown, transfer_msg, DscSource,
+};
+use crate::error::ContractError;
+use crate::state::{
+    add_surplus, BalanceSheetAuction, BalanceSheetAuctionKind, BalanceSheetAuctionParams, BAD_DEBT,
+    BALANCE_SHEET_AUCTIONS, BALANCE_SHEET_AUCTION_PARAMS, CONFIG, DEBT_ON_AUCTION,
+    GLOBAL_SETTLEMENT, NEXT_BALANCE_SHEET_AUCTION_ID, SURPLUS,
+};
+
+pub fn update_balance_sheet_auction_params(
+    deps: DepsMut,
+    info: MessageInfo,
+    params: BalanceSheetAuctionParams,
+) -> Result<Response, ContractError> {
+    revert_if_shutdown(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &info.sender)?;
+
+    deps.api.addr_validate(params.governance_token.as_str())?;
+    if params.surplus_lot.is_zero()
+        || params.debt_lot.is_zero()
+        || params.debt_initial_lot.is_zero()
+        || params.min_bid_increment.is_zero()
+        || params.bid_duration == 0
+        || params.auction_duration == 0
+    {
+        return Err(ContractError::InvalidBalanceSheetAuctionParams {});
+    }
+    BALANCE_SHEET_AUCTION_PARAMS.save(deps.storage, &params)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_balance_sheet_auction_params")
+        .add_attribute("from", info.sender)
+        .add_attribute("governance_token", params.governance_token);
+    Ok(res)
+}
+
+/// Sells a lot of the DSC surplus above the buffer, once there is no bad debt left to settle
+pub fn kick_surplus_auction(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    revert_if_shutdown(deps.storage)?;
+    let params = load_params(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    // stability fees owed so far are part of the surplus
+    accrue_debt_rate(deps.storage, &env, &config)?;
+    if !BAD_DEBT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .is_zero()
+    {
+        return Err(ContractError::BadDebtOutstanding {});
+    }
+    let surplus = SURPLUS
+        .may_load(deps.storage, config.dsc_address.to_string())?
+        .unwrap_or_default();
+    if surplus < params.surplus_buffer + params.surplus_lot {
+        return Err(ContractError::InsufficientSurplusForAuction {});
+    }
+    // the lot is minted to the winner once settled
+    SURPLUS.save(
+        deps.storage,
+        config.dsc_address.to_string(),
+        &(surplus - params.surplus_lot),
+    )?;
+
+    let id = kick(
+        deps.storage,
+        &env,
+        &params,
+        BalanceSheetAuctionKind::Surplus,
+        params.surplus_lot,
+        Uint128::zero(),
+    )?;
+    let res = Response::new()
+        .add_attribute("action", "kick_surplus_auction")
+        .add_attribute("id", id.to_string())
+        .add_attribute("lot", params.surplus_lot);
+    Ok(res)
+}
+
+/// Raises a lot of the bad debt that the DSC surplus cannot settle by minting governance tokens
+pub fn kick_debt_auction(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    revert_if_shutdown(deps.storage)?;
+    let params = load_params(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    accrue_debt_rate(deps.storage, &env, &config)?;
+
+    // the DSC surplus settles bad debt before any governance token is minted for it
+    let bad_debt = BAD_DEBT.may_load(deps.storage)?.unwrap_or_default();
+    let debt_on_auction = DEBT_ON_AUCTION.may_load(deps.storage)?.unwrap_or_default();
+    let surplus = SURPLUS
+        .may_load(deps.storage, config.dsc_address.to_string())?
+        .unwrap_or_default();
+    let settled_by_surplus = bad_debt.saturating_sub(debt_on_auction).min(surplus);
+    let bad_debt = bad_debt - settled_by_surplus;
+    BAD_DEBT.save(deps.storage, &bad_debt)?;
+    SURPLUS.save(
+        deps.storage,
+        config.dsc_address.to_string(),
+        &(surplus - settled_by_surplus),
+    )?;
+    if bad_debt.saturating_sub(debt_on_auction) < params.debt_lot {
+        return Err(ContractError::InsufficientBadDebtForAuction {});
+    }
+    DEBT_ON_AUCTION.save(deps.storage, &(debt_on_auction + params.debt_lot))?;
+
+    let id = kick(
+        deps.storage,
+        &env,
+        &params,
+        BalanceSheetAuctionKind::Debt,
+        params.debt_initial_lot,
+        params.debt_lot,
+    )?;
+    let res = Response::new()
+        .add_attribute("action", "kick_debt_auction")
+        .add_attribute("id", id.to_string())
+        .add_attribute("lot", params.debt_initial_lot)
+        .add_attribute("bid", params.debt_lot)
+        .add_attribute("settled_by_surplus", settled_by_surplus);
+    Ok(res)
+}
+
+/// Bids the governance tokens `received` for the DSC lot of a surplus auction
+pub fn bid_surplus_auction(
+    deps: DepsMut,
+    env: Env,
+    bidder: Addr,
+    token: Addr,
+    received: Uint128,
+    id: u64,
+) -> Result<Response, ContractError> {
+    revert_if_shutdown(deps.storage)?;
+    let params = load_params(deps.storage)?;
+    if token != params.governance_token {
+        return Err(ContractError::InvalidCw20Token {
+            token: token.into_string(),
+        });
+    }
+    let mut auction = load_live_auction(deps.storage, &env, id, BalanceSheetAuctionKind::Surplus)?;
+    if received < min_increased(auction.bid, params.min_bid_increment) {
+        return Err(ContractError::BidNotImproved {});
+    }
+
+    let mut res = Response::new();
+    if let Some(previous_bidder) = &auction.bidder {
+        res = res.add_message(transfer_msg(
+            &AssetInfo::Cw20(params.governance_token.clone()),
+            auction.bid,
+            previous_bidder,
+        )?);
+    }
+    auction.bid = received;
+    save_bid(deps.storage, &env, &params, id, auction, &bidder)?;
+
+    res = res
+        .add_attribute("action", "bid_surplus_auction")
+        .add_attribute("id", id.to_string())
+        .add_attribute("from", bidder)
+        .add_attribute("bid", received);
+    Ok(res)
+}
+
+/// Pays the DSC bid of a debt auction for `lot` governance tokens. The first bid settles bad
+/// debt, later ones refund the previous bidder.
+pub fn bid_debt_auction(
+    deps: DepsMut,
+    env: Env,
+    bidder: Addr,
+    received: Uint128,
+    id: u64,
+    lot: Uint128,
+) -> Result<Response, ContractError> {
+    revert_if_shutdown(deps.storage)?;
+    let params = load_params(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut auction = load_live_auction(deps.storage, &env, id, BalanceSheetAuctionKind::Debt)?;
+    if received != auction.bid {
+        return Err(ContractError::InvalidDebtAuctionBid { bid: auction.bid });
+    }
+    if lot.is_zero() || min_increased(lot, params.min_bid_increment) > auction.lot {
+        return Err(ContractError::BidNotImproved {});
+    }
+
+    let mut res = Response::new();
+    match &auction.bidder {
+        Some(previous_bidder) => {
+            res = res.add_message(transfer_msg(
+                &AssetInfo::Cw20(config.dsc_address.clone()),
+                auction.bid,
+                previous_bidder,
+            )?);
+        }
+        None => {
+            // bad debt settled meanwhile leaves the DSC raised in the surplus
+            let bad_debt = BAD_DEBT.may_load(deps.storage)?.unwrap_or_default();
+            let settled = bad_debt.min(received);
+            BAD_DEBT.save(deps.storage, &(bad_debt - settled))?;
+            let debt_on_auction = DEBT_ON_AUCTION.may_load(deps.storage)?.unwrap_or_default();
+            DEBT_ON_AUCTION.save(deps.storage, &debt_on_auction.saturating_sub(received))?;
+            add_surplus(
+                deps.storage,
+                config.dsc_address.to_string(),
+                received - settled,
+            )?;
+            res = res.add_message(burn_msg(&config, received, &DscSource::Received)?);
+        }
+    }
+    auction.lot = lot;
+    save_bid(deps.storage, &env, &params, id, auction, &bidder)?;
+
+    res = res
+        .add_attribute("action", "bid_debt_auction")
+        .add_attribute("id", id.to_string())
+        .add_attribute("from", bidder)
+        .add_attribute("lot", lot);
+    Ok(res)
+}
+
+/// Closes an ended auction, paying the lot to the winner. Surplus auctions without bids give
+/// the lot back to the surplus, debt auctions without bids leave the debt to a new auction.
+pub fn settle_balance_sheet_auction(
+    deps: DepsMut,
+    env: Env,
+    id: u64,
+) -> Result<Response, ContractError> {
+    revert_if_shutdown(deps.storage)?;
+    let params = load_params(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let auction = BALANCE_SHEET_AUCTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::BalanceSheetAuctionNotFound { id })?;
+    if !auction.has_ended(env.block.time.seconds()) {
+        return Err(ContractError::BalanceSheetAuctionNotEnded { id });
+    }
+    BALANCE_SHEET_AUCTIONS.remove(deps.storage, id);
+
+    let mut res = Response::new()
+        .add_attribute("action", "settle_balance_sheet_auction")
+        .add_attribute("id", id.to_string());
+    match (&auction.kind, &auction.bidder) {
+        (BalanceSheetAuctionKind::Surplus, Some(winner)) => {
+            res = res
+                .add_message(mint_msg(&config.dsc_address, auction.lot, winner)?)
+                .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: params.governance_token.to_string(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                        amount: auction.bid,
+                    })?,
+                    funds: vec![],
+                }));
+        }
+        (BalanceSheetAuctionKind::Surplus, None) => {
+            add_surplus(deps.storage, config.dsc_address.to_string(), auction.lot)?;
+        }
+        (BalanceSheetAuctionKind::Debt, Some(winner)) => {
+            res = res.add_message(mint_msg(&params.governance_token, auction.lot, winner)?);
+        }
+        (BalanceSheetAuctionKind::Debt, None) => {
+            let debt_on_auction = DEBT_ON_AUCTION.may_load(deps.storage)?.unwrap_or_default();
+            DEBT_ON_AUCTION.save(deps.storage, &debt_on_auction.saturating_sub(auction.bid))?;
+        }
+    }
+    if let Some(winner) = auction.bidder {
+        res = res
+            .add_attribute("winner", winner)
+            .add_attribute("lot", auction.lot)
+            .add_attribute("bid", auction.bid);
+    }
+    Ok(res)
+}
+
+/// Closes an auction once the protocol is shut down, refunding its bidder. The surplus lot goes
+/// back to the surplus, and the DSC of a debt auction bidder is minted back, its debt being bad
+/// debt again.
+pub fn yank_balance_sheet_auction(deps: DepsMut, id: u64) -> Result<Response, ContractError> {
+    if GLOBAL_SETTLEMENT.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::ContractNotShutdown {});
+    }
+    let params = load_params(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let auction = BALANCE_SHEET_AUCTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::BalanceSheetAuctionNotFound { id })?;
+    BALANCE_SHEET_AUCTIONS.remove(deps.storage, id);
+
+    let mut res = Response::new()
+        .add_attribute("action", "yank_balance_sheet_auction")
+        .add_attribute("id", id.to_string());
+    match (&auction.kind, &auction.bidder) {
+        (BalanceSheetAuctionKind::Surplus, bidder) => {
+            add_surplus(deps.storage, config.dsc_address.to_string(), auction.lot)?;
+            if let Some(bidder) = bidder {
+                res = res.add_message(transfer_msg(
+                    &AssetInfo::Cw20(params.governance_token),
+                    auction.bid,
+                    bidder,
+                )?);
+            }
+        }
+        (BalanceSheetAuctionKind::Debt, Some(bidder)) => {
+            // the first bid settled bad debt with DSC that was burnt
+            let bad_debt = BAD_DEBT.may_load(deps.storage)?.unwrap_or_default();
+            BAD_DEBT.save(deps.storage, &(bad_debt + auction.bid))?;
+            res = res.add_message(mint_msg(&config.dsc_address, auction.bid, bidder)?);
+        }
+        (BalanceSheetAuctionKind::Debt, None) => {
+            let debt_on_auction = DEBT_ON_AUCTION.may_load(deps.storage)?.unwrap_or_default();
+            DEBT_ON_AUCTION.save(deps.storage, &debt_on_auction.saturating_sub(auction.bid))?;
+        }
+    }
+    if let Some(bidder) = auction.bidder {
+        res = res
+            .add_attribute("refunded", bidder)
+            .add_attribute("bid", auction.bid);
+    }
+    Ok(res)
+}
+
+fn load_params(storage: &dyn Storage) -> Result<BalanceSheetAuctionParams, ContractError> {
+    BALANCE_SHEET_AUCTION_PARAMS
+        .may_load(storage)?
+        .ok_or(ContractError::BalanceSheetAuctionsDisabled {})
+}
+
+fn kick(
+    storage: &mut dyn Storage,
+    env: &Env,
+    params: &BalanceSheetAuctionParams,
+    kind: BalanceSheetAuctionKind,
+    lot: Uint128,
+    bid: Uint128,
+) -> StdResult<u64> {
+    let id = NEXT_BALANCE_SHEET_AUCTION_ID
+        .may_load(storage)?
+        .unwrap_or(1);
+    NEXT_BALANCE_SHEET_AUCTION_ID.save(storage, &(id + 1))?;
+    let auction = BalanceSheetAuction {
+        kind,
+        lot,
+        bid,
+        bidder: None,
+        bid_expiry: None,
+        end: env.block.time.seconds() + params.auction_duration,
+    };
+    BALANCE_SHEET_AUCTIONS.save(storage, id, &auction)?;
+    Ok(id)
+}
+
+/// Auction `id` of `kind` that still accepts bids
+fn load_live_auction(
+    storage: &dyn Storage,
+    env: &Env,
+    id: u64,
+    kind: BalanceSheetAuctionKind,
+) -> Result<BalanceSheetAuction, ContractError> {
+    let auction = BALANCE_SHEET_AUCTIONS
+        .may_load(storage, id)?
+        .filter(|auction| auction.kind == kind)
+        .ok_or(ContractError::BalanceSheetAuctionNotFound { id })?;
+    if auction.has_ended(env.block.time.seconds()) {
+        return Err(ContractError::BalanceSheetAuctionEnded { id });
+    }
+    Ok(auction)
+}
+
+/// Records `bidder` as the winner, who wins once bid_duration passes without a better bid
+fn save_bid(
+    storage: &mut dyn Storage,
+    env: &Env,
+    params: &BalanceSheetAuctionParams,
+    id: u64,
+    mut auction: BalanceSheetAuction,
+    bidder: &Addr,
+) -> StdResult<()> {
+    auction.bidder = Some(bidder.clone());
+    auction.bid_expiry = Some(env.block.time.seconds() + params.bid_duration);
+    BALANCE_SHEET_AUCTIONS.save(storage, id, &auction)
+}
+
+/// `amount` increased by the min bid increment, rounded up
+fn min_increased(amount: Uint128, min_bid_increment: Decimal) -> Uint128 {
+    amount.mul_ceil(Decimal::one() + min_bid_increment)
+}
+
+fn mint_msg(token: &Addr, amount: Uint128, recipient: &Addr) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }))
+}
@@ -1,72 +1,166 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Empty, Env, MessageInfo,
-    QuerierWrapper, QueryRequest, Response, StdResult, Uint128, WasmMsg, WasmQuery,
+    from_json, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Empty, Env, MessageInfo, Order, QuerierWrapper, Response, StdResult, Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
-use cw20::Cw20ExecuteMsg;
-use cw_asset::AssetInfo;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw_asset::{Asset, AssetInfo};
+use cw_utils::Expiration;
 
+use crate::auctions;
 use crate::error::ContractError;
-use crate::msg::{AccountInfoResponse, ExecuteMsg, InstantiateMsg};
-use crate::state::{Config, COLLATERAL_DEPOSITED, CONFIG, DSC_MINTED};
-use oracle::msg::{FetchPriceResponse, QueryMsg as OracleQueryMsg};
-use pyth_sdk_cw::PriceIdentifier;
+use crate::migrations::migrate_state;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, ReceiveMsg};
+use crate::queries::{self, PriceFreshness};
+use crate::state::{
+    add_surplus, load_collateral_params, load_debt_rate, load_redemption_rate,
+    reduce_collateral_debt, set_collateral_debt, update_redemption_index, Auction,
+    CollateralParams, Config, DebtRate, GlobalSettlement, LiquidationMode, OwnershipProposal,
+    PauseFlags, RedemptionRate, AUCTIONS, BAD_DEBT, COLLATERAL_DEBT, COLLATERAL_DEPOSITED,
+    COLLATERAL_PARAMS, CONFIG, DEBT_RATE, DSC_MINTED, GLOBAL_SETTLEMENT, NEXT_AUCTION_ID,
+    OWNERSHIP_PROPOSAL, PAUSE_FLAGS, REDEMPTION_BETA, REDEMPTION_INDEX, REDEMPTION_RATE,
+    SETTLEMENT_COLLATERAL, SURPLUS, TOTAL_COLLATERAL_DEBT, TOTAL_COLLATERAL_DEPOSITED,
+    TOTAL_NORMALIZED_DEBT,
+};
+use pyth_sdk_cw::{get_update_fee, ExecuteMsg as PythExecuteMsg};
+use semver::Version;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "dsc-engine";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Decimals of the DSC cw20 token
+pub const DSC_DECIMALS: u32 = 6;
+
+/// Max positions a single redemption draws collateral from
+pub const MAX_REDEMPTION_POSITIONS: usize = 50;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    if msg.assets.len() != msg.price_feed_ids.len() {
-        return Err(ContractError::TokenAddressesAndPriceFeedIdsLengthsDontMatch {});
+    exec::validate_risk_params(
+        msg.liquidation_threshold,
+        msg.liquidation_bonus,
+        msg.min_health_factor,
+    )?;
+    exec::validate_stability_fee(msg.stability_fee)?;
+    exec::validate_liquidation_limits(
+        msg.close_factor,
+        msg.full_liquidation_health_factor,
+        msg.min_health_factor,
+    )?;
+    exec::validate_protocol_fees(
+        msg.liquidation_fee_share,
+        msg.stability_fee_share,
+        msg.origination_fee,
+        msg.redemption_fee_floor,
+    )?;
+    let mut assets_decimals = HashMap::new();
+    for asset in &msg.assets {
+        let decimals = exec::query_asset_decimals(
+            &deps.querier,
+            asset,
+            msg.native_assets_decimals.get(&asset.inner()).copied(),
+        )?;
+        assets_decimals.insert(asset.inner(), decimals);
     }
-    let assets_to_feeds = msg
-        .assets
-        .clone()
-        .into_iter()
-        .map(|asset| asset.inner())
-        .zip(msg.price_feed_ids.into_iter())
-        .collect();
 
     let config = Config {
-        owner: deps.api.addr_validate(&msg.owner)?,
+        owner: Some(deps.api.addr_validate(&msg.owner)?),
+        guardian: None,
         assets: msg.assets,
-        assets_to_feeds: assets_to_feeds,
+        assets_to_feeds: HashMap::new(),
+        assets_decimals,
         oracle_address: deps.api.addr_validate(&msg.oracle_address)?,
-        pyth_oracle_address: deps.api.addr_validate(&msg.pyth_oracle_address)?,
+        pyth_oracle_address: None,
         dsc_address: deps.api.addr_validate(&msg.dsc_address)?,
         liquidation_threshold: msg.liquidation_threshold,
         liquidation_bonus: msg.liquidation_bonus,
         min_health_factor: msg.min_health_factor,
+        stability_fee: msg.stability_fee,
+        close_factor: msg.close_factor,
+        full_liquidation_health_factor: msg.full_liquidation_health_factor,
+        liquidation_dust: msg.liquidation_dust,
+        liquidation_fee_share: msg.liquidation_fee_share,
+        stability_fee_share: msg.stability_fee_share,
+        origination_fee: msg.origination_fee,
+        redemption_fee_floor: msg.redemption_fee_floor,
     };
 
+    for asset in &config.assets {
+        COLLATERAL_PARAMS.save(
+            deps.storage,
+            asset.inner(),
+            &config.default_collateral_params(),
+        )?;
+    }
     CONFIG.save(deps.storage, &config)?;
+    DEBT_RATE.save(
+        deps.storage,
+        &DebtRate {
+            rate: Decimal::one(),
+            last_accrual: env.block.time.seconds(),
+        },
+    )?;
+    TOTAL_NORMALIZED_DEBT.save(deps.storage, &Uint128::zero())?;
 
     Ok(Response::default())
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract,
+        });
+    }
+    let stored_version: Version = stored.version.parse()?;
+    let new_version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > new_version {
+        return Err(ContractError::CannotDowngrade {
+            stored_version: stored.version,
+            new_version: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    migrate_state(deps.branch(), &env, &stored_version)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("previous_version", stored.version)
+        .add_attribute("new_version", CONTRACT_VERSION);
+    Ok(res)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: ExecuteMsg,
+    mut msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // prices read by the action must be updated first, it runs in a callback once they are
+    if let Some(price_update_data) = msg.take_price_update_data() {
+        return exec::update_prices_and_execute(deps, env, info, price_update_data, msg);
+    }
     match msg {
         ExecuteMsg::DepositCollateralAndMintDsc {
             collateral_asset,
             amount_collateral,
             amount_dsc_to_mint,
+            ..
         } => exec::deposit_collateral_and_mint_dsc(
             deps,
             env,
@@ -75,10 +169,21 @@ pub fn execute(
             amount_collateral,
             amount_dsc_to_mint,
         ),
+        ExecuteMsg::DepositCollateral {
+            collateral_asset,
+            amount_collateral,
+        } => exec::deposit_collateral(deps, env, info, collateral_asset, amount_collateral),
+        ExecuteMsg::DepositCollaterals { assets } => {
+            exec::deposit_collaterals(deps, env, info, assets)
+        }
+        ExecuteMsg::MintDsc {
+            amount_dsc_to_mint, ..
+        } => exec::mint_dsc(deps, env, info, amount_dsc_to_mint),
         ExecuteMsg::RedeemCollateralForDsc {
             collateral_asset,
             amount_collateral,
             amount_dsc_to_burn,
+            ..
         } => exec::redeem_collateral_for_dsc(
             deps,
             env,
@@ -93,123 +198,419 @@ pub fn execute(
         ExecuteMsg::RedeemCollateral {
             collateral_asset,
             amount_collateral,
-        } => exec::redeem_collateral(deps, info, collateral_asset, amount_collateral),
+            ..
+        } => exec::redeem_collateral(deps, env, info, collateral_asset, amount_collateral),
         ExecuteMsg::Liquidate {
             collateral_asset,
             user,
             debt_to_cover,
-        } => exec::liquidate(deps, env, info, collateral_asset, user, debt_to_cover),
+            ..
+        } => exec::liquidate(
+            deps,
+            env,
+            info.sender.clone(),
+            exec::DscSource::Allowance(info.sender),
+            collateral_asset,
+            user,
+            debt_to_cover,
+        ),
+        ExecuteMsg::RedeemDsc {
+            amount,
+            collateral_asset,
+            max_fee,
+            ..
+        } => exec::redeem_dsc(deps, env, info, amount, collateral_asset, max_fee),
+        ExecuteMsg::KickAuction {
+            user,
+            collateral_asset,
+        } => exec::kick_auction(deps, env, user, collateral_asset),
+        ExecuteMsg::TakeAuction {
+            id,
+            max_collateral,
+            max_price,
+        } => exec::take_auction(deps, env, info, id, max_collateral, max_price),
+        ExecuteMsg::ResetAuction { id } => exec::reset_auction(deps, env, id),
+        ExecuteMsg::SettleBadDebt {} => exec::settle_bad_debt(deps, env, None),
+        ExecuteMsg::UpdateBalanceSheetAuctionParams { params } => {
+            auctions::update_balance_sheet_auction_params(deps, info, params)
+        }
+        ExecuteMsg::KickSurplusAuction {} => auctions::kick_surplus_auction(deps, env),
+        ExecuteMsg::KickDebtAuction {} => auctions::kick_debt_auction(deps, env),
+        ExecuteMsg::SettleBalanceSheetAuction { id } => {
+            auctions::settle_balance_sheet_auction(deps, env, id)
+        }
+        ExecuteMsg::YankBalanceSheetAuction { id } => {
+            auctions::yank_balance_sheet_auction(deps, id)
+        }
+        ExecuteMsg::Receive(cw20_msg) => exec::receive_cw20(deps, env, info, cw20_msg),
+        ExecuteMsg::AfterPriceUpdate { sender, msg } => {
+            exec::after_price_update(deps, env, info, sender, *msg)
+        }
+        ExecuteMsg::UpdateConfig {
+            oracle_address,
+            liquidation_threshold,
+            liquidation_bonus,
+            min_health_factor,
+            stability_fee,
+            close_factor,
+            full_liquidation_health_factor,
+            liquidation_dust,
+            liquidation_fee_share,
+            stability_fee_share,
+            origination_fee,
+            redemption_fee_floor,
+        } => exec::update_config(
+            deps,
+            env,
+            info,
+            oracle_address,
+            liquidation_threshold,
+            liquidation_bonus,
+            min_health_factor,
+            stability_fee,
+            close_factor,
+            full_liquidation_health_factor,
+            liquidation_dust,
+            liquidation_fee_share,
+            stability_fee_share,
+            origination_fee,
+            redemption_fee_floor,
+        ),
+        ExecuteMsg::AddCollateralAsset {
+            asset,
+            decimals,
+            params,
+        } => exec::add_collateral_asset(deps, info, asset, decimals, params),
+        ExecuteMsg::UpdateCollateralParams { asset, params } => {
+            exec::update_collateral_params(deps, info, asset, params)
+        }
+        ExecuteMsg::RemoveCollateralAsset { asset } => {
+            exec::remove_collateral_asset(deps, info, asset)
+        }
+        ExecuteMsg::Drip {} => exec::drip(deps, env),
+        ExecuteMsg::WithdrawSurplus { amount, recipient } => {
+            exec::withdraw_surplus(deps, env, info, amount, recipient)
+        }
+        ExecuteMsg::UpdateGuardian { guardian } => exec::update_guardian(deps, info, guardian),
+        ExecuteMsg::UpdatePauseFlags {
+            mint,
+            deposit,
+            redeem,
+            liquidate,
+        } => exec::update_pause_flags(deps, info, mint, deposit, redeem, liquidate),
+        ExecuteMsg::Shutdown {} => exec::shutdown(deps, env, info),
+        ExecuteMsg::SettlePosition { user } => exec::settle_position(deps, env, user),
+        ExecuteMsg::YankAuction { id } => exec::yank_auction(deps, env, id),
+        ExecuteMsg::RedeemAfterShutdown { amount } => {
+            exec::redeem_after_shutdown(deps, info, amount)
+        }
+        ExecuteMsg::ProposeNewOwner { owner, expiry } => {
+            exec::propose_new_owner(deps, env, info, owner, expiry)
+        }
+        ExecuteMsg::AcceptOwnership {} => exec::accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => exec::cancel_ownership_proposal(deps, info),
+        ExecuteMsg::RenounceOwnership {} => exec::renounce_ownership(deps, info),
     }
 }
 
-mod exec {
+pub(crate) mod exec {
 
     use cosmwasm_std::Storage;
 
     use super::*;
 
-    pub fn deposit_collateral_and_mint_dsc(
-        deps: DepsMut,
+    /// Where the DSC burnt to repay a debt comes from
+    pub enum DscSource {
+        /// Burnt from this address, DSC Engine must be allowed to spend it
+        Allowance(Addr),
+        /// Already sent to DSC Engine through a cw20 `Send`
+        Received,
+    }
+
+    pub fn deposit_collateral(
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
         collateral_asset: AssetInfo,
         amount_collateral: Uint128,
-        amount_dsc_to_mint: Uint128,
     ) -> Result<Response, ContractError> {
-        // CHECK IF COLLATERAL ASSET IS VALID
-        let config = CONFIG.load(deps.storage)?;
-        if !config
-            .assets_to_feeds
-            .contains_key(&collateral_asset.inner())
-        {
-            return Err(ContractError::InvalidCollateralAsset {
-                denom: collateral_asset.inner(),
-            });
-        }
+        let messages = _deposit_collateral_from_sender(
+            &mut deps,
+            &env,
+            &info,
+            &collateral_asset,
+            amount_collateral,
+        )?;
 
-        // TRANSFER COLLATERAL FROM USER TO CONTRACT
-        // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
-        // If the asset is native token, the pool balance is already increased
-        let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
-        if let AssetInfo::Cw20(contract_addr) = &collateral_asset {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: contract_addr.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: env.contract.address.to_string(),
-                    amount: amount_collateral,
-                })?,
-                funds: vec![],
-            }));
-        } else {
-            if info.funds.len() == 0
-                || info.funds[0].denom != collateral_asset.inner()
-                || info.funds[0].amount != amount_collateral
-            {
-                return Err(ContractError::MissingNativeFunds {
-                    denom: collateral_asset.inner(),
-                });
-            }
-        };
+        let res = Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "deposit_collateral")
+            .add_attribute("from", info.sender)
+            .add_attribute("asset", collateral_asset.inner())
+            .add_attribute("amount", amount_collateral);
+        Ok(res)
+    }
 
-        COLLATERAL_DEPOSITED.update(
-            deps.storage,
-            (&info.sender, collateral_asset.inner()),
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default() + amount_collateral)
-            },
-        )?;
+    pub fn mint_dsc(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount_dsc_to_mint: Uint128,
+    ) -> Result<Response, ContractError> {
+        let mint_dsc_msg =
+            _mint_against_collateral(&mut deps, &env, &info.sender, amount_dsc_to_mint)?;
 
-        // MINT DSC TO USER
-        // NOTE: DSC Engine must be declared as minter on DSC CW20 intantiation
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.dsc_address.into_string(),
-            msg: to_json_binary(&Cw20ExecuteMsg::Mint {
-                recipient: info.sender.to_string(),
-                amount: amount_dsc_to_mint,
-            })?,
-            funds: vec![],
-        }));
+        let res = Response::new()
+            .add_message(mint_dsc_msg)
+            .add_attribute("action", "mint_dsc")
+            .add_attribute("to", info.sender)
+            .add_attribute("amount", amount_dsc_to_mint);
+        Ok(res)
+    }
 
-        DSC_MINTED.update(
-            deps.storage,
-            &info.sender,
-            env.block.height,
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default() + amount_dsc_to_mint)
-            },
+    pub fn deposit_collateral_and_mint_dsc(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        collateral_asset: AssetInfo,
+        amount_collateral: Uint128,
+        amount_dsc_to_mint: Uint128,
+    ) -> Result<Response, ContractError> {
+        let mut messages = _deposit_collateral_from_sender(
+            &mut deps,
+            &env,
+            &info,
+            &collateral_asset,
+            amount_collateral,
         )?;
 
-        // VERIFY NEW USER HEALTH FACTOR
-        revert_if_health_factor_is_broken(&deps, &info.sender)?;
+        // MINT DSC TO USER
+        let mint_dsc_msg =
+            _mint_against_collateral(&mut deps, &env, &info.sender, amount_dsc_to_mint)?;
+        messages.push(mint_dsc_msg);
 
         let res = Response::new()
             .add_messages(messages)
             .add_attribute("action", "deposit_collateral")
-            .add_attribute("from", info.sender)
+            .add_attribute("from", &info.sender)
             .add_attribute("asset", collateral_asset.inner())
-            .add_attribute("amount", amount_collateral);
+            .add_attribute("amount", amount_collateral)
+            .add_attribute("action", "mint_dsc")
+            .add_attribute("to", info.sender)
+            .add_attribute("amount", amount_dsc_to_mint);
         Ok(res)
     }
 
-    pub fn redeem_collateral_for_dsc(
+    pub fn deposit_collaterals(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        assets: Vec<Asset>,
+    ) -> Result<Response, ContractError> {
+        if assets.is_empty() {
+            return Err(ContractError::EmptyCollateralList {});
+        }
+        let messages = _deposit_collaterals_from_sender(&mut deps, &env, &info, &assets)?;
+
+        let mut res = Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "deposit_collaterals")
+            .add_attribute("from", info.sender);
+        for collateral in assets {
+            res = res.add_attribute("asset", collateral.to_string());
+        }
+        Ok(res)
+    }
+
+    /// Forwards Pyth price updates paying their fee from the funds sent, then calls back DSC
+    /// Engine with the rest of the funds to run the action once the prices are fresh
+    pub fn update_prices_and_execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        price_update_data: Vec<Binary>,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let pyth_contract_addr = queries::get_pyth_contract(&deps.as_ref(), &config)?;
+        let update_fee = get_update_fee(
+            &deps.querier,
+            pyth_contract_addr.clone(),
+            &price_update_data,
+        )?;
+        let funds = deduct_update_fee(info.funds, &update_fee)?;
+
+        let update_msg = WasmMsg::Execute {
+            contract_addr: pyth_contract_addr.to_string(),
+            msg: to_json_binary(&PythExecuteMsg::UpdatePriceFeeds {
+                data: price_update_data,
+            })?,
+            funds: if update_fee.amount.is_zero() {
+                vec![]
+            } else {
+                vec![update_fee.clone()]
+            },
+        };
+        let callback_msg = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_json_binary(&ExecuteMsg::AfterPriceUpdate {
+                sender: info.sender,
+                msg: Box::new(msg),
+            })?,
+            funds,
+        };
+        let res = Response::new()
+            .add_message(update_msg)
+            .add_message(callback_msg)
+            .add_attribute("action", "update_price_feeds")
+            .add_attribute("update_fee", update_fee.to_string());
+        Ok(res)
+    }
+
+    /// Runs an action for its original sender, called by DSC Engine itself once the Pyth prices
+    /// sent with the action are updated
+    pub fn after_price_update(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
+        sender: Addr,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        if info.sender != env.contract.address {
+            return Err(ContractError::Unauthorized {});
+        }
+        let info = MessageInfo {
+            sender,
+            funds: info.funds,
+        };
+        execute(deps, env, info, msg)
+    }
+
+    /// Handles collateral or DSC sent through a cw20 `Send`
+    pub fn receive_cw20(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        cw20_msg: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+        let received_asset = AssetInfo::Cw20(info.sender.clone());
+
+        match from_json(&cw20_msg.msg)? {
+            ReceiveMsg::DepositCollateral {} => {
+                _deposit_collateral(&mut deps, &sender, &received_asset, cw20_msg.amount)?;
+                let res = Response::new()
+                    .add_attribute("action", "deposit_collateral")
+                    .add_attribute("from", sender)
+                    .add_attribute("asset", received_asset.inner())
+                    .add_attribute("amount", cw20_msg.amount);
+                Ok(res)
+            }
+            ReceiveMsg::DepositCollateralAndMint { amount_dsc_to_mint } => {
+                _deposit_collateral(&mut deps, &sender, &received_asset, cw20_msg.amount)?;
+                let mint_dsc_msg =
+                    _mint_against_collateral(&mut deps, &env, &sender, amount_dsc_to_mint)?;
+                let res = Response::new()
+                    .add_message(mint_dsc_msg)
+                    .add_attribute("action", "deposit_collateral")
+                    .add_attribute("from", sender)
+                    .add_attribute("asset", received_asset.inner())
+                    .add_attribute("amount", cw20_msg.amount);
+                Ok(res)
+            }
+            ReceiveMsg::RepayDebt { on_behalf_of } => {
+                if info.sender != config.dsc_address {
+                    return Err(ContractError::InvalidCw20Token {
+                        token: info.sender.into_string(),
+                    });
+                }
+                let on_behalf_of = match on_behalf_of {
+                    Some(on_behalf_of) => deps.api.addr_validate(&on_behalf_of)?,
+                    None => sender,
+                };
+                repay_debt(deps, env, on_behalf_of, cw20_msg.amount)
+            }
+            ReceiveMsg::Liquidate {
+                user,
+                collateral_asset,
+            } => {
+                if info.sender != config.dsc_address {
+                    return Err(ContractError::InvalidCw20Token {
+                        token: info.sender.into_string(),
+                    });
+                }
+                liquidate(
+                    deps,
+                    env,
+                    sender,
+                    DscSource::Received,
+                    collateral_asset,
+                    user,
+                    Decimal::from_atomics(cw20_msg.amount, DSC_DECIMALS)?,
+                )
+            }
+            ReceiveMsg::DepositSurplus {} => {
+                if info.sender != config.dsc_address {
+                    return Err(ContractError::InvalidCw20Token {
+                        token: info.sender.into_string(),
+                    });
+                }
+                // DSC surplus is only minted once withdrawn
+                add_surplus(
+                    deps.storage,
+                    config.dsc_address.to_string(),
+                    cw20_msg.amount,
+                )?;
+                let res = Response::new()
+                    .add_message(burn_msg(&config, cw20_msg.amount, &DscSource::Received)?)
+                    .add_attribute("action", "deposit_surplus")
+                    .add_attribute("from", sender)
+                    .add_attribute("amount", cw20_msg.amount);
+                Ok(res)
+            }
+            ReceiveMsg::SettleBadDebt {} => {
+                if info.sender != config.dsc_address {
+                    return Err(ContractError::InvalidCw20Token {
+                        token: info.sender.into_string(),
+                    });
+                }
+                settle_bad_debt(deps, env, Some((sender, cw20_msg.amount)))
+            }
+            ReceiveMsg::BidSurplusAuction { id } => {
+                auctions::bid_surplus_auction(deps, env, sender, info.sender, cw20_msg.amount, id)
+            }
+            ReceiveMsg::BidDebtAuction { id, lot } => {
+                if info.sender != config.dsc_address {
+                    return Err(ContractError::InvalidCw20Token {
+                        token: info.sender.into_string(),
+                    });
+                }
+                auctions::bid_debt_auction(deps, env, sender, cw20_msg.amount, id, lot)
+            }
+        }
+    }
+
+    pub fn redeem_collateral_for_dsc(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
         collateral_asset: AssetInfo,
         amount_collateral: Uint128,
         amount_dsc_to_burn: Uint128,
     ) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.redeem, "redeem")?;
+
         let config = CONFIG.load(deps.storage)?;
-        if !config
-            .assets_to_feeds
-            .contains_key(&collateral_asset.inner())
-        {
+        if !config.is_collateral_asset(&collateral_asset.inner()) {
             return Err(ContractError::InvalidCollateralAsset {
                 denom: collateral_asset.inner(),
             });
         }
 
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
         let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
 
         // BURN DSC
@@ -217,9 +618,11 @@ mod exec {
         let burn_dsc_msg = _burn_dsc(
             deps.storage,
             &env,
+            &debt_rate,
             amount_dsc_to_burn,
             &info.sender,
-            &info.sender,
+            &DscSource::Allowance(info.sender.clone()),
+            None,
         )?;
         messages.push(burn_dsc_msg);
 
@@ -233,8 +636,8 @@ mod exec {
         )?;
         messages.push(redeem_collateral_msg);
 
-        // VERIFY NEW USER HEALTH FACTOR
-        revert_if_health_factor_is_broken(&deps, &info.sender)?;
+        // VERIFY NEW USER HEALTH FACTOR AND BORROWING POWER
+        reattribute_debt_and_revert_if_unsafe(&mut deps, &env, &config, &debt_rate, &info.sender)?;
 
         let res = Response::new()
             .add_messages(messages)
@@ -247,73 +650,152 @@ mod exec {
     }
 
     pub fn liquidate(
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
-        info: MessageInfo,
+        liquidator: Addr,
+        dsc_source: DscSource,
         collateral_asset: AssetInfo,
         user: String,
         debt_to_cover: Decimal, // usd value
     ) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.liquidate, "liquidate")?;
+
         let config = CONFIG.load(deps.storage)?;
-        let collateral_token_decimals_precision = 6; // TODO: dynamic
-        let decimal_liquidation_bonus_precision =
-            Decimal::from_atomics(config.liquidation_bonus, 2)?;
-        let starting_user_health_factor = get_health_factor(&deps, user.to_string())?;
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        let collateral_token_decimals = config.asset_decimals(&collateral_asset.inner());
+        let collateral_params =
+            load_collateral_params(deps.storage, &config, &collateral_asset.inner())?;
+        if let LiquidationMode::Auction(_) = collateral_params.liquidation_mode {
+            return Err(ContractError::AuctionLiquidationOnly {
+                denom: collateral_asset.inner(),
+            });
+        }
+        let decimal_liquidation_bonus_precision = collateral_params.liquidation_bonus_ratio();
+        let user_addr = &deps.api.addr_validate(&user)?;
+
+        // every price needed by the liquidation is fetched once
+        let prices =
+            get_liquidation_prices(&deps, &config, user_addr, &liquidator, &collateral_asset)?;
+        let total_dsc_minted = queries::get_dsc_minted(&deps.as_ref(), &env, user_addr.as_str())?;
+        let balances = queries::get_collateral_balances(&deps.as_ref(), &config, user_addr)?;
+        let account_value = queries::value_collateral(&deps.as_ref(), &config, &balances, &prices)?;
+        let starting_user_health_factor =
+            queries::health_factor(total_dsc_minted, account_value.liquidation_value)?;
         if starting_user_health_factor >= config.min_health_factor {
             return Err(ContractError::HealthFactorOk {});
         }
-        let token_amount_from_debt_covered =
-            _get_token_amount_from_usd(&deps, collateral_asset.inner(), debt_to_cover)?;
-        let bonus_collateral = token_amount_from_debt_covered * decimal_liquidation_bonus_precision;
-        let collateral_to_redeem = token_amount_from_debt_covered + bonus_collateral;
-        let precision_adjusted_collateral_to_redeem = collateral_to_redeem
-            .checked_mul(Decimal::from_atomics(
-                10_u32.pow(collateral_token_decimals_precision as u32),
-                0,
-            )?)?
-            .floor()
-            .to_string()
-            .parse::<Uint128>()?;
+        let underwater = queries::is_underwater(
+            account_value.total,
+            total_dsc_minted,
+            decimal_liquidation_bonus_precision,
+        )?;
+        let max_debt_to_cover = queries::max_liquidatable_debt(
+            &config,
+            total_dsc_minted,
+            starting_user_health_factor,
+            underwater,
+        )?;
+        if debt_to_cover > max_debt_to_cover {
+            return Err(ContractError::DebtToCoverExceedsMax { max_debt_to_cover });
+        }
+        let price = queries::price_of(&prices, &collateral_asset.inner())?
+            .debt_price(&collateral_params.pricing_policy);
+        let mut token_amount_from_debt_covered = debt_to_cover / price;
+        let mut bonus_collateral =
+            token_amount_from_debt_covered * decimal_liquidation_bonus_precision;
+        let mut collateral_to_redeem = token_amount_from_debt_covered + bonus_collateral;
+        let mut precision_adjusted_collateral_to_redeem =
+            to_atomics(collateral_to_redeem, collateral_token_decimals);
+        let mut precision_adjusted_debt_to_cover = to_atomics(debt_to_cover, DSC_DECIMALS);
+        let balance = balances
+            .iter()
+            .find(|(denom, _)| *denom == collateral_asset.inner())
+            .map(|(_, balance)| *balance)
+            .unwrap_or_default();
+        if underwater && precision_adjusted_collateral_to_redeem > balance {
+            // all the collateral left is seized, for the part of the debt it is worth
+            precision_adjusted_collateral_to_redeem = balance;
+            collateral_to_redeem = Decimal::from_atomics(balance, collateral_token_decimals)?;
+            token_amount_from_debt_covered =
+                collateral_to_redeem / (Decimal::one() + decimal_liquidation_bonus_precision);
+            bonus_collateral = collateral_to_redeem - token_amount_from_debt_covered;
+            precision_adjusted_debt_to_cover = to_atomics(
+                token_amount_from_debt_covered.checked_mul(price)?,
+                DSC_DECIMALS,
+            );
+        }
 
-        let user_addr = &deps.api.addr_validate(&user)?;
         let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
 
-        // REDEEM COLLATERAL
+        // REDEEM COLLATERAL, the protocol keeps its share of the bonus
+        let protocol_fee = to_atomics(
+            bonus_collateral.checked_mul(config.liquidation_fee_share)?,
+            collateral_token_decimals,
+        );
         let redeem_collateral_msg = _redeem_collateral(
             deps.storage,
             &collateral_asset,
-            precision_adjusted_collateral_to_redeem,
+            precision_adjusted_collateral_to_redeem - protocol_fee,
             user_addr,
-            &info.sender,
+            &liquidator,
         )?;
         messages.push(redeem_collateral_msg);
+        if !protocol_fee.is_zero() {
+            _remove_collateral(
+                deps.storage,
+                &collateral_asset.inner(),
+                protocol_fee,
+                user_addr,
+            )?;
+            add_surplus(deps.storage, collateral_asset.inner(), protocol_fee)?;
+        }
 
         // BURN DSC
-        let dsc_token_decimals = 6;
-        let precision_adjusted_debt_to_cover = debt_to_cover
-            .checked_mul(Decimal::from_atomics(
-                10_u32.pow(dsc_token_decimals as u32),
-                0,
-            )?)?
-            .floor()
-            .to_string()
-            .parse::<Uint128>()?;
         let burn_dsc_msg = _burn_dsc(
             deps.storage,
             &env,
+            &debt_rate,
             precision_adjusted_debt_to_cover,
             user_addr,
-            &info.sender,
+            &dsc_source,
+            Some(&collateral_asset.inner()),
         )?;
         messages.push(burn_dsc_msg);
+        if let DscSource::Received = dsc_source {
+            // DSC sent beyond the debt covered goes back to the liquidator
+            let refund = to_atomics(debt_to_cover, DSC_DECIMALS) - precision_adjusted_debt_to_cover;
+            if !refund.is_zero() {
+                messages.push(transfer_msg(
+                    &AssetInfo::Cw20(config.dsc_address.clone()),
+                    refund,
+                    &liquidator,
+                )?);
+            }
+        }
 
-        let ending_user_health_factor = get_health_factor(&deps, user.clone())?;
+        let mut bad_debt = Uint128::zero();
+        if underwater {
+            // selling collateral of an underwater position cannot improve its health factor
+            if queries::get_collateral_balances(&deps.as_ref(), &config, user_addr)?.is_empty() {
+                bad_debt = queries::get_dsc_minted(&deps.as_ref(), &env, user_addr.as_str())?;
+                _reduce_debt(deps.storage, &env, &debt_rate, bad_debt, user_addr, None)?;
+                _add_bad_debt(deps.storage, bad_debt)?;
+            }
+        }
+        resplit_debt(&mut deps, &config, user_addr, &prices)?;
+        let ending_user_health_factor =
+            queries::get_health_factor_with_prices(&deps.as_ref(), &env, user_addr, &prices)?;
 
-        if ending_user_health_factor <= starting_user_health_factor {
+        if !underwater && ending_user_health_factor <= starting_user_health_factor {
             return Err(ContractError::HealthFactorNotImproved {});
         }
 
-        revert_if_health_factor_is_broken(&deps, &info.sender)?;
+        revert_if_below_min_health_factor(
+            deps.storage,
+            queries::get_health_factor_with_prices(&deps.as_ref(), &env, &liquidator, &prices)?,
+        )?;
 
         let res = Response::new()
             .add_messages(messages)
@@ -322,7 +804,7 @@ mod exec {
             .add_attribute("asset", collateral_asset.inner())
             .add_attribute("amount", collateral_to_redeem.to_string())
             .add_attribute("action", "liquidate")
-            .add_attribute("from", &info.sender)
+            .add_attribute("from", &liquidator)
             .add_attribute(
                 "collateral_to_redeem",
                 precision_adjusted_collateral_to_redeem,
@@ -340,69 +822,1337 @@ mod exec {
                 "token_amount_from_debt_covered",
                 token_amount_from_debt_covered.to_string(),
             )
-            .add_attribute("bonus_collateral", bonus_collateral.to_string());
+            .add_attribute("bonus_collateral", bonus_collateral.to_string())
+            .add_attribute("protocol_fee", protocol_fee)
+            .add_attribute("bad_debt", bad_debt);
 
         Ok(res)
     }
 
-    pub fn redeem_collateral(
-        deps: DepsMut,
+    /// Burns DSC for its face value in collateral, drawn from the positions with the lowest
+    /// nominal collateral ratio in that collateral first
+    pub fn redeem_dsc(
+        mut deps: DepsMut,
+        env: Env,
         info: MessageInfo,
+        amount: Uint128,
         collateral_asset: AssetInfo,
-        amount_collateral: Uint128,
+        max_fee: Decimal,
     ) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.redeem, "redeem")?;
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
         let config = CONFIG.load(deps.storage)?;
-        if !config
-            .assets_to_feeds
-            .contains_key(&collateral_asset.to_string())
-        {
-            return Err(ContractError::InvalidCollateralAsset {
-                denom: collateral_asset.inner(),
-            });
+        let denom = collateral_asset.inner();
+        if !config.is_collateral_asset(&denom) {
+            return Err(ContractError::InvalidCollateralAsset { denom });
         }
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        // the base rate grows with the share of the supply redeemed, before it is burnt
+        let dsc_supply: TokenInfoResponse = deps
+            .querier
+            .query_wasm_smart(config.dsc_address.to_string(), &Cw20QueryMsg::TokenInfo {})?;
 
-        let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
+        let positions = REDEMPTION_INDEX
+            .sub_prefix(denom.clone())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(MAX_REDEMPTION_POSITIONS)
+            .collect::<StdResult<Vec<_>>>()?;
+        // every collateral of the positions is priced once, to split their debt again
+        let mut assets = BTreeSet::from([denom.clone()]);
+        for (_, user) in &positions {
+            for (asset, _) in queries::get_collateral_balances(&deps.as_ref(), &config, user)? {
+                assets.insert(asset);
+            }
+        }
+        let prices = queries::get_asset_prices(
+            &deps.as_ref(),
+            &config,
+            assets.into_iter().collect(),
+            PriceFreshness::Fresh,
+        )?;
+        let collateral_params = load_collateral_params(deps.storage, &config, &denom)?;
+        let price =
+            queries::price_of(&prices, &denom)?.debt_price(&collateral_params.pricing_policy);
+        let decimals = config.asset_decimals(&denom);
+        let mut amount_redeemed = Uint128::zero();
+        let mut collateral_drawn = Uint128::zero();
+        let mut bad_debt = Uint128::zero();
+        for (_, user) in positions {
+            if amount_redeemed == amount {
+                break;
+            }
+            let debt = debt_rate.debt(DSC_MINTED.load(deps.storage, &user)?)?;
+            let balance = COLLATERAL_DEPOSITED.load(deps.storage, (&user, denom.clone()))?;
+            let balance_value = to_atomics(
+                Decimal::from_atomics(balance, decimals)?.checked_mul(price)?,
+                DSC_DECIMALS,
+            );
+            // a position worth less than its debt only repays what its collateral is worth
+            let redeemed = (amount - amount_redeemed).min(debt).min(balance_value);
+            if redeemed.is_zero() {
+                continue;
+            }
+            let collateral = if redeemed == balance_value {
+                balance
+            } else {
+                to_atomics(queries::dsc_to_decimal(redeemed)? / price, decimals).min(balance)
+            };
+            _remove_collateral(deps.storage, &denom, collateral, &user)?;
+            _reduce_debt(
+                deps.storage,
+                &env,
+                &debt_rate,
+                redeemed,
+                &user,
+                Some(&denom),
+            )?;
+            // the debt left once the last collateral is drawn is no longer backed by anything
+            if queries::get_collateral_balances(&deps.as_ref(), &config, &user)?.is_empty() {
+                let debt_left = queries::get_dsc_minted(&deps.as_ref(), &env, user.as_str())?;
+                _reduce_debt(deps.storage, &env, &debt_rate, debt_left, &user, None)?;
+                _add_bad_debt(deps.storage, debt_left)?;
+                bad_debt += debt_left;
+            }
+            resplit_debt(&mut deps, &config, &user, &prices)?;
+            amount_redeemed += redeemed;
+            collateral_drawn += collateral;
+        }
+        if amount_redeemed.is_zero() {
+            return Err(ContractError::NothingToRedeem { denom });
+        }
 
-        let redeem_collateral_msg = _redeem_collateral(
+        let redemption_rate = load_redemption_rate(deps.storage, env.block.time.seconds())?;
+        let base_rate = Decimal::checked_from_ratio(
+            amount_redeemed,
+            dsc_supply.total_supply * Uint128::from(REDEMPTION_BETA),
+        )
+        .unwrap_or(Decimal::one())
+        .saturating_add(redemption_rate.base_rate)
+        .min(Decimal::one());
+        let fee_rate = queries::redemption_fee_rate(&config, base_rate);
+        if fee_rate > max_fee {
+            return Err(ContractError::RedemptionFeeExceeded { fee_rate, max_fee });
+        }
+        REDEMPTION_RATE.save(
             deps.storage,
-            &collateral_asset,
-            amount_collateral,
-            &info.sender,
-            &info.sender,
+            &RedemptionRate {
+                base_rate,
+                last_update: redemption_rate.last_update,
+            },
         )?;
-        messages.push(redeem_collateral_msg);
-
-        revert_if_health_factor_is_broken(&deps, &info.sender)?;
+        let fee = collateral_drawn * fee_rate;
+        add_surplus(deps.storage, denom.clone(), fee)?;
 
-        let res = Response::new()
-            .add_messages(messages)
-            .add_attribute("action", "redeem_collateral")
+        let mut res = Response::new().add_message(burn_msg(
+            &config,
+            amount_redeemed,
+            &DscSource::Allowance(info.sender.clone()),
+        )?);
+        if collateral_drawn > fee {
+            res = res.add_message(transfer_msg(
+                &collateral_asset,
+                collateral_drawn - fee,
+                &info.sender,
+            )?);
+        }
+        Ok(res
+            .add_attribute("action", "redeem_dsc")
             .add_attribute("from", info.sender)
-            .add_attribute("asset", collateral_asset.inner())
-            .add_attribute("amount", amount_collateral);
-        Ok(res)
+            .add_attribute("asset", denom)
+            .add_attribute("amount_redeemed", amount_redeemed)
+            .add_attribute("collateral_drawn", collateral_drawn)
+            .add_attribute("fee", fee)
+            .add_attribute("fee_rate", fee_rate.to_string())
+            .add_attribute("bad_debt", bad_debt))
     }
 
-    pub fn burn_dsc(
-        deps: DepsMut,
+    pub fn kick_auction(
+        mut deps: DepsMut,
         env: Env,
-        info: MessageInfo,
-        amount_dsc_to_burn: Uint128,
+        user: String,
+        collateral_asset: AssetInfo,
     ) -> Result<Response, ContractError> {
-        let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
-        let burn_dsc_msg = _burn_dsc(
-            deps.storage,
-            &env,
-            amount_dsc_to_burn,
-            &info.sender,
-            &info.sender,
-        )?;
-        messages.push(burn_dsc_msg);
-        revert_if_health_factor_is_broken(&deps, &info.sender)?;
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.liquidate, "liquidate")?;
+
+        let config = CONFIG.load(deps.storage)?;
+        let denom = collateral_asset.inner();
+        if !config.is_collateral_asset(&denom) {
+            return Err(ContractError::InvalidCollateralAsset { denom });
+        }
+        let collateral_params = load_collateral_params(deps.storage, &config, &denom)?;
+        let LiquidationMode::Auction(params) = collateral_params.liquidation_mode else {
+            return Err(ContractError::FixedBonusLiquidationOnly { denom });
+        };
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        let user_addr = deps.api.addr_validate(&user)?;
+
+        // the auction sells what a fixed bonus liquidation would seize for the same debt
+        let quote =
+            queries::get_liquidation_quote(&deps.as_ref(), &env, &config, &user_addr, &denom)?;
+        if quote.health_factor >= config.min_health_factor {
+            return Err(ContractError::HealthFactorOk {});
+        }
+        let mut debt = to_atomics(quote.max_debt_to_cover, DSC_DECIMALS);
+        if debt.is_zero() || quote.collateral_to_seize.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        _remove_collateral(deps.storage, &denom, quote.collateral_to_seize, &user_addr)?;
+        if quote.underwater
+            && queries::get_collateral_balances(&deps.as_ref(), &config, &user_addr)?.is_empty()
+        {
+            // nothing else backs the position, the auction takes its whole debt
+            debt = queries::get_dsc_minted(&deps.as_ref(), &env, user_addr.as_str())?;
+        }
+        _reduce_debt(
+            deps.storage,
+            &env,
+            &debt_rate,
+            debt,
+            &user_addr,
+            Some(&denom),
+        )?;
+        resplit_debt(&mut deps, &config, &user_addr, &quote.prices)?;
+
+        let id = NEXT_AUCTION_ID.may_load(deps.storage)?.unwrap_or(1);
+        NEXT_AUCTION_ID.save(deps.storage, &(id + 1))?;
+        let auction = Auction {
+            user: user_addr,
+            collateral_asset,
+            debt,
+            collateral: quote.collateral_to_seize,
+            starting_price: quote.price.checked_mul(params.starting_premium)?,
+            start_time: env.block.time.seconds(),
+            params,
+        };
+        AUCTIONS.save(deps.storage, id, &auction)?;
+
+        let res = Response::new()
+            .add_attribute("action", "kick_auction")
+            .add_attribute("id", id.to_string())
+            .add_attribute("user", user)
+            .add_attribute("asset", denom)
+            .add_attribute("debt", auction.debt)
+            .add_attribute("collateral", auction.collateral)
+            .add_attribute("starting_price", auction.starting_price.to_string())
+            .add_attribute("initial_health_factor", quote.health_factor.to_string());
+        Ok(res)
+    }
+
+    pub fn take_auction(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+        max_collateral: Uint128,
+        max_price: Decimal,
+    ) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.liquidate, "liquidate")?;
+
+        let config = CONFIG.load(deps.storage)?;
+        let mut auction = AUCTIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::AuctionNotFound { id })?;
+        let now = env.block.time.seconds();
+        if auction.needs_reset(now) {
+            return Err(ContractError::AuctionNeedsReset { id });
+        }
+        let price = auction.price(now);
+        if price > max_price {
+            return Err(ContractError::AuctionPriceTooHigh { price });
+        }
+
+        let denom = auction.collateral_asset.inner();
+        let decimals = config.asset_decimals(&denom);
+        let mut collateral = max_collateral.min(auction.collateral);
+        let mut debt_covered = to_atomics(
+            Decimal::from_atomics(collateral, decimals)?.checked_mul(price)?,
+            DSC_DECIMALS,
+        );
+        if debt_covered > auction.debt {
+            // never pay more than the debt left to raise
+            debt_covered = auction.debt;
+            collateral = to_atomics(queries::dsc_to_decimal(debt_covered)? / price, decimals)
+                .min(auction.collateral);
+        }
+        if debt_covered.is_zero() || collateral.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        auction.debt -= debt_covered;
+        auction.collateral -= collateral;
+
+        let mut res = Response::new()
+            .add_message(burn_msg(
+                &config,
+                debt_covered,
+                &DscSource::Allowance(info.sender.clone()),
+            )?)
+            .add_message(transfer_msg(
+                &auction.collateral_asset,
+                collateral,
+                &info.sender,
+            )?)
+            .add_attribute("action", "take_auction")
+            .add_attribute("id", id.to_string())
+            .add_attribute("from", info.sender)
+            .add_attribute("price", price.to_string())
+            .add_attribute("collateral", collateral)
+            .add_attribute("debt_covered", debt_covered);
+
+        if auction.debt.is_zero() || auction.collateral.is_zero() {
+            // the collateral left goes back to the position it was taken from
+            if !auction.collateral.is_zero() {
+                _add_collateral(deps.storage, &denom, auction.collateral, &auction.user)?;
+            }
+            // the debt left once the collateral is sold is no longer backed by anything
+            _add_bad_debt(deps.storage, auction.debt)?;
+            AUCTIONS.remove(deps.storage, id);
+            res = res
+                .add_attribute("returned_collateral", auction.collateral)
+                .add_attribute("unraised_debt", auction.debt);
+        } else {
+            AUCTIONS.save(deps.storage, id, &auction)?;
+        }
+        Ok(res)
+    }
+
+    pub fn reset_auction(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.liquidate, "liquidate")?;
+
+        let config = CONFIG.load(deps.storage)?;
+        let mut auction = AUCTIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::AuctionNotFound { id })?;
+        let now = env.block.time.seconds();
+        if !auction.needs_reset(now) {
+            return Err(ContractError::AuctionNotResettable { id });
+        }
+        let denom = auction.collateral_asset.inner();
+        let collateral_params = load_collateral_params(deps.storage, &config, &denom)?;
+        let price =
+            queries::get_asset_price(&deps.as_ref(), &config, &denom, PriceFreshness::Fresh)?
+                .debt_price(&collateral_params.pricing_policy);
+        auction.starting_price = price.checked_mul(auction.params.starting_premium)?;
+        auction.start_time = now;
+        AUCTIONS.save(deps.storage, id, &auction)?;
+
+        let res = Response::new()
+            .add_attribute("action", "reset_auction")
+            .add_attribute("id", id.to_string())
+            .add_attribute("starting_price", auction.starting_price.to_string());
+        Ok(res)
+    }
+
+    /// Settles bad debt with the DSC surplus, then with the DSC `received` from a sender
+    pub fn settle_bad_debt(
+        deps: DepsMut,
+        env: Env,
+        received: Option<(Addr, Uint128)>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        // stability fees owed so far are part of the surplus
+        accrue_debt_rate(deps.storage, &env, &config)?;
+        let bad_debt = BAD_DEBT.may_load(deps.storage)?.unwrap_or_default();
+        if bad_debt.is_zero() {
+            return Err(ContractError::NoBadDebt {});
+        }
+        let surplus = SURPLUS
+            .may_load(deps.storage, config.dsc_address.to_string())?
+            .unwrap_or_default();
+        let settled_by_surplus = bad_debt.min(surplus);
+        let amount_received = received
+            .as_ref()
+            .map(|(_, amount)| *amount)
+            .unwrap_or_default();
+        let settled_by_received = (bad_debt - settled_by_surplus).min(amount_received);
+        let settled = settled_by_surplus + settled_by_received;
+        if settled.is_zero() {
+            return Err(ContractError::NoSurplus {});
+        }
+        BAD_DEBT.save(deps.storage, &(bad_debt - settled))?;
+        SURPLUS.save(
+            deps.storage,
+            config.dsc_address.to_string(),
+            &(surplus - settled_by_surplus),
+        )?;
+
+        // DSC surplus was never minted, only the DSC received is burnt
+        let mut res = Response::new();
+        if !settled_by_received.is_zero() {
+            res = res.add_message(burn_msg(
+                &config,
+                settled_by_received,
+                &DscSource::Received,
+            )?);
+        }
+        res = res
+            .add_attribute("action", "settle_bad_debt")
+            .add_attribute("settled_by_surplus", settled_by_surplus)
+            .add_attribute("settled_by_sender", settled_by_received)
+            .add_attribute("bad_debt", bad_debt - settled);
+        if let Some((sender, amount)) = received {
+            let refund = amount - settled_by_received;
+            if !refund.is_zero() {
+                res = res.add_message(transfer_msg(
+                    &AssetInfo::Cw20(config.dsc_address),
+                    refund,
+                    &sender,
+                )?);
+            }
+            res = res.add_attribute("from", sender);
+        }
+        Ok(res)
+    }
+
+    pub fn redeem_collateral(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        collateral_asset: AssetInfo,
+        amount_collateral: Uint128,
+    ) -> Result<Response, ContractError> {
+        if GLOBAL_SETTLEMENT.may_load(deps.storage)?.is_some() {
+            // once shut down, collateral is only freed by settling the position, and pausing
+            // redemptions can no longer lock it
+            if !DSC_MINTED
+                .may_load(deps.storage, &info.sender)?
+                .unwrap_or_default()
+                .is_zero()
+            {
+                return Err(ContractError::PositionNotSettled {});
+            }
+        } else {
+            let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+            revert_if_paused(pause_flags.redeem, "redeem")?;
+        }
+
+        let config = CONFIG.load(deps.storage)?;
+        if !config.is_collateral_asset(&collateral_asset.inner()) {
+            return Err(ContractError::InvalidCollateralAsset {
+                denom: collateral_asset.inner(),
+            });
+        }
+
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
+
+        let redeem_collateral_msg = _redeem_collateral(
+            deps.storage,
+            &collateral_asset,
+            amount_collateral,
+            &info.sender,
+            &info.sender,
+        )?;
+        messages.push(redeem_collateral_msg);
+
+        reattribute_debt_and_revert_if_unsafe(&mut deps, &env, &config, &debt_rate, &info.sender)?;
+
+        let res = Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "redeem_collateral")
+            .add_attribute("from", info.sender)
+            .add_attribute("asset", collateral_asset.inner())
+            .add_attribute("amount", amount_collateral);
+        Ok(res)
+    }
+
+    pub fn burn_dsc(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount_dsc_to_burn: Uint128,
+    ) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let config = CONFIG.load(deps.storage)?;
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
+        let burn_dsc_msg = _burn_dsc(
+            deps.storage,
+            &env,
+            &debt_rate,
+            amount_dsc_to_burn,
+            &info.sender,
+            &DscSource::Allowance(info.sender.clone()),
+            None,
+        )?;
+        messages.push(burn_dsc_msg);
+        // burning only improves the health factor, last good prices are enough
+        revert_if_health_factor_is_broken(
+            &deps,
+            &env,
+            &info.sender,
+            PriceFreshness::CachedAllowed,
+        )?;
         let res = Response::new().add_messages(messages);
         Ok(res)
     }
 
+    /// Burns DSC received by the contract to repay the debt of `on_behalf_of`
+    pub fn repay_debt(
+        deps: DepsMut,
+        env: Env,
+        on_behalf_of: Addr,
+        amount_dsc_to_burn: Uint128,
+    ) -> Result<Response, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let config = CONFIG.load(deps.storage)?;
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        let burn_dsc_msg = _burn_dsc(
+            deps.storage,
+            &env,
+            &debt_rate,
+            amount_dsc_to_burn,
+            &on_behalf_of,
+            &DscSource::Received,
+            None,
+        )?;
+        let res = Response::new()
+            .add_message(burn_dsc_msg)
+            .add_attribute("action", "repay_debt")
+            .add_attribute("on_behalf_of", on_behalf_of)
+            .add_attribute("amount", amount_dsc_to_burn);
+        Ok(res)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        oracle_address: Option<String>,
+        liquidation_threshold: Option<Uint128>,
+        liquidation_bonus: Option<Uint128>,
+        min_health_factor: Option<Decimal>,
+        stability_fee: Option<Decimal>,
+        close_factor: Option<Decimal>,
+        full_liquidation_health_factor: Option<Decimal>,
+        liquidation_dust: Option<Uint128>,
+        liquidation_fee_share: Option<Decimal>,
+        stability_fee_share: Option<Decimal>,
+        origination_fee: Option<Decimal>,
+        redemption_fee_floor: Option<Decimal>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+        revert_if_shutdown(deps.storage)?;
+
+        if let Some(oracle_address) = oracle_address {
+            config.oracle_address = deps.api.addr_validate(&oracle_address)?;
+        }
+        if let Some(liquidation_threshold) = liquidation_threshold {
+            config.liquidation_threshold = liquidation_threshold;
+        }
+        if let Some(liquidation_bonus) = liquidation_bonus {
+            config.liquidation_bonus = liquidation_bonus;
+        }
+        if let Some(min_health_factor) = min_health_factor {
+            config.min_health_factor = min_health_factor;
+        }
+        if let Some(stability_fee) = stability_fee {
+            validate_stability_fee(stability_fee)?;
+            // fees owed so far are accrued at the previous stability fee
+            accrue_debt_rate(deps.storage, &env, &config)?;
+            config.stability_fee = stability_fee;
+        }
+        if let Some(close_factor) = close_factor {
+            config.close_factor = close_factor;
+        }
+        if let Some(full_liquidation_health_factor) = full_liquidation_health_factor {
+            config.full_liquidation_health_factor = full_liquidation_health_factor;
+        }
+        if let Some(liquidation_dust) = liquidation_dust {
+            config.liquidation_dust = liquidation_dust;
+        }
+        if let Some(liquidation_fee_share) = liquidation_fee_share {
+            config.liquidation_fee_share = liquidation_fee_share;
+        }
+        if let Some(stability_fee_share) = stability_fee_share {
+            // fees owed so far go to the surplus at the previous share
+            accrue_debt_rate(deps.storage, &env, &config)?;
+            config.stability_fee_share = stability_fee_share;
+        }
+        if let Some(origination_fee) = origination_fee {
+            config.origination_fee = origination_fee;
+        }
+        if let Some(redemption_fee_floor) = redemption_fee_floor {
+            config.redemption_fee_floor = redemption_fee_floor;
+        }
+        validate_protocol_fees(
+            config.liquidation_fee_share,
+            config.stability_fee_share,
+            config.origination_fee,
+            config.redemption_fee_floor,
+        )?;
+        validate_liquidation_limits(
+            config.close_factor,
+            config.full_liquidation_health_factor,
+            config.min_health_factor,
+        )?;
+        validate_collateral_params(
+            &config.default_collateral_params(),
+            config.min_health_factor,
+        )?;
+        for asset in &config.assets {
+            let params = load_collateral_params(deps.storage, &config, &asset.inner())?;
+            validate_collateral_params(&params, config.min_health_factor)?;
+        }
+
+        CONFIG.save(deps.storage, &config)?;
+
+        let res = Response::new()
+            .add_attribute("action", "update_config")
+            .add_attribute("from", info.sender);
+        Ok(res)
+    }
+
+    pub fn add_collateral_asset(
+        deps: DepsMut,
+        info: MessageInfo,
+        asset: AssetInfo,
+        decimals: Option<u8>,
+        params: Option<CollateralParams>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        if let AssetInfo::Cw20(contract_addr) = &asset {
+            deps.api.addr_validate(contract_addr.as_str())?;
+        }
+        if config.is_collateral_asset(&asset.inner()) {
+            return Err(ContractError::CollateralAssetAlreadyRegistered {
+                denom: asset.inner(),
+            });
+        }
+        let decimals = query_asset_decimals(&deps.querier, &asset, decimals)?;
+        let params = params.unwrap_or_else(|| config.default_collateral_params());
+        validate_collateral_params(&params, config.min_health_factor)?;
+
+        config.assets_decimals.insert(asset.inner(), decimals);
+        config.assets.push(asset.clone());
+        CONFIG.save(deps.storage, &config)?;
+        COLLATERAL_PARAMS.save(deps.storage, asset.inner(), &params)?;
+
+        let res = Response::new()
+            .add_attribute("action", "add_collateral_asset")
+            .add_attribute("asset", asset.inner())
+            .add_attribute("decimals", decimals.to_string());
+        Ok(res)
+    }
+
+    pub fn remove_collateral_asset(
+        deps: DepsMut,
+        info: MessageInfo,
+        asset: AssetInfo,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        if !config.is_collateral_asset(&asset.inner()) {
+            return Err(ContractError::InvalidCollateralAsset {
+                denom: asset.inner(),
+            });
+        }
+        let total_deposited = TOTAL_COLLATERAL_DEPOSITED
+            .may_load(deps.storage, asset.inner())?
+            .unwrap_or_default();
+        if !total_deposited.is_zero() {
+            return Err(ContractError::CollateralAssetInUse {
+                denom: asset.inner(),
+            });
+        }
+
+        config.assets_decimals.remove(&asset.inner());
+        config
+            .assets
+            .retain(|collateral_asset| collateral_asset.inner() != asset.inner());
+        CONFIG.save(deps.storage, &config)?;
+        COLLATERAL_PARAMS.remove(deps.storage, asset.inner());
+
+        let res = Response::new()
+            .add_attribute("action", "remove_collateral_asset")
+            .add_attribute("asset", asset.inner());
+        Ok(res)
+    }
+
+    pub fn update_collateral_params(
+        deps: DepsMut,
+        info: MessageInfo,
+        asset: AssetInfo,
+        params: CollateralParams,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        if !config.is_collateral_asset(&asset.inner()) {
+            return Err(ContractError::InvalidCollateralAsset {
+                denom: asset.inner(),
+            });
+        }
+        validate_collateral_params(&params, config.min_health_factor)?;
+        COLLATERAL_PARAMS.save(deps.storage, asset.inner(), &params)?;
+
+        let res = Response::new()
+            .add_attribute("action", "update_collateral_params")
+            .add_attribute("from", info.sender)
+            .add_attribute("asset", asset.inner())
+            .add_attribute("liquidation_threshold", params.liquidation_threshold)
+            .add_attribute("max_ltv", params.max_ltv)
+            .add_attribute("liquidation_bonus", params.liquidation_bonus)
+            .add_attribute("enabled", params.enabled.to_string());
+        Ok(res)
+    }
+
+    pub fn drip(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let total_normalized_debt = TOTAL_NORMALIZED_DEBT
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        let previous_debt = match DEBT_RATE.may_load(deps.storage)? {
+            Some(previous_rate) => previous_rate.debt(total_normalized_debt)?,
+            None => total_normalized_debt,
+        };
+        let debt_rate = accrue_debt_rate(deps.storage, &env, &config)?;
+        let accrued_fees = debt_rate
+            .debt(total_normalized_debt)?
+            .saturating_sub(previous_debt);
+
+        let res = Response::new()
+            .add_attribute("action", "drip")
+            .add_attribute("rate", debt_rate.rate.to_string())
+            .add_attribute("accrued_fees", accrued_fees);
+        Ok(res)
+    }
+
+    pub fn withdraw_surplus(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Asset,
+        recipient: String,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+        let recipient = deps.api.addr_validate(&recipient)?;
+        let asset = amount.info.inner();
+        let is_dsc = asset == config.dsc_address.as_str();
+        if is_dsc {
+            // stability fees owed so far are part of the surplus
+            accrue_debt_rate(deps.storage, &env, &config)?;
+            if !BAD_DEBT
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+                .is_zero()
+            {
+                return Err(ContractError::BadDebtOutstanding {});
+            }
+        }
+        if amount.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let available = SURPLUS
+            .may_load(deps.storage, asset.clone())?
+            .unwrap_or_default();
+        if amount.amount > available {
+            return Err(ContractError::SurplusExceeded { asset, available });
+        }
+        SURPLUS.save(deps.storage, asset.clone(), &(available - amount.amount))?;
+
+        let message = if is_dsc {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.dsc_address.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: recipient.to_string(),
+                    amount: amount.amount,
+                })?,
+                funds: vec![],
+            })
+        } else {
+            transfer_msg(&amount.info, amount.amount, &recipient)?
+        };
+        let res = Response::new()
+            .add_message(message)
+            .add_attribute("action", "withdraw_surplus")
+            .add_attribute("asset", asset)
+            .add_attribute("amount", amount.amount)
+            .add_attribute("recipient", recipient);
+        Ok(res)
+    }
+
+    pub fn update_guardian(
+        deps: DepsMut,
+        info: MessageInfo,
+        guardian: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        config.guardian = guardian
+            .map(|guardian| deps.api.addr_validate(&guardian))
+            .transpose()?;
+        CONFIG.save(deps.storage, &config)?;
+
+        let res = Response::new()
+            .add_attribute("action", "update_guardian")
+            .add_attribute("from", info.sender)
+            .add_attribute(
+                "guardian",
+                config
+                    .guardian
+                    .map_or_else(|| String::from("none"), Addr::into_string),
+            );
+        Ok(res)
+    }
+
+    pub fn update_pause_flags(
+        deps: DepsMut,
+        info: MessageInfo,
+        mint: Option<bool>,
+        deposit: Option<bool>,
+        redeem: Option<bool>,
+        liquidate: Option<bool>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if assert_owner(&config, &info.sender).is_err() {
+            // the guardian can only pause
+            let is_guardian = config.guardian.as_ref() == Some(&info.sender);
+            let unpauses = [mint, deposit, redeem, liquidate].contains(&Some(false));
+            if !is_guardian || unpauses {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+
+        let mut pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        if let Some(mint) = mint {
+            pause_flags.mint = mint;
+        }
+        if let Some(deposit) = deposit {
+            pause_flags.deposit = deposit;
+        }
+        if let Some(redeem) = redeem {
+            pause_flags.redeem = redeem;
+        }
+        if let Some(liquidate) = liquidate {
+            pause_flags.liquidate = liquidate;
+        }
+        PAUSE_FLAGS.save(deps.storage, &pause_flags)?;
+
+        let PauseFlags {
+            mint,
+            deposit,
+            redeem,
+            liquidate,
+        } = pause_flags;
+        let res = Response::new()
+            .add_attribute("action", "update_pause_flags")
+            .add_attribute("from", info.sender)
+            .add_attribute("mint", mint.to_string())
+            .add_attribute("deposit", deposit.to_string())
+            .add_attribute("redeem", redeem.to_string())
+            .add_attribute("liquidate", liquidate.to_string());
+        Ok(res)
+    }
+
+    pub fn shutdown(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+        revert_if_shutdown(deps.storage)?;
+
+        // debts stop growing
+        accrue_debt_rate(deps.storage, &env, &config)?;
+        config.stability_fee = Decimal::zero();
+        CONFIG.save(deps.storage, &config)?;
+
+        // a stale feed cannot hold the shutdown back, its last good price is used instead
+        let mut prices = HashMap::new();
+        for asset in &config.assets {
+            let pricing_policy =
+                load_collateral_params(deps.storage, &config, &asset.inner())?.pricing_policy;
+            let price = queries::get_asset_price(
+                &deps.as_ref(),
+                &config,
+                &asset.inner(),
+                PriceFreshness::CachedAllowed,
+            )?
+            .collateral_price(&pricing_policy);
+            prices.insert(asset.inner(), price);
+
+            // collateral kept as protocol fees backs DSC like the collateral of positions
+            let surplus = SURPLUS
+                .may_load(deps.storage, asset.inner())?
+                .unwrap_or_default();
+            if !surplus.is_zero() {
+                SURPLUS.remove(deps.storage, asset.inner());
+                SETTLEMENT_COLLATERAL.save(deps.storage, asset.inner(), &surplus)?;
+            }
+        }
+        GLOBAL_SETTLEMENT.save(
+            deps.storage,
+            &GlobalSettlement {
+                shutdown_time: env.block.time.seconds(),
+                prices,
+            },
+        )?;
+
+        let res = Response::new()
+            .add_attribute("action", "shutdown")
+            .add_attribute("from", info.sender);
+        Ok(res)
+    }
+
+    pub fn settle_position(
+        deps: DepsMut,
+        env: Env,
+        user: String,
+    ) -> Result<Response, ContractError> {
+        let settlement = GLOBAL_SETTLEMENT
+            .may_load(deps.storage)?
+            .ok_or(ContractError::ContractNotShutdown {})?;
+        let config = CONFIG.load(deps.storage)?;
+        let user_addr = deps.api.addr_validate(&user)?;
+
+        let debt_rate = load_debt_rate(deps.storage, &config, env.block.time.seconds())?;
+        let normalized_debt = DSC_MINTED
+            .may_load(deps.storage, &user_addr)?
+            .unwrap_or_default();
+        let debt = debt_rate.debt(normalized_debt)?;
+        let mut uncovered_debt = queries::dsc_to_decimal(debt)?;
+
+        let mut res = Response::new()
+            .add_attribute("action", "settle_position")
+            .add_attribute("user", &user)
+            .add_attribute("debt", debt);
+
+        // seize collateral worth the debt at frozen prices, in the order assets were registered
+        for collateral_asset in &config.assets {
+            if uncovered_debt.is_zero() {
+                break;
+            }
+            let denom = collateral_asset.inner();
+            let balance = COLLATERAL_DEPOSITED
+                .may_load(deps.storage, (&user_addr, denom.clone()))?
+                .unwrap_or_default();
+            if balance.is_zero() {
+                continue;
+            }
+            let price = settlement.prices.get(&denom).copied().unwrap_or_default();
+            let decimals = config.asset_decimals(&denom);
+            let balance_value = Decimal::from_atomics(balance, decimals)?.checked_mul(price)?;
+            let seized = if balance_value <= uncovered_debt {
+                uncovered_debt -= balance_value;
+                balance
+            } else {
+                let seized = to_atomics(uncovered_debt / price, decimals).min(balance);
+                uncovered_debt = Decimal::zero();
+                seized
+            };
+
+            COLLATERAL_DEPOSITED.save(
+                deps.storage,
+                (&user_addr, denom.clone()),
+                &(balance - seized),
+            )?;
+            TOTAL_COLLATERAL_DEPOSITED.update(
+                deps.storage,
+                denom.clone(),
+                |total: Option<Uint128>| -> StdResult<_> {
+                    Ok(total.unwrap_or_default().saturating_sub(seized))
+                },
+            )?;
+            SETTLEMENT_COLLATERAL.update(
+                deps.storage,
+                denom.clone(),
+                |pot: Option<Uint128>| -> StdResult<_> { Ok(pot.unwrap_or_default() + seized) },
+            )?;
+            res = res
+                .add_attribute("asset", denom)
+                .add_attribute("seized", seized);
+        }
+
+        DSC_MINTED.save(deps.storage, &user_addr, &Uint128::zero(), env.block.height)?;
+        TOTAL_NORMALIZED_DEBT.update(deps.storage, |total| -> StdResult<_> {
+            Ok(total.saturating_sub(normalized_debt))
+        })?;
+        reduce_collateral_debt(deps.storage, &user_addr, normalized_debt, None)?;
+        update_redemption_index(deps.storage, &user_addr)?;
+
+        Ok(res.add_attribute("uncovered_debt", uncovered_debt.to_string()))
+    }
+
+    pub fn yank_auction(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+        if GLOBAL_SETTLEMENT.may_load(deps.storage)?.is_none() {
+            return Err(ContractError::ContractNotShutdown {});
+        }
+        let config = CONFIG.load(deps.storage)?;
+        let auction = AUCTIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::AuctionNotFound { id })?;
+        AUCTIONS.remove(deps.storage, id);
+
+        // the collateral and debt go back to the position, to be settled at frozen prices
+        let denom = auction.collateral_asset.inner();
+        let debt_rate = load_debt_rate(deps.storage, &config, env.block.time.seconds())?;
+        let normalized_debt = debt_rate.normalize(auction.debt)?;
+        DSC_MINTED.update(
+            deps.storage,
+            &auction.user,
+            env.block.height,
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance.unwrap_or_default() + normalized_debt)
+            },
+        )?;
+        TOTAL_NORMALIZED_DEBT.update(deps.storage, |total| -> StdResult<_> {
+            Ok(total + normalized_debt)
+        })?;
+        let backed_debt = COLLATERAL_DEBT
+            .may_load(deps.storage, (&auction.user, denom.clone()))?
+            .unwrap_or_default();
+        set_collateral_debt(
+            deps.storage,
+            &auction.user,
+            denom.clone(),
+            backed_debt + normalized_debt,
+        )?;
+        _add_collateral(deps.storage, &denom, auction.collateral, &auction.user)?;
+
+        let res = Response::new()
+            .add_attribute("action", "yank_auction")
+            .add_attribute("id", id.to_string())
+            .add_attribute("user", auction.user)
+            .add_attribute("asset", denom)
+            .add_attribute("debt", auction.debt)
+            .add_attribute("collateral", auction.collateral);
+        Ok(res)
+    }
+
+    pub fn redeem_after_shutdown(
+        deps: DepsMut,
+        info: MessageInfo,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        if GLOBAL_SETTLEMENT.may_load(deps.storage)?.is_none() {
+            return Err(ContractError::ContractNotShutdown {});
+        }
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let total_normalized_debt = TOTAL_NORMALIZED_DEBT
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        // debt still on auction is settled once the auction is yanked
+        let live_auction = AUCTIONS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if !total_normalized_debt.is_zero() || live_auction {
+            return Err(ContractError::DebtNotSettled {});
+        }
+
+        let config = CONFIG.load(deps.storage)?;
+        let dsc_info: TokenInfoResponse = deps
+            .querier
+            .query_wasm_smart(&config.dsc_address, &Cw20QueryMsg::TokenInfo {})?;
+
+        // NOTE: DSC Engine must be declared as minter on DSC CW20 intantiation
+        let mut messages: std::vec::Vec<CosmosMsg<Empty>> =
+            vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.dsc_address.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::BurnFrom {
+                    owner: info.sender.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            })];
+        let mut res = Response::new()
+            .add_attribute("action", "redeem_after_shutdown")
+            .add_attribute("from", &info.sender)
+            .add_attribute("amount", amount);
+
+        for collateral_asset in &config.assets {
+            let pot = SETTLEMENT_COLLATERAL
+                .may_load(deps.storage, collateral_asset.inner())?
+                .unwrap_or_default();
+            let share = pot.multiply_ratio(amount, dsc_info.total_supply);
+            if share.is_zero() {
+                continue;
+            }
+            // will fail if amount is greater than the DSC supply
+            SETTLEMENT_COLLATERAL.save(
+                deps.storage,
+                collateral_asset.inner(),
+                &pot.checked_sub(share)?,
+            )?;
+            messages.push(transfer_msg(collateral_asset, share, &info.sender)?);
+            res = res
+                .add_attribute("asset", collateral_asset.inner())
+                .add_attribute("redeemed", share);
+        }
+
+        Ok(res.add_messages(messages))
+    }
+
+    pub fn propose_new_owner(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: String,
+        expiry: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        if let Some(expiry) = &expiry {
+            if expiry.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+        }
+        let proposal = OwnershipProposal {
+            owner: deps.api.addr_validate(&owner)?,
+            expiry,
+        };
+        OWNERSHIP_PROPOSAL.save(deps.storage, &proposal)?;
+
+        let res = Response::new()
+            .add_attribute("action", "propose_new_owner")
+            .add_attribute("from", info.sender)
+            .add_attribute("pending_owner", proposal.owner);
+        Ok(res)
+    }
+
+    pub fn accept_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let proposal = OWNERSHIP_PROPOSAL
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoOwnershipProposal {})?;
+        if proposal.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if let Some(expiry) = &proposal.expiry {
+            if expiry.is_expired(&env.block) {
+                return Err(ContractError::OwnershipProposalExpired {});
+            }
+        }
+
+        let mut config = CONFIG.load(deps.storage)?;
+        config.owner = Some(proposal.owner);
+        CONFIG.save(deps.storage, &config)?;
+        OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+        let res = Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("owner", info.sender);
+        Ok(res)
+    }
+
+    pub fn cancel_ownership_proposal(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        if OWNERSHIP_PROPOSAL.may_load(deps.storage)?.is_none() {
+            return Err(ContractError::NoOwnershipProposal {});
+        }
+        OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+        let res = Response::new()
+            .add_attribute("action", "cancel_ownership_proposal")
+            .add_attribute("from", info.sender);
+        Ok(res)
+    }
+
+    pub fn renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        assert_owner(&config, &info.sender)?;
+
+        config.owner = None;
+        CONFIG.save(deps.storage, &config)?;
+        OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+        let res = Response::new()
+            .add_attribute("action", "renounce_ownership")
+            .add_attribute("from", info.sender);
+        Ok(res)
+    }
+
+    /// Rejects risk parameters under which an unhealthy position could never be brought back above
+    /// `min_health_factor`. Liquidating `d` usd of debt seizes `d * (1 + bonus)` of collateral, which
+    /// only improves the health factor while `collateral / debt > 1 + bonus`, i.e. while
+    /// `min_health_factor > liquidation_threshold * (1 + liquidation_bonus)`.
+    pub fn validate_risk_params(
+        liquidation_threshold: Uint128,
+        liquidation_bonus: Uint128,
+        min_health_factor: Decimal,
+    ) -> Result<(), ContractError> {
+        if liquidation_threshold.is_zero() || liquidation_threshold > Uint128::new(100) {
+            return Err(ContractError::InvalidLiquidationThreshold {});
+        }
+        if liquidation_bonus >= Uint128::new(100) {
+            return Err(ContractError::InvalidLiquidationBonus {});
+        }
+        let min_liquidatable_health_factor = Decimal::percent(liquidation_threshold.u128() as u64)
+            .checked_mul(Decimal::one() + Decimal::percent(liquidation_bonus.u128() as u64))?;
+        if min_health_factor <= min_liquidatable_health_factor {
+            return Err(ContractError::InvalidMinHealthFactor {});
+        }
+        Ok(())
+    }
+
+    pub fn validate_stability_fee(stability_fee: Decimal) -> Result<(), ContractError> {
+        if stability_fee > Decimal::one() {
+            return Err(ContractError::InvalidStabilityFee {});
+        }
+        Ok(())
+    }
+
+    /// The close factor must let liquidations cover some debt, and full liquidations can only
+    /// start below the health factor that allows liquidations
+    pub fn validate_liquidation_limits(
+        close_factor: Decimal,
+        full_liquidation_health_factor: Decimal,
+        min_health_factor: Decimal,
+    ) -> Result<(), ContractError> {
+        if close_factor.is_zero() || close_factor > Decimal::one() {
+            return Err(ContractError::InvalidCloseFactor {});
+        }
+        if full_liquidation_health_factor > min_health_factor {
+            return Err(ContractError::InvalidFullLiquidationHealthFactor {});
+        }
+        Ok(())
+    }
+
+    /// Fee shares and the redemption fee floor cannot exceed what they are taken from, and
+    /// minting cannot cost all of it
+    pub fn validate_protocol_fees(
+        liquidation_fee_share: Decimal,
+        stability_fee_share: Decimal,
+        origination_fee: Decimal,
+        redemption_fee_floor: Decimal,
+    ) -> Result<(), ContractError> {
+        if liquidation_fee_share > Decimal::one()
+            || stability_fee_share > Decimal::one()
+            || origination_fee >= Decimal::one()
+            || redemption_fee_floor > Decimal::one()
+        {
+            return Err(ContractError::InvalidProtocolFees {});
+        }
+        Ok(())
+    }
+
+    /// Risk parameters of a collateral asset must pass `validate_risk_params`, minting can
+    /// never go beyond what the health factor allows and auctions must start at the oracle
+    /// price or above
+    pub fn validate_collateral_params(
+        params: &CollateralParams,
+        min_health_factor: Decimal,
+    ) -> Result<(), ContractError> {
+        validate_risk_params(
+            params.liquidation_threshold,
+            params.liquidation_bonus,
+            min_health_factor,
+        )?;
+        if params.max_ltv > params.liquidation_threshold {
+            return Err(ContractError::InvalidMaxLtv {});
+        }
+        if let LiquidationMode::Auction(auction_params) = &params.liquidation_mode {
+            if auction_params.starting_premium < Decimal::one()
+                || auction_params.duration == 0
+                || auction_params.reset_price_ratio >= Decimal::one()
+            {
+                return Err(ContractError::InvalidAuctionParams {});
+            }
+        }
+        Ok(())
+    }
+
+    /// Decimals of a collateral asset: queried from the token contract for cw20 assets, must be
+    /// `supplied_decimals` for native ones
+    pub fn query_asset_decimals(
+        querier: &QuerierWrapper,
+        asset: &AssetInfo,
+        supplied_decimals: Option<u8>,
+    ) -> Result<u8, ContractError> {
+        match asset {
+            AssetInfo::Cw20(contract_addr) => {
+                let token_info: TokenInfoResponse =
+                    querier.query_wasm_smart(contract_addr, &Cw20QueryMsg::TokenInfo {})?;
+                match supplied_decimals {
+                    Some(decimals) if decimals != token_info.decimals => {
+                        Err(ContractError::InvalidAssetDecimals {
+                            denom: asset.inner(),
+                        })
+                    }
+                    _ => Ok(token_info.decimals),
+                }
+            }
+            _ => supplied_decimals.ok_or(ContractError::MissingNativeDecimals {
+                denom: asset.inner(),
+            }),
+        }
+    }
+
+    /// Converts a token amount to its atomic units, rounding down
+    pub fn to_atomics(amount: Decimal, decimals: u32) -> Uint128 {
+        amount
+            .atomics()
+            .multiply_ratio(10_u128.pow(decimals), 10_u128.pow(Decimal::DECIMAL_PLACES))
+    }
+
+    /// Funds left once the Pyth update fee is paid out of them
+    fn deduct_update_fee(
+        mut funds: Vec<Coin>,
+        update_fee: &Coin,
+    ) -> Result<Vec<Coin>, ContractError> {
+        if update_fee.amount.is_zero() {
+            return Ok(funds);
+        }
+        let fee_funds = funds
+            .iter_mut()
+            .find(|coin| coin.denom == update_fee.denom && coin.amount >= update_fee.amount)
+            .ok_or_else(|| ContractError::InsufficientUpdateFee {
+                fee: update_fee.clone(),
+            })?;
+        fee_funds.amount -= update_fee.amount;
+        funds.retain(|coin| !coin.amount.is_zero());
+        Ok(funds)
+    }
+
+    pub fn revert_if_shutdown(storage: &dyn Storage) -> Result<(), ContractError> {
+        if GLOBAL_SETTLEMENT.may_load(storage)?.is_some() {
+            return Err(ContractError::ContractShutdown {});
+        }
+        Ok(())
+    }
+
+    fn revert_if_paused(paused: bool, action: &str) -> Result<(), ContractError> {
+        if paused {
+            return Err(ContractError::ActionPaused {
+                action: action.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn assert_owner(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+        match &config.owner {
+            Some(owner) if owner == sender => Ok(()),
+            _ => Err(ContractError::Unauthorized {}),
+        }
+    }
+
     fn _redeem_collateral(
         storage: &mut dyn Storage,
         collateral_asset: &AssetInfo,
@@ -410,210 +2160,592 @@ mod exec {
         from: &Addr,
         to: &Addr,
     ) -> Result<CosmosMsg, ContractError> {
-        let message: CosmosMsg;
-        if let AssetInfo::Cw20(contract_addr) = &collateral_asset {
-            message = CosmosMsg::Wasm(WasmMsg::Execute {
+        if amount_collateral.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let message = transfer_msg(collateral_asset, amount_collateral, to)?;
+        _remove_collateral(storage, &collateral_asset.inner(), amount_collateral, from)?;
+        Ok(message)
+    }
+
+    /// Takes collateral out of the deposits of a user, without sending it anywhere
+    fn _remove_collateral(
+        storage: &mut dyn Storage,
+        collateral_denom: &str,
+        amount_collateral: Uint128,
+        from: &Addr,
+    ) -> Result<(), ContractError> {
+        COLLATERAL_DEPOSITED.update(
+            storage,
+            (from, collateral_denom.to_string()),
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance.unwrap_or_default() - amount_collateral)
+            },
+        )?; // will fail if user hasn't enough deposited collateral deposited
+        TOTAL_COLLATERAL_DEPOSITED.update(
+            storage,
+            collateral_denom.to_string(),
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance
+                    .unwrap_or_default()
+                    .saturating_sub(amount_collateral))
+            },
+        )?;
+        update_redemption_index(storage, from)?;
+        Ok(())
+    }
+
+    /// Credits collateral already held by DSC Engine to the deposits of a user
+    fn _add_collateral(
+        storage: &mut dyn Storage,
+        collateral_denom: &str,
+        amount_collateral: Uint128,
+        to: &Addr,
+    ) -> Result<(), ContractError> {
+        COLLATERAL_DEPOSITED.update(
+            storage,
+            (to, collateral_denom.to_string()),
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance.unwrap_or_default() + amount_collateral)
+            },
+        )?;
+        TOTAL_COLLATERAL_DEPOSITED.update(
+            storage,
+            collateral_denom.to_string(),
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance.unwrap_or_default() + amount_collateral)
+            },
+        )?;
+        update_redemption_index(storage, to)?;
+        Ok(())
+    }
+
+    /// Accrues the stability fee up to the current block and saves the new rate
+    pub fn accrue_debt_rate(
+        storage: &mut dyn Storage,
+        env: &Env,
+        config: &Config,
+    ) -> Result<DebtRate, ContractError> {
+        let previous_debt_rate = DEBT_RATE.may_load(storage)?;
+        let debt_rate = load_debt_rate(storage, config, env.block.time.seconds())?;
+        DEBT_RATE.save(storage, &debt_rate)?;
+        if let Some(previous_debt_rate) = previous_debt_rate {
+            // the protocol share of the fees accrued since the last accrual
+            let total_normalized_debt =
+                TOTAL_NORMALIZED_DEBT.may_load(storage)?.unwrap_or_default();
+            let fees_accrued = debt_rate
+                .debt(total_normalized_debt)?
+                .saturating_sub(previous_debt_rate.debt(total_normalized_debt)?);
+            add_surplus(
+                storage,
+                config.dsc_address.to_string(),
+                fees_accrued * config.stability_fee_share,
+            )?;
+        }
+        Ok(debt_rate)
+    }
+
+    fn _mint_dsc(
+        storage: &mut dyn Storage,
+        env: &Env,
+        debt_rate: &DebtRate,
+        amount_dsc_to_mint: Uint128,
+        origination_fee: Uint128,
+        to: &Addr,
+    ) -> Result<CosmosMsg, ContractError> {
+        let config = CONFIG.load(storage)?;
+        let message = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.dsc_address.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                recipient: to.to_string(),
+                amount: amount_dsc_to_mint,
+            })?,
+            funds: vec![],
+        });
+        // the origination fee is owed like minted DSC, but only minted once withdrawn
+        add_surplus(storage, config.dsc_address.to_string(), origination_fee)?;
+        let normalized_amount = debt_rate.normalize(amount_dsc_to_mint + origination_fee)?;
+        DSC_MINTED.update(
+            storage,
+            to,
+            env.block.height,
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance.unwrap_or_default() + normalized_amount)
+            },
+        )?;
+        TOTAL_NORMALIZED_DEBT.update(storage, |total| -> StdResult<_> {
+            Ok(total + normalized_amount)
+        })?;
+        Ok(message)
+    }
+
+    /// Credits `amount_collateral`, already received by the contract, to the deposits of
+    /// `depositor`
+    fn _deposit_collateral(
+        deps: &mut DepsMut,
+        depositor: &Addr,
+        collateral_asset: &AssetInfo,
+        amount_collateral: Uint128,
+    ) -> Result<(), ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.deposit, "deposit")?;
+        if amount_collateral.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        // CHECK IF COLLATERAL ASSET IS VALID
+        let config = CONFIG.load(deps.storage)?;
+        if !config.is_collateral_asset(&collateral_asset.inner()) {
+            return Err(ContractError::InvalidCollateralAsset {
+                denom: collateral_asset.inner(),
+            });
+        }
+        let collateral_params =
+            load_collateral_params(deps.storage, &config, &collateral_asset.inner())?;
+        if !collateral_params.enabled {
+            return Err(ContractError::CollateralAssetDisabled {
+                denom: collateral_asset.inner(),
+            });
+        }
+
+        _add_collateral(
+            deps.storage,
+            &collateral_asset.inner(),
+            amount_collateral,
+            depositor,
+        )?;
+        Ok(())
+    }
+
+    /// Credits collateral paid by the message sender to its deposits, returning the messages
+    /// pulling it in
+    fn _deposit_collateral_from_sender(
+        deps: &mut DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        collateral_asset: &AssetInfo,
+        amount_collateral: Uint128,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        _deposit_collaterals_from_sender(
+            deps,
+            env,
+            info,
+            &[Asset::new(collateral_asset.clone(), amount_collateral)],
+        )
+    }
+
+    /// Credits several collaterals paid by the message sender to its deposits, returning the
+    /// messages pulling them in. Native funds sent must match the native collaterals declared.
+    fn _deposit_collaterals_from_sender(
+        deps: &mut DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        collateral_assets: &[Asset],
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        let mut messages: std::vec::Vec<CosmosMsg<Empty>> = vec![];
+        let mut native_amounts: BTreeMap<String, Uint128> = BTreeMap::new();
+        for collateral in collateral_assets {
+            _deposit_collateral(deps, &info.sender, &collateral.info, collateral.amount)?;
+
+            // TRANSFER COLLATERAL FROM USER TO CONTRACT
+            // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
+            // If the asset is native token, the pool balance is already increased
+            if let AssetInfo::Cw20(contract_addr) = &collateral.info {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: collateral.amount,
+                    })?,
+                    funds: vec![],
+                }));
+            } else {
+                *native_amounts.entry(collateral.info.inner()).or_default() += collateral.amount;
+            }
+        }
+
+        // every coin sent must be declared, otherwise it would be stuck in the contract
+        for coin in &info.funds {
+            match native_amounts.remove(&coin.denom) {
+                Some(amount) if amount == coin.amount => {}
+                Some(_) => {
+                    return Err(ContractError::MissingNativeFunds {
+                        denom: coin.denom.clone(),
+                    })
+                }
+                None => {
+                    return Err(ContractError::UnexpectedNativeFunds {
+                        denom: coin.denom.clone(),
+                    })
+                }
+            }
+        }
+        if let Some(denom) = native_amounts.into_keys().next() {
+            return Err(ContractError::MissingNativeFunds { denom });
+        }
+        Ok(messages)
+    }
+
+    /// Mints DSC to `minter`, reverting if its position is no longer safe afterwards
+    fn _mint_against_collateral(
+        deps: &mut DepsMut,
+        env: &Env,
+        minter: &Addr,
+        amount_dsc_to_mint: Uint128,
+    ) -> Result<CosmosMsg, ContractError> {
+        revert_if_shutdown(deps.storage)?;
+        let pause_flags = PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default();
+        revert_if_paused(pause_flags.mint, "mint")?;
+        if amount_dsc_to_mint.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let config = CONFIG.load(deps.storage)?;
+        let debt_rate = accrue_debt_rate(deps.storage, env, &config)?;
+        // collaterals are priced once for the debt ceilings and the position checks
+        let account_value = queries::get_account_collateral_value(
+            &deps.as_ref(),
+            minter.to_string(),
+            PriceFreshness::Fresh,
+        )?;
+        // NOTE: DSC Engine must be declared as minter on DSC CW20 intantiation
+        let origination_fee = amount_dsc_to_mint * config.origination_fee;
+        let mint_dsc_msg = _mint_dsc(
+            deps.storage,
+            env,
+            &debt_rate,
+            amount_dsc_to_mint,
+            origination_fee,
+            minter,
+        )?;
+        attribute_debt(
+            deps.storage,
+            &config,
+            &debt_rate,
+            minter,
+            &account_value.borrowing_power_by_asset,
+        )?;
+
+        // VERIFY NEW USER HEALTH FACTOR AND BORROWING POWER
+        revert_if_account_is_unsafe(&deps.as_ref(), env, minter, &account_value)?;
+        Ok(mint_dsc_msg)
+    }
+
+    /// Sends `amount` of a collateral asset held by the contract to `to`
+    pub fn transfer_msg(
+        collateral_asset: &AssetInfo,
+        amount: Uint128,
+        to: &Addr,
+    ) -> Result<CosmosMsg, ContractError> {
+        let message = if let AssetInfo::Cw20(contract_addr) = &collateral_asset {
+            CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: contract_addr.to_string(),
                 msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: to.to_string(),
-                    amount: amount_collateral,
+                    amount,
                 })?,
                 funds: vec![],
-            });
+            })
         } else {
-            message = CosmosMsg::Bank(BankMsg::Send {
+            CosmosMsg::Bank(BankMsg::Send {
                 to_address: to.to_string(),
                 amount: vec![Coin {
                     denom: collateral_asset.inner(),
-                    amount: amount_collateral,
+                    amount,
                 }],
-            });
+            })
         };
-
-        COLLATERAL_DEPOSITED.update(
-            storage,
-            (&from, collateral_asset.inner()),
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default() - amount_collateral)
-            },
-        )?; // will fail if user hasn't enough deposited collateral deposited
-
         Ok(message)
     }
 
     fn _burn_dsc(
         storage: &mut dyn Storage,
         env: &Env,
+        debt_rate: &DebtRate,
         amount_dsc_to_burn: Uint128,
         on_behalf_of: &Addr,
-        dsc_from: &Addr,
+        dsc_source: &DscSource,
+        backing: Option<&str>,
     ) -> Result<CosmosMsg, ContractError> {
         let config = CONFIG.load(storage)?;
-        let message = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.dsc_address.into_string(),
-            msg: to_json_binary(&Cw20ExecuteMsg::BurnFrom {
-                owner: dsc_from.to_string(),
+        let message = burn_msg(&config, amount_dsc_to_burn, dsc_source)?;
+        _reduce_debt(
+            storage,
+            env,
+            debt_rate,
+            amount_dsc_to_burn,
+            on_behalf_of,
+            backing,
+        )?;
+        Ok(message)
+    }
+
+    pub fn burn_msg(
+        config: &Config,
+        amount_dsc_to_burn: Uint128,
+        dsc_source: &DscSource,
+    ) -> Result<CosmosMsg, ContractError> {
+        let burn_msg = match dsc_source {
+            DscSource::Allowance(owner) => Cw20ExecuteMsg::BurnFrom {
+                owner: owner.to_string(),
                 amount: amount_dsc_to_burn,
-            })?,
+            },
+            DscSource::Received => Cw20ExecuteMsg::Burn {
+                amount: amount_dsc_to_burn,
+            },
+        };
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.dsc_address.to_string(),
+            msg: to_json_binary(&burn_msg)?,
             funds: vec![],
-        });
-        DSC_MINTED.update(
+        }))
+    }
+
+    /// Writes off the debt left uncovered by a position, or an auction, without collateral
+    fn _add_bad_debt(storage: &mut dyn Storage, amount_dsc: Uint128) -> Result<(), ContractError> {
+        let bad_debt = BAD_DEBT.may_load(storage)?.unwrap_or_default();
+        BAD_DEBT.save(storage, &(bad_debt + amount_dsc))?;
+        Ok(())
+    }
+
+    /// Removes DSC from the debt of a user, without burning it. The debt backed by `backing`, the
+    /// collateral it is repaid with if any, goes first
+    fn _reduce_debt(
+        storage: &mut dyn Storage,
+        env: &Env,
+        debt_rate: &DebtRate,
+        amount_dsc: Uint128,
+        on_behalf_of: &Addr,
+        backing: Option<&str>,
+    ) -> Result<(), ContractError> {
+        let normalized_debt = DSC_MINTED
+            .may_load(storage, on_behalf_of)?
+            .unwrap_or_default();
+        // will fail if reducing more than the debt
+        let remaining_debt = debt_rate.debt(normalized_debt)?.checked_sub(amount_dsc)?;
+        let remaining_normalized_debt = debt_rate.normalize(remaining_debt)?.min(normalized_debt);
+        DSC_MINTED.save(
             storage,
             on_behalf_of,
+            &remaining_normalized_debt,
             env.block.height,
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default() - amount_dsc_to_burn)
-            },
         )?;
-        Ok(message)
+        TOTAL_NORMALIZED_DEBT.update(storage, |total| -> StdResult<_> {
+            Ok(total.saturating_sub(normalized_debt - remaining_normalized_debt))
+        })?;
+        reduce_collateral_debt(
+            storage,
+            on_behalf_of,
+            normalized_debt - remaining_normalized_debt,
+            backing,
+        )?;
+        update_redemption_index(storage, on_behalf_of)?;
+        Ok(())
     }
 
     fn revert_if_health_factor_is_broken(
         deps: &DepsMut,
+        env: &Env,
         user_addr: &Addr,
+        freshness: PriceFreshness,
     ) -> Result<(), ContractError> {
-        let user_health_factor = get_health_factor(&deps, user_addr.to_string())?;
-        let config = CONFIG.load(deps.storage)?;
+        let user_health_factor = get_health_factor(deps, env, user_addr.to_string(), freshness)?;
+        revert_if_below_min_health_factor(deps.storage, user_health_factor)
+    }
 
-        if user_health_factor < config.min_health_factor {
+    fn revert_if_below_min_health_factor(
+        storage: &dyn Storage,
+        health_factor: Decimal,
+    ) -> Result<(), ContractError> {
+        let config = CONFIG.load(storage)?;
+        if health_factor < config.min_health_factor {
             return Err(ContractError::BreaksHealthFactor {
-                health_factor_value: user_health_factor,
+                health_factor_value: health_factor,
                 min_value: config.min_health_factor,
             });
         }
         Ok(())
     }
 
-    fn get_health_factor(deps: &DepsMut, user: String) -> Result<Decimal, ContractError> {
-        let AccountInfoResponse {
-            deposited_collateral_in_usd,
-            total_dsc_minted,
-        } = get_account_information(deps, user)?;
-        Ok(calculate_health_factor(
-            deps,
-            total_dsc_minted,
-            deposited_collateral_in_usd,
-        )?)
+    /// Splits the debt of a position again once collateral was withdrawn from it. Withdrawals
+    /// leaving debt outstanding must keep the position within its max LTV and the debt ceilings
+    /// of the collaterals now backing more of it
+    fn reattribute_debt_and_revert_if_unsafe(
+        deps: &mut DepsMut,
+        env: &Env,
+        config: &Config,
+        debt_rate: &DebtRate,
+        user_addr: &Addr,
+    ) -> Result<(), ContractError> {
+        if queries::get_dsc_minted(&deps.as_ref(), env, user_addr.as_str())?.is_zero() {
+            return Ok(());
+        }
+        let account_value = queries::get_account_collateral_value(
+            &deps.as_ref(),
+            user_addr.to_string(),
+            PriceFreshness::Fresh,
+        )?;
+        attribute_debt(
+            deps.storage,
+            config,
+            debt_rate,
+            user_addr,
+            &account_value.borrowing_power_by_asset,
+        )?;
+        revert_if_account_is_unsafe(&deps.as_ref(), env, user_addr, &account_value)
     }
 
-    fn calculate_health_factor(
-        deps: &DepsMut,
-        total_dsc_minted: Uint128,
-        collateral_value_in_usd: Decimal,
-    ) -> Result<Decimal, ContractError> {
-        if total_dsc_minted == Uint128::new(0) {
-            Ok(Decimal::new(Uint128::MAX))
-        } else {
-            let config = CONFIG.load(deps.storage)?;
-            let liquidation_threshold =
-                Decimal::percent(config.liquidation_threshold.u128() as u64);
-            let collateral_adjusted_for_threshold =
-                collateral_value_in_usd.checked_mul(liquidation_threshold)?;
-            Ok(collateral_adjusted_for_threshold / Decimal::from_atomics(total_dsc_minted, 6)?)
+    /// Checks the health factor and the max LTV of a position from a single valuation.
+    /// Minting is capped by the max LTV of each enabled collateral, which is stricter than the
+    /// liquidation threshold backing the health factor
+    fn revert_if_account_is_unsafe(
+        deps: &Deps,
+        env: &Env,
+        user_addr: &Addr,
+        account_value: &queries::AccountCollateralValue,
+    ) -> Result<(), ContractError> {
+        let total_dsc_minted = queries::get_dsc_minted(deps, env, user_addr.as_str())?;
+        if total_dsc_minted.is_zero() {
+            return Ok(());
         }
-    }
+        revert_if_below_min_health_factor(
+            deps.storage,
+            queries::health_factor(total_dsc_minted, account_value.liquidation_value)?,
+        )?;
 
-    fn get_account_information(
-        deps: &DepsMut,
-        user_addr: String,
-    ) -> Result<AccountInfoResponse, ContractError> {
-        let total_dsc_minted =
-            DSC_MINTED.may_load(deps.storage, &deps.api.addr_validate(&user_addr)?)?;
-        let total_dsc_minted_parsed = match total_dsc_minted {
-            Some(amount) => amount,
-            None => Uint128::new(0),
-        };
-        let acc_info = AccountInfoResponse {
-            deposited_collateral_in_usd: get_account_collateral_value(&deps, user_addr.clone())?,
-            total_dsc_minted: total_dsc_minted_parsed,
-        };
-        Ok(acc_info)
+        let dsc_minted = queries::dsc_to_decimal(total_dsc_minted)?;
+        let borrowing_power = account_value.borrowing_power;
+        if dsc_minted > borrowing_power {
+            return Err(ContractError::MaxLtvExceeded {
+                dsc_minted,
+                borrowing_power,
+            });
+        }
+        Ok(())
     }
 
-    fn get_account_collateral_value(
-        deps: &DepsMut,
-        user_addr: String,
-    ) -> Result<Decimal, ContractError> {
-        let config = CONFIG.load(deps.storage)?;
+    /// Splits the normalized debt of `user` between its enabled collaterals, pro rata to their
+    /// `borrowing_powers`, and checks the debt ceiling of every collateral backing more of it
+    pub fn attribute_debt(
+        storage: &mut dyn Storage,
+        config: &Config,
+        debt_rate: &DebtRate,
+        user: &Addr,
+        borrowing_powers: &[(String, Decimal)],
+    ) -> Result<(), ContractError> {
+        for denom in split_debt(storage, user, borrowing_powers)? {
+            revert_if_debt_ceiling_exceeded(storage, config, debt_rate, &denom)?;
+        }
+        Ok(())
+    }
 
-        let collateral_list = config.assets;
+    /// Splits the normalized debt of `user` between its enabled collaterals, pro rata to their
+    /// `borrowing_powers`, and indexes the position again. Returns the collaterals backing more
+    /// of the debt than before. Without borrowing power the previous split is kept.
+    fn split_debt(
+        storage: &mut dyn Storage,
+        user: &Addr,
+        borrowing_powers: &[(String, Decimal)],
+    ) -> StdResult<Vec<String>> {
+        let mut grown = vec![];
+        let total_power = borrowing_powers
+            .iter()
+            .map(|(_, power)| *power)
+            .sum::<Decimal>();
+        if !total_power.is_zero() {
+            let normalized_debt = DSC_MINTED.may_load(storage, user)?.unwrap_or_default();
+            let previous_debts = COLLATERAL_DEBT
+                .prefix(user)
+                .range(storage, None, None, Order::Ascending)
+                .collect::<StdResult<HashMap<_, _>>>()?;
+            for denom in previous_debts.keys() {
+                set_collateral_debt(storage, user, denom.clone(), Uint128::zero())?;
+            }
+            for (denom, power) in borrowing_powers {
+                let debt = normalized_debt.multiply_ratio(power.atomics(), total_power.atomics());
+                set_collateral_debt(storage, user, denom.clone(), debt)?;
+                if debt > previous_debts.get(denom).copied().unwrap_or_default() {
+                    grown.push(denom.clone());
+                }
+            }
+        }
+        update_redemption_index(storage, user)?;
+        Ok(grown)
+    }
 
-        let mut user_deposited_balance_usd = Decimal::new(Uint128::zero());
+    /// Splits the debt of `user` again at `prices` once collateral was seized or redeemed from
+    /// its position. Debt ceilings are not checked, liquidations and redemptions must go on.
+    fn resplit_debt(
+        deps: &mut DepsMut,
+        config: &Config,
+        user: &Addr,
+        prices: &queries::AssetPrices,
+    ) -> Result<(), ContractError> {
+        let balances = queries::get_collateral_balances(&deps.as_ref(), config, user)?;
+        let account_value = queries::value_collateral(&deps.as_ref(), config, &balances, prices)?;
+        split_debt(deps.storage, user, &account_value.borrowing_power_by_asset)?;
+        Ok(())
+    }
 
-        for collateral_asset in collateral_list {
-            let user_collateral_balance = COLLATERAL_DEPOSITED.may_load(
-                deps.storage,
-                (
-                    &deps.api.addr_validate(&user_addr)?,
-                    collateral_asset.inner(),
-                ),
+    fn revert_if_debt_ceiling_exceeded(
+        storage: &dyn Storage,
+        config: &Config,
+        debt_rate: &DebtRate,
+        denom: &str,
+    ) -> Result<(), ContractError> {
+        let collateral_params = load_collateral_params(storage, config, denom)?;
+        if let Some(debt_ceiling) = collateral_params.debt_ceiling {
+            let backed_debt = debt_rate.debt(
+                TOTAL_COLLATERAL_DEBT
+                    .may_load(storage, denom.to_string())?
+                    .unwrap_or_default(),
             )?;
-            let user_collateral_balance_usd: Decimal = match user_collateral_balance {
-                Some(balance) => exec::get_usd_value(&deps, &collateral_asset, balance)?,
-                None => Decimal::new(Uint128::zero()),
-            };
-            user_deposited_balance_usd += user_collateral_balance_usd;
+            if backed_debt > debt_ceiling {
+                return Err(ContractError::DebtCeilingExceeded {
+                    denom: denom.to_string(),
+                });
+            }
         }
-
-        Ok(user_deposited_balance_usd)
+        Ok(())
     }
 
-    fn get_usd_value(
+    fn get_health_factor(
         deps: &DepsMut,
-        asset: &AssetInfo,
-        amount: Uint128,
+        env: &Env,
+        user: String,
+        freshness: PriceFreshness,
     ) -> Result<Decimal, ContractError> {
-        let amount_decimals: u32 = 6;
-        let config = CONFIG.load(deps.storage)?;
-        let asset_denom = asset.inner();
-        let price_feed_id = config.assets_to_feeds.get(&asset_denom).unwrap();
-        let oracle_res = query_price_from_oracle(
-            &deps.querier,
-            config.oracle_address.to_string(),
-            config.pyth_oracle_address.to_string(),
-            PriceIdentifier::from_hex(price_feed_id)?,
-        )?;
-        let asset_price_usd = Decimal::from_atomics(
-            Uint128::from(oracle_res.current_price.price as u64),
-            oracle_res.current_price.expo.abs() as u32,
-        )?;
-        let amount = Decimal::from_atomics(amount, amount_decimals)?;
-        Ok(amount.checked_mul(asset_price_usd)?)
-    }
-
-    fn query_price_from_oracle(
-        querier: &QuerierWrapper,
-        oracle_address: String,
-        pyth_oracle_address: String,
-        price_feed_id: PriceIdentifier,
-    ) -> Result<FetchPriceResponse, ContractError> {
-        let asset_price_usd = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: oracle_address,
-            msg: to_json_binary(&OracleQueryMsg::FetchPrice {
-                pyth_contract_addr: pyth_oracle_address,
-                price_feed_id: price_feed_id,
-            })?,
-        }))?;
-        Ok(asset_price_usd)
+        Ok(queries::get_health_factor(
+            &deps.as_ref(),
+            env,
+            user,
+            freshness,
+        )?)
     }
 
-    fn _get_token_amount_from_usd(
+    /// Prices of the collateral seized and of every collateral held by the liquidated user and
+    /// the liquidator, fetched in a single oracle query
+    fn get_liquidation_prices(
         deps: &DepsMut,
-        asset_denom: String,
-        usd_amount: Decimal,
-    ) -> Result<Decimal, ContractError> {
-        let config = CONFIG.load(deps.storage)?;
-        let price_feed_id = config.assets_to_feeds.get(&asset_denom).unwrap();
-        let oracle_res = query_price_from_oracle(
-            &deps.querier,
-            config.oracle_address.to_string(),
-            config.pyth_oracle_address.to_string(),
-            PriceIdentifier::from_hex(price_feed_id)?,
-        )?;
-        let asset_price_usd = Decimal::from_atomics(
-            Uint128::from(oracle_res.current_price.price as u64),
-            oracle_res.current_price.expo.abs() as u32,
-        )?;
-        Ok(usd_amount / asset_price_usd)
+        config: &Config,
+        user_addr: &Addr,
+        liquidator: &Addr,
+        collateral_asset: &AssetInfo,
+    ) -> Result<queries::AssetPrices, ContractError> {
+        let deps = deps.as_ref();
+        let mut assets = BTreeSet::from([collateral_asset.inner()]);
+        for addr in [user_addr, liquidator] {
+            for (denom, _) in queries::get_collateral_balances(&deps, config, addr)? {
+                assets.insert(denom);
+            }
+        }
+        Ok(queries::get_asset_prices(
+            &deps,
+            config,
+            assets.into_iter().collect(),
+            PriceFreshness::Fresh,
+        )?)
     }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Decimal, DecimalRangeExceeded, OverflowError, StdError};
+use cosmwasm_std::{Coin, Decimal, DecimalRangeExceeded, OverflowError, StdError, Uint128};
 use hex::FromHexError;
 use thiserror::Error;
 
@@ -19,9 +19,6 @@ pub enum ContractError {
         min_value: Decimal,
     },
 
-    #[error("Token addresses and price feed ids lengths don't match")]
-    TokenAddressesAndPriceFeedIdsLengthsDontMatch {},
-
     #[error("Health factor from liquidated user not improved")]
     HealthFactorNotImproved {},
 
@@ -31,6 +28,15 @@ pub enum ContractError {
     #[error("Missing Native Asset Funds")]
     MissingNativeFunds { denom: String },
 
+    #[error("Native funds sent are not declared as collateral: {denom}")]
+    UnexpectedNativeFunds { denom: String },
+
+    #[error("Pyth update fee not paid: {fee}")]
+    InsufficientUpdateFee { fee: Coin },
+
+    #[error("No collateral asset to deposit")]
+    EmptyCollateralList {},
+
     #[error("Cannot set to own account")]
     CannotSetOwnAccount {},
 
@@ -51,6 +57,158 @@ pub enum ContractError {
 
     #[error("Duplicate initial balance addresses")]
     DuplicateInitialBalanceAddresses {},
+
+    #[error("Liquidation threshold must be greater than 0 and at most 100")]
+    InvalidLiquidationThreshold {},
+
+    #[error("Liquidation bonus must be lower than 100")]
+    InvalidLiquidationBonus {},
+
+    #[error(
+        "Min health factor must be greater than liquidation threshold * (1 + liquidation bonus)"
+    )]
+    InvalidMinHealthFactor {},
+
+    #[error("Max LTV must be at most the liquidation threshold")]
+    InvalidMaxLtv {},
+
+    #[error("Collateral asset is disabled")]
+    CollateralAssetDisabled { denom: String },
+
+    #[error("Collateral asset debt ceiling exceeded")]
+    DebtCeilingExceeded { denom: String },
+
+    #[error("DSC minted exceeds the max LTV of the deposited collateral")]
+    MaxLtvExceeded {
+        dsc_minted: Decimal,
+        borrowing_power: Decimal,
+    },
+
+    #[error("Stability fee must be at most 1")]
+    InvalidStabilityFee {},
+
+    #[error("Close factor must be greater than 0 and at most 1")]
+    InvalidCloseFactor {},
+
+    #[error("Full liquidation health factor must be at most the min health factor")]
+    InvalidFullLiquidationHealthFactor {},
+
+    #[error("Debt to cover exceeds the max liquidatable debt: {max_debt_to_cover}")]
+    DebtToCoverExceedsMax { max_debt_to_cover: Decimal },
+
+    #[error("Collateral asset is liquidated through auctions")]
+    AuctionLiquidationOnly { denom: String },
+
+    #[error("Collateral asset is liquidated at a fixed bonus")]
+    FixedBonusLiquidationOnly { denom: String },
+
+    #[error("Auction starting premium must be at least 1, duration greater than 0 and reset price ratio lower than 1")]
+    InvalidAuctionParams {},
+
+    #[error("Auction {id} not found")]
+    AuctionNotFound { id: u64 },
+
+    #[error("Auction {id} must be reset")]
+    AuctionNeedsReset { id: u64 },
+
+    #[error("Auction {id} cannot be reset yet")]
+    AuctionNotResettable { id: u64 },
+
+    #[error("Auction price {price} is above the max price")]
+    AuctionPriceTooHigh { price: Decimal },
+
+    #[error("Invalid protocol fees")]
+    InvalidProtocolFees {},
+
+    #[error("Surplus of {asset} is only {available}")]
+    SurplusExceeded { asset: String, available: Uint128 },
+
+    #[error("DSC surplus cannot be withdrawn while there is bad debt")]
+    BadDebtOutstanding {},
+
+    #[error("Surplus and debt auctions are not configured")]
+    BalanceSheetAuctionsDisabled {},
+
+    #[error("Invalid surplus and debt auction parameters")]
+    InvalidBalanceSheetAuctionParams {},
+
+    #[error("Surplus does not exceed the buffer by a surplus auction lot")]
+    InsufficientSurplusForAuction {},
+
+    #[error("Bad debt left to auction is below a debt auction lot")]
+    InsufficientBadDebtForAuction {},
+
+    #[error("Surplus or debt auction {id} not found")]
+    BalanceSheetAuctionNotFound { id: u64 },
+
+    #[error("Surplus or debt auction {id} has ended")]
+    BalanceSheetAuctionEnded { id: u64 },
+
+    #[error("Surplus or debt auction {id} has not ended")]
+    BalanceSheetAuctionNotEnded { id: u64 },
+
+    #[error("Bid does not improve the current one by the min bid increment")]
+    BidNotImproved {},
+
+    #[error("Debt auction bids must pay {bid} DSC")]
+    InvalidDebtAuctionBid { bid: Uint128 },
+
+    #[error("No position to redeem {denom} from")]
+    NothingToRedeem { denom: String },
+
+    #[error("Redemption fee rate {fee_rate} exceeds max fee {max_fee}")]
+    RedemptionFeeExceeded { fee_rate: Decimal, max_fee: Decimal },
+
+    #[error("No bad debt to settle")]
+    NoBadDebt {},
+
+    #[error("No surplus to settle bad debt with")]
+    NoSurplus {},
+
+    #[error("Action is paused")]
+    ActionPaused { action: String },
+
+    #[error("Protocol is shut down")]
+    ContractShutdown {},
+
+    #[error("Protocol is not shut down")]
+    ContractNotShutdown {},
+
+    #[error("Position must be settled first")]
+    PositionNotSettled {},
+
+    #[error("Every position must be settled first")]
+    DebtNotSettled {},
+
+    #[error("Received cw20 token cannot be used for this operation")]
+    InvalidCw20Token { token: String },
+
+    #[error("Collateral asset already registered")]
+    CollateralAssetAlreadyRegistered { denom: String },
+
+    #[error("Collateral asset still has deposits")]
+    CollateralAssetInUse { denom: String },
+
+    #[error("No pending ownership proposal")]
+    NoOwnershipProposal {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Missing decimals of native asset")]
+    MissingNativeDecimals { denom: String },
+
+    #[error("Decimals don't match the token contract")]
+    InvalidAssetDecimals { denom: String },
+
+    #[error("Cannot migrate from a different contract")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from a newer contract version")]
+    CannotDowngrade {
+        stored_version: String,
+        new_version: String,
+    },
 }
 
 impl From<cw20_base::ContractError> for ContractError {
@@ -96,3 +254,9 @@ impl From<FromHexError> for ContractError {
         ContractError::Std(StdError::generic_err(error.to_string()))
     }
 }
+
+impl From<semver::Error> for ContractError {
+    fn from(error: semver::Error) -> Self {
+        ContractError::Std(StdError::generic_err(error.to_string()))
+    }
+}
@@ -1,5 +1,7 @@
+pub mod auctions;
 pub mod contract;
 mod error;
+mod migrations;
 pub mod msg;
 pub mod queries;
 pub mod state;
@@ -0,0 +1,101 @@
+use cosmwasm_std::{Decimal, Deps, DepsMut, Env, Order, StdResult, Uint128};
+use cw_asset::AssetInfo;
+use semver::Version;
+use std::collections::HashMap;
+
+use crate::contract::exec::{attribute_debt, query_asset_decimals};
+use crate::error::ContractError;
+use crate::queries::{get_account_collateral_value, PriceFreshness};
+use crate::state::{
+    DebtRate, COLLATERAL_DEPOSITED, COLLATERAL_PARAMS, CONFIG, DEBT_RATE, DSC_MINTED,
+    LEGACY_ASSET_DECIMALS, TOTAL_COLLATERAL_DEPOSITED, TOTAL_NORMALIZED_DEBT,
+};
+
+/// Runs, in order, every state migration needed to bring the storage written by
+/// `stored_version` up to the current contract version
+pub fn migrate_state(
+    deps: DepsMut,
+    env: &Env,
+    stored_version: &Version,
+) -> Result<(), ContractError> {
+    if *stored_version < Version::new(1, 1, 0) {
+        migrate_from_1_0_0(deps, env)?;
+    }
+    Ok(())
+}
+
+/// 1.0.0 -> 1.1.0
+/// - `Config.owner` became optional so that ownership can be renounced
+/// - `TOTAL_COLLATERAL_DEPOSITED` didn't exist and is rebuilt from users deposits
+/// - `Config.assets_decimals` didn't exist: cw20 decimals are queried from the token contracts,
+///   native assets keep the 6 decimals assumed by 1.0.0
+/// - `COLLATERAL_PARAMS` didn't exist: every asset gets the global liquidation threshold and
+///   bonus it was using, with max_ltv equal to the liquidation threshold
+/// - There was no stability fee: it starts at 0 with a rate of 1, so `DSC_MINTED` balances
+///   are already normalized debts and `TOTAL_NORMALIZED_DEBT` is their sum
+/// - `COLLATERAL_DEBT` didn't exist: the debt of every position is split between its collaterals
+///   at the last good prices, pro rata to their value since max_ltv was the liquidation threshold
+/// - `REDEMPTION_INDEX` didn't exist and is built from every position with debt
+fn migrate_from_1_0_0(deps: DepsMut, env: &Env) -> Result<(), ContractError> {
+    let storage = deps.storage;
+    let querier = deps.querier;
+    let api = deps.api;
+    let mut config = CONFIG.load(storage)?;
+    for asset in &config.assets {
+        let decimals = match asset {
+            AssetInfo::Cw20(_) => query_asset_decimals(&querier, asset, None)?,
+            _ => LEGACY_ASSET_DECIMALS,
+        };
+        config.assets_decimals.insert(asset.inner(), decimals);
+        COLLATERAL_PARAMS.save(storage, asset.inner(), &config.default_collateral_params())?;
+    }
+    CONFIG.save(storage, &config)?;
+
+    let deposits = COLLATERAL_DEPOSITED
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut total_collateral_deposited: HashMap<String, Uint128> = HashMap::new();
+    for ((_, collateral_asset), amount) in deposits {
+        *total_collateral_deposited
+            .entry(collateral_asset)
+            .or_default() += amount;
+    }
+    for (collateral_asset, amount) in total_collateral_deposited {
+        TOTAL_COLLATERAL_DEPOSITED.save(storage, collateral_asset, &amount)?;
+    }
+
+    DEBT_RATE.save(
+        storage,
+        &DebtRate {
+            rate: Decimal::one(),
+            last_accrual: env.block.time.seconds(),
+        },
+    )?;
+    let total_normalized_debt = DSC_MINTED
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, amount)| amount))
+        .sum::<StdResult<Uint128>>()?;
+    TOTAL_NORMALIZED_DEBT.save(storage, &total_normalized_debt)?;
+
+    let users = DSC_MINTED
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let debt_rate = DEBT_RATE.load(storage)?;
+    for user in users {
+        let deps = Deps {
+            storage,
+            api,
+            querier,
+        };
+        let account_value =
+            get_account_collateral_value(&deps, user.to_string(), PriceFreshness::CachedAllowed)?;
+        attribute_debt(
+            storage,
+            &config,
+            &debt_rate,
+            &user,
+            &account_value.borrowing_power_by_asset,
+        )?;
+    }
+    Ok(())
+}
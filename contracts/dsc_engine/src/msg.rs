@@ -1,66 +1,126 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Decimal, Uint128};
-use cw_asset::AssetInfo;
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_asset::{Asset, AssetInfo};
 pub use cw_controllers::ClaimsResponse;
+use cw_utils::Expiration;
 use std::collections::HashMap;
 
+use crate::state::{
+    Auction, BalanceSheetAuction, BalanceSheetAuctionParams, CollateralParams, GlobalSettlement,
+    PauseFlags,
+};
+
 #[cw_serde]
 pub struct InstantiateMsg {
     /// Address allowed to change contract parameters
     pub owner: String,
     /// AssetInfo of tokens that can be deposited and used as collateral
     pub assets: Vec<AssetInfo>,
-    /// address of protocol wrapper for pyth oracles
+    /// address of protocol wrapper for pyth oracles, whose feed registry prices every asset
     pub oracle_address: String,
-    /// pyth oracle contract address
-    /// https://docs.pyth.network/documentation/pythnet-price-feeds/cosmwasm
-    pub pyth_oracle_address: String,
-    /// pyth price feed id's for each asset that can be deposited and used as collateral
-    /// https://pyth.network/developers/price-feed-ids#cosmwasm-stable
-    pub price_feed_ids: Vec<String>,
+    /// key is native asset denom, value is its number of decimals
+    /// cw20 decimals are queried from the token contract
+    pub native_assets_decimals: HashMap<String, u8>,
     /// address of stable asset to be minted
     pub dsc_address: String,
+    /// default liquidation threshold of collateral assets, every asset starts with max_ltv equal to it
     /// liquidation threshold = 50 means you need to be 200% over-collateralized
     pub liquidation_threshold: Uint128,
+    /// default liquidation bonus of collateral assets
     /// liquidation_bonus = 10 means you get assets at a 10% discount when liquidating
     pub liquidation_bonus: Uint128,
     /// health factor that leads to liquidation
     pub min_health_factor: Decimal,
+    /// annualized fee accrued on minted DSC, 0.02 means debts grow by 2% a year
+    pub stability_fee: Decimal,
+    /// max share of the debt of a user that can be covered in one liquidation, 0.5 means half
+    /// of it, 1 means no limit
+    pub close_factor: Decimal,
+    /// below this health factor the whole debt of a user can be covered in one liquidation
+    pub full_liquidation_health_factor: Decimal,
+    /// debt, in DSC atomic units, that the close factor cannot leave behind: the whole debt can
+    /// be covered instead
+    pub liquidation_dust: Uint128,
+    /// share of the liquidation bonus kept as surplus, 0.1 means a tenth of the bonus collateral
+    pub liquidation_fee_share: Decimal,
+    /// share of the stability fees accrued that goes to the surplus
+    pub stability_fee_share: Decimal,
+    /// fee on minted DSC, added to the debt of the minter and kept as surplus, 0.005 means 0.5%
+    pub origination_fee: Decimal,
+    /// min fee on the collateral drawn by DSC redemptions, 0.005 means 0.5%
+    pub redemption_fee_floor: Decimal,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     /*
      * @param collateral_asset: asset you're depositing as collateral
      * @param amount_collateral: The amount of collateral you're depositing
      * @param amount_dsc_to_mint: The amount of DSC you want to mint
+     * @param price_update_data: optional Pyth price updates, see MintDsc
      * @notice This function will deposit your collateral and mint DSC in one transaction
      */
     DepositCollateralAndMintDsc {
         collateral_asset: AssetInfo,
         amount_collateral: Uint128,
         amount_dsc_to_mint: Uint128,
+        price_update_data: Option<Vec<Binary>>,
+    },
+    /*
+     * @param collateral_asset: asset you're depositing as collateral
+     * @param amount_collateral: The amount of collateral you're depositing
+     * @notice Native collateral must be sent as funds, cw20 collateral needs an allowance to DSC Engine
+     */
+    DepositCollateral {
+        collateral_asset: AssetInfo,
+        amount_collateral: Uint128,
+    },
+    /*
+     * @param assets: assets you're depositing as collateral with their amounts
+     * @notice Native collaterals must be sent as funds, exactly matching the declared amounts.
+     * cw20 collaterals need an allowance to DSC Engine
+     */
+    DepositCollaterals {
+        assets: Vec<Asset>,
+    },
+    /*
+     * @param amount_dsc_to_mint: The amount of DSC you want to mint
+     * @param price_update_data: optional Pyth price updates, forwarded to Pyth before the action.
+     * The update fee is taken from the funds sent, the rest of them goes to the action
+     * @notice You must have more collateral value than the minimum threshold
+     */
+    MintDsc {
+        amount_dsc_to_mint: Uint128,
+        price_update_data: Option<Vec<Binary>>,
     },
     /*
      * @param collateral_asset: asset deposited as collateral
      * @param amount_collateral: The amount of collateral you're depositing
      * @param amount_dsc_to_burn: The amount of DSC you want to burn
+     * @param price_update_data: optional Pyth price updates, see MintDsc
      * @notice This function will withdraw your collateral and burn DSC in one transaction
      */
     RedeemCollateralForDsc {
         collateral_asset: AssetInfo,
         amount_collateral: Uint128,
         amount_dsc_to_burn: Uint128,
+        price_update_data: Option<Vec<Binary>>,
     },
     /*
      * @param collateral_asset: The collateral asset you're redeeming
      * @param amount_collateral: The amount of collateral you're redeeming
+     * @param price_update_data: optional Pyth price updates, see MintDsc
      * @notice This function will redeem your collateral.
      * @notice If you have DSC minted, you will not be able to redeem until you burn your DSC
      */
     RedeemCollateral {
         collateral_asset: AssetInfo,
         amount_collateral: Uint128,
+        price_update_data: Option<Vec<Binary>>,
     },
     /*
      * @notice careful! You'll burn your DSC here! Make sure you want to do this...
@@ -76,17 +136,380 @@ pub enum ExecuteMsg {
      * In return, you have to burn your DSC to pay off their debt, but you don't pay off your own.
      * @param user: The user who is insolvent. They have to have a _healthFactor below MIN_HEALTH_FACTOR
      * @param debt_to_cover: The amount of DSC you want to burn to cover the user's debt.
+     * @param price_update_data: optional Pyth price updates, see MintDsc
      *
      * @notice: You can partially liquidate a user.
-     * @notice: You will get a 10% LIQUIDATION_BONUS for taking the users funds.
-     * @notice: This function working assumes that the protocol will be roughly 150% overcollateralized in order for this to work.
-     * @notice: A known bug would be if the protocol was only 100% collateralized, we wouldn't be able to liquidate anyone.
-     * For example, if the price of the collateral plummeted before anyone could be liquidated.
+     * @notice: debt_to_cover is limited by the close factor, see the MaxLiquidatable query.
+     * @notice: Only for collateral assets liquidated at a fixed bonus, see KickAuction.
+     * @notice: You will get the liquidation bonus of collateral_asset for taking the users funds.
+     * @notice: Once the collateral of user is worth no more than their debt plus the liquidation bonus,
+     * the close factor no longer applies and all of collateral_asset can be seized for the debt it covers.
+     * When no collateral is left, the rest of the debt is written off as bad debt, see SettleBadDebt.
      */
     Liquidate {
         collateral_asset: AssetInfo,
         user: String,
         debt_to_cover: Decimal,
+        price_update_data: Option<Vec<Binary>>,
+    },
+    /*
+     * @param amount: The amount of DSC you want to redeem, DSC Engine must be allowed to burn it
+     * @param collateral_asset: The collateral asset you get for it
+     * @param max_fee: max redemption fee rate you accept, 0.05 means 5% of the collateral drawn
+     * @param price_update_data: optional Pyth price updates, see MintDsc
+     * @notice Burns DSC for 1 usd of collateral_asset per DSC, minus the redemption fee, drawn from
+     * the positions holding the least collateral_asset per unit of debt first. Their debt is
+     * repaid by the DSC burnt.
+     * @notice The fee rate is the redemption fee floor plus a base rate, which grows with the share
+     * of the DSC supply redeemed and halves every 12 hours. The fee is kept as surplus.
+     * @notice At most 50 positions are redeemed against, only the DSC redeemed is burnt.
+     */
+    RedeemDsc {
+        amount: Uint128,
+        collateral_asset: AssetInfo,
+        max_fee: Decimal,
+        price_update_data: Option<Vec<Binary>>,
+    },
+    /*
+     * @param user: The user who is insolvent. They have to have a _healthFactor below MIN_HEALTH_FACTOR
+     * @param collateral_asset: The collateral asset sold, must be liquidated through auctions
+     * @notice Callable by anyone. Moves the debt a liquidation could cover, and the collateral a
+     * fixed bonus liquidation would seize for it, from the position to a new Dutch auction.
+     * @notice The auction starts at the oracle price times the starting premium of the asset and
+     * decays linearly to zero.
+     * @notice When the position is left without collateral, its whole debt moves to the auction and
+     * the debt the auction does not raise becomes bad debt.
+     */
+    KickAuction {
+        user: String,
+        collateral_asset: AssetInfo,
+    },
+    /*
+     * @param id: auction to buy collateral from
+     * @param max_collateral: max amount of collateral you want to buy
+     * @param max_price: max usd price you accept to pay for one whole token of collateral
+     * @notice Burns the DSC paid at the current auction price, DSC Engine must be allowed to burn it.
+     * You never pay for more than the debt left to raise.
+     * @notice Once the debt is raised the collateral left goes back to the position of the user.
+     * Once the collateral is sold the debt left becomes bad debt.
+     */
+    TakeAuction {
+        id: u64,
+        max_collateral: Uint128,
+        max_price: Decimal,
+    },
+    /*
+     * @param id: auction whose price decayed beyond its reset price ratio
+     * @notice Callable by anyone. Restarts the price from the current oracle price.
+     */
+    ResetAuction {
+        id: u64,
+    },
+    /*
+     * @notice Callable by anyone. Settles as much bad debt as the DSC surplus covers.
+     * @dev DSC can also be sent through ReceiveMsg::SettleBadDebt to settle the rest.
+     */
+    SettleBadDebt {},
+    /*
+     * @param params: governance token, lots, min bid increment and durations of surplus and
+     * debt auctions
+     * @notice Only callable by the owner. DSC Engine must be a minter of the governance token.
+     */
+    UpdateBalanceSheetAuctionParams {
+        params: BalanceSheetAuctionParams,
+    },
+    /*
+     * @notice Callable by anyone. Auctions a lot of the DSC surplus above the surplus buffer for
+     * governance tokens, which are burnt. Fails while there is bad debt.
+     * @dev Bid with ReceiveMsg::BidSurplusAuction
+     */
+    KickSurplusAuction {},
+    /*
+     * @notice Callable by anyone. Settles bad debt with the DSC surplus, then auctions newly
+     * minted governance tokens to raise a lot of the bad debt left.
+     * @dev Bid with ReceiveMsg::BidDebtAuction
+     */
+    KickDebtAuction {},
+    /*
+     * @param id: surplus or debt auction that ended
+     * @notice Callable by anyone. Pays the lot to the winning bidder.
+     * @notice An auction ends bid_duration after its last bid, or auction_duration after its kick.
+     */
+    SettleBalanceSheetAuction {
+        id: u64,
+    },
+    /*
+     * @param id: id of the surplus or debt auction
+     * @notice Callable by anyone once the protocol is shut down
+     * @notice Ends the auction without a winner. The current bid is refunded to its bidder,
+     * the surplus lot goes back to the surplus and the debt raised goes back to the bad debt.
+     */
+    YankBalanceSheetAuction {
+        id: u64,
+    },
+    /*
+     * @notice Entry point of cw20 `Send`, see ReceiveMsg for the operations it can carry.
+     * Collateral cw20s and DSC are used without any allowance.
+     */
+    Receive(Cw20ReceiveMsg),
+    /*
+     * @param sender: account the action runs for
+     * @param msg: action sent with Pyth price updates, without them
+     * @notice Only sent by DSC Engine to itself, once the Pyth prices are updated
+     */
+    AfterPriceUpdate {
+        sender: Addr,
+        msg: Box<ExecuteMsg>,
+    },
+    /*
+     * @param oracle_address: new address of protocol wrapper for pyth oracles
+     * @param liquidation_threshold: new default liquidation threshold of collateral assets
+     * @param liquidation_bonus: new default liquidation bonus of collateral assets
+     * @param min_health_factor: new health factor that leads to liquidation
+     * @param stability_fee: new annualized fee accrued on minted DSC, at most 1 (100% a year).
+     * Fees owed at the previous stability fee are accrued first.
+     * @param close_factor: new max share of a user debt covered in one liquidation, greater than
+     * 0 and at most 1
+     * @param full_liquidation_health_factor: new health factor below which the whole debt can be
+     * covered, at most min_health_factor
+     * @param liquidation_dust: new debt that the close factor cannot leave behind
+     * @param liquidation_fee_share: new share of the liquidation bonus kept as surplus, at most 1
+     * @param stability_fee_share: new share of the stability fees going to the surplus, at most 1.
+     * Fees owed at the previous share are accrued first.
+     * @param origination_fee: new fee on minted DSC, lower than 1
+     * @param redemption_fee_floor: new min fee on the collateral drawn by redemptions, at most 1
+     * @notice Only callable by the owner. Fields left empty keep their current value.
+     * @notice Defaults only apply to assets added without their own risk parameters, use
+     * UpdateCollateralParams to change the ones of a registered asset.
+     * @notice Risk parameters are rejected if liquidations could no longer improve the health
+     * factor of an unhealthy position, since every position below it would be stuck liquidatable.
+     */
+    UpdateConfig {
+        oracle_address: Option<String>,
+        liquidation_threshold: Option<Uint128>,
+        liquidation_bonus: Option<Uint128>,
+        min_health_factor: Option<Decimal>,
+        stability_fee: Option<Decimal>,
+        close_factor: Option<Decimal>,
+        full_liquidation_health_factor: Option<Decimal>,
+        liquidation_dust: Option<Uint128>,
+        liquidation_fee_share: Option<Decimal>,
+        stability_fee_share: Option<Decimal>,
+        origination_fee: Option<Decimal>,
+        redemption_fee_floor: Option<Decimal>,
+    },
+    /*
+     * @param asset: asset that can be deposited and used as collateral
+     * @param decimals: number of decimals of the asset, required for native assets.
+     * cw20 decimals are queried from the token contract and must match it if provided.
+     * @param params: risk parameters of the asset, defaults to the global liquidation threshold
+     * and bonus
+     * @notice Only callable by the owner
+     */
+    AddCollateralAsset {
+        asset: AssetInfo,
+        decimals: Option<u8>,
+        params: Option<CollateralParams>,
+    },
+    /*
+     * @param asset: registered collateral asset
+     * @param params: new risk parameters of the asset
+     * @notice Only callable by the owner
+     * @notice Same checks as UpdateConfig: liquidations of this asset must always be able to
+     * improve the health factor, and max_ltv cannot exceed the liquidation threshold.
+     */
+    UpdateCollateralParams {
+        asset: AssetInfo,
+        params: CollateralParams,
+    },
+    /*
+     * @param asset: collateral asset to be removed
+     * @notice Only callable by the owner
+     * @notice Fails while any user still has this asset deposited
+     */
+    RemoveCollateralAsset {
+        asset: AssetInfo,
+    },
+    /*
+     * @notice Accrues the stability fee on every debt up to the current block.
+     * @notice Callable by anyone. Every other message that reads or changes debts already accrues
+     * the stability fee first.
+     */
+    Drip {},
+    /*
+     * @param amount: asset and amount withdrawn from the surplus
+     * @param recipient: address receiving it
+     * @notice Only callable by the owner. DSC surplus is minted to the recipient, and cannot be
+     * withdrawn while there is bad debt to settle.
+     */
+    WithdrawSurplus {
+        amount: Asset,
+        recipient: String,
+    },
+    /*
+     * @param guardian: address allowed to pause actions, None removes the guardian
+     * @notice Only callable by the owner
+     */
+    UpdateGuardian {
+        guardian: Option<String>,
+    },
+    /*
+     * @param mint: pause or unpause minting DSC
+     * @param deposit: pause or unpause depositing collateral
+     * @param redeem: pause or unpause redeeming collateral
+     * @param liquidate: pause or unpause liquidations
+     * @notice Callable by the owner, and by the guardian to pause only.
+     * Fields left empty keep their current value.
+     */
+    UpdatePauseFlags {
+        mint: Option<bool>,
+        deposit: Option<bool>,
+        redeem: Option<bool>,
+        liquidate: Option<bool>,
+    },
+    /*
+     * @notice Only callable by the owner. Cannot be undone!
+     * @notice Freezes collateral prices, falling back on the oracle last good prices of stale
+     * feeds, and the stability fee, and stops minting, deposits, burns and liquidations. The
+     * collateral surplus goes to the settlement pot. Positions are then closed with
+     * SettlePosition and DSC holders get back collateral through RedeemAfterShutdown.
+     */
+    Shutdown {},
+    /*
+     * @param user: user whose position is settled
+     * @notice Callable by anyone once the protocol is shut down
+     * @notice Moves collateral worth the user debt at frozen prices to the settlement pot, and
+     * clears the debt. The user can then redeem the remaining collateral.
+     */
+    SettlePosition {
+        user: String,
+    },
+    /*
+     * @param id: id of the collateral auction
+     * @notice Callable by anyone once the protocol is shut down
+     * @notice Ends a live collateral auction, giving its collateral and debt back to the
+     * position it was taken from. The position can then be settled with SettlePosition.
+     */
+    YankAuction {
+        id: u64,
+    },
+    /*
+     * @param amount: amount of DSC to burn
+     * @notice Callable once the protocol is shut down and every position is settled
+     * @notice Burns your DSC for your pro-rata share, over the DSC supply, of every collateral
+     * in the settlement pot. DSC Engine must be allowed to burn amount.
+     */
+    RedeemAfterShutdown {
+        amount: Uint128,
+    },
+    /*
+     * @param owner: address proposed as the new owner
+     * @param expiry: optional expiration after which the proposal can no longer be accepted
+     * @notice Only callable by the owner. Replaces any pending proposal.
+     * @notice Ownership only moves once the proposed owner calls AcceptOwnership
+     */
+    ProposeNewOwner {
+        owner: String,
+        expiry: Option<Expiration>,
+    },
+    /*
+     * @notice Only callable by the proposed owner, before the proposal expires
+     */
+    AcceptOwnership {},
+    /*
+     * @notice Only callable by the owner. Drops the pending ownership proposal.
+     */
+    CancelOwnershipProposal {},
+    /*
+     * @notice Only callable by the owner
+     * @notice careful! After this no one will ever be able to change contract parameters
+     */
+    RenounceOwnership {},
+}
+
+impl ExecuteMsg {
+    /// Takes the Pyth price updates sent with an action, None when there are none
+    pub fn take_price_update_data(&mut self) -> Option<Vec<Binary>> {
+        match self {
+            ExecuteMsg::DepositCollateralAndMintDsc {
+                price_update_data, ..
+            }
+            | ExecuteMsg::MintDsc {
+                price_update_data, ..
+            }
+            | ExecuteMsg::RedeemCollateralForDsc {
+                price_update_data, ..
+            }
+            | ExecuteMsg::RedeemCollateral {
+                price_update_data, ..
+            }
+            | ExecuteMsg::Liquidate {
+                price_update_data, ..
+            }
+            | ExecuteMsg::RedeemDsc {
+                price_update_data, ..
+            } => price_update_data.take().filter(|data| !data.is_empty()),
+            _ => None,
+        }
+    }
+}
+
+/// Operations carried by the `msg` of a cw20 `Send` to DSC Engine
+#[cw_serde]
+pub enum ReceiveMsg {
+    /*
+     * @notice Deposits the cw20 sent, which must be a registered collateral asset
+     */
+    DepositCollateral {},
+    /*
+     * @param amount_dsc_to_mint: The amount of DSC you want to mint
+     * @notice Deposits the cw20 sent, which must be a registered collateral asset, and mints DSC
+     */
+    DepositCollateralAndMint {
+        amount_dsc_to_mint: Uint128,
+    },
+    /*
+     * @param on_behalf_of: user whose debt is repaid, defaults to the sender
+     * @notice Burns the DSC sent to repay a debt
+     */
+    RepayDebt {
+        on_behalf_of: Option<String>,
+    },
+    /*
+     * @param user: The user who is insolvent
+     * @param collateral_asset: The collateral asset taken from the user
+     * @notice Burns the DSC sent to cover the debt of user, see ExecuteMsg::Liquidate
+     */
+    Liquidate {
+        user: String,
+        collateral_asset: AssetInfo,
+    },
+    /*
+     * @notice Burns the DSC sent and adds it to the DSC surplus
+     */
+    DepositSurplus {},
+    /*
+     * @notice Settles bad debt with the DSC surplus first, then burns the DSC sent for the
+     * rest of it. DSC sent beyond the bad debt is sent back.
+     */
+    SettleBadDebt {},
+    /*
+     * @param id: surplus auction
+     * @notice Bids the governance tokens sent for the DSC lot, refunding the previous bidder.
+     * Each bid must offer min_bid_increment more than the last one.
+     */
+    BidSurplusAuction {
+        id: u64,
+    },
+    /*
+     * @param id: debt auction
+     * @param lot: governance tokens asked for the DSC sent, which must equal the auction bid
+     * @notice Each bid must ask min_bid_increment less than the last one. The first bid settles
+     * bad debt, later ones refund the previous bidder.
+     */
+    BidDebtAuction {
+        id: u64,
+        lot: Uint128,
     },
 }
 
@@ -95,6 +518,16 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    #[returns(OwnershipResponse)]
+    Ownership {},
+    #[returns(DebtRateResponse)]
+    DebtRate {},
+    #[returns(StatusResponse)]
+    Status {},
+    #[returns(CollateralParams)]
+    CollateralParams { asset: String },
+    #[returns(Vec<CollateralParamsResponse>)]
+    AllCollateralParams {},
     #[returns(Uint128)]
     CollateralBalanceOfUser {
         user: String,
@@ -111,6 +544,41 @@ pub enum QueryMsg {
         total_dsc_minted: Uint128,
         collateral_value_usd: Decimal,
     },
+    #[returns(MaxLiquidatableResponse)]
+    MaxLiquidatable {
+        user: String,
+        collateral_asset: String,
+    },
+    #[returns(AuctionResponse)]
+    Auction { id: u64 },
+    #[returns(Vec<AuctionResponse>)]
+    Auctions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// DSC, in atomic units, left uncovered by underwater positions and not yet settled
+    #[returns(Uint128)]
+    TotalBadDebt {},
+    /// Surplus of an asset, DSC included, in atomic units
+    #[returns(Uint128)]
+    Surplus { asset: String },
+    #[returns(Vec<SurplusResponse>)]
+    AllSurplus {},
+    #[returns(Option<BalanceSheetAuctionParams>)]
+    BalanceSheetAuctionParams {},
+    #[returns(BalanceSheetAuction)]
+    BalanceSheetAuction { id: u64 },
+    #[returns(Vec<BalanceSheetAuctionResponse>)]
+    BalanceSheetAuctions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Bad debt, in DSC atomic units, that debt auctions are raising
+    #[returns(Uint128)]
+    DebtOnAuction {},
+    /// Fee rate a redemption would pay at the current block, before its own base rate increase
+    #[returns(Decimal)]
+    RedemptionFeeRate {},
     #[returns(Decimal)]
     GetUsdValue { token: String, amount: Uint128 },
     #[returns(Decimal)]
@@ -123,32 +591,123 @@ pub enum QueryMsg {
 
 #[cw_serde]
 pub struct ConfigResponse {
-    /// Address allowed to change contract parameters
-    pub owner: Addr,
+    /// Address allowed to change contract parameters, None once ownership is renounced
+    pub owner: Option<Addr>,
+    /// Address allowed to pause actions
+    pub guardian: Option<Addr>,
     /// List of depositable asset infos
     pub assets: Vec<AssetInfo>,
-    /// key is asset deposited denom or address, value is price_feed_id (https://pyth.network/developers/price-feed-ids#cosmwasm-stable)
-    pub assets_to_feeds: HashMap<String, String>,
+    /// key is asset deposited denom or address, value is the number of decimals of the asset
+    pub assets_decimals: HashMap<String, u8>,
     /// pyth oracle contract address
     /// https://docs.pyth.network/documentation/pythnet-price-feeds/cosmwasm
     pub oracle_address: Addr,
-    /// pyth oracle contract address
-    /// https://docs.pyth.network/documentation/pythnet-price-feeds/cosmwasm
-    pub pyth_oracle_address: Addr,
     /// address of stable asset to be minted
     pub dsc_address: Addr,
+    /// default liquidation threshold of new collateral assets
     /// liquidation threshold = 50 means you need to be 200% over-collateralized
     pub liquidation_threshold: Uint128,
+    /// default liquidation bonus of new collateral assets
     /// liquidation_bonus = 10 means you get assets at a 10% discount when liquidating
     pub liquidation_bonus: Uint128,
     /// health factor that leads to liquidation
     pub min_health_factor: Decimal,
+    /// annualized fee accrued on minted DSC
+    pub stability_fee: Decimal,
+    /// max share of the debt of a user that can be covered in one liquidation
+    pub close_factor: Decimal,
+    /// below this health factor the whole debt of a user can be covered in one liquidation
+    pub full_liquidation_health_factor: Decimal,
+    /// debt, in DSC atomic units, that the close factor cannot leave behind
+    pub liquidation_dust: Uint128,
+    /// share of the liquidation bonus kept as surplus
+    pub liquidation_fee_share: Decimal,
+    /// share of the stability fees accrued that goes to the surplus
+    pub stability_fee_share: Decimal,
+    /// fee on minted DSC, added to the debt of the minter and kept as surplus
+    pub origination_fee: Decimal,
+    /// min fee on the collateral drawn by DSC redemptions
+    pub redemption_fee_floor: Decimal,
+}
+
+#[cw_serde]
+pub struct DebtRateResponse {
+    /// Stability fee index accrued up to the current block, debt = normalized debt * rate
+    pub rate: Decimal,
+    /// Block time, in seconds, of the last saved accrual
+    pub last_accrual: u64,
+    /// Sum of the normalized debt of every user
+    pub total_normalized_debt: Uint128,
+    /// Sum of the DSC debt of every user, stability fees included
+    pub total_debt: Uint128,
+}
+
+#[cw_serde]
+pub struct StatusResponse {
+    /// Paused actions
+    pub pause_flags: PauseFlags,
+    /// Global settlement, if the protocol is shut down
+    pub global_settlement: Option<GlobalSettlement>,
+    /// Key is collateral token denom/ address, value is the amount redeemable by DSC holders
+    pub settlement_collateral: HashMap<String, Uint128>,
+}
+
+#[cw_serde]
+pub struct BalanceSheetAuctionResponse {
+    pub id: u64,
+    pub auction: BalanceSheetAuction,
+}
+
+#[cw_serde]
+pub struct SurplusResponse {
+    /// Asset denom or address
+    pub asset: String,
+    /// Surplus of the asset, in atomic units
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CollateralParamsResponse {
+    /// Collateral asset
+    pub asset: AssetInfo,
+    /// Risk parameters of the asset
+    pub params: CollateralParams,
+}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    /// Address allowed to change contract parameters, None once ownership is renounced
+    pub owner: Option<Addr>,
+    /// Address that can accept the ownership, if a transfer is pending
+    pub pending_owner: Option<Addr>,
+    /// Expiration of the pending ownership transfer
+    pub pending_expiry: Option<Expiration>,
 }
 
 #[cw_serde]
 pub struct AccountInfoResponse {
     /// Collaterals deposited by user in usd
     pub deposited_collateral_in_usd: Decimal,
-    /// DCS Minted by user
+    /// DSC debt of user, accrued stability fees included
     pub total_dsc_minted: Uint128,
 }
+
+#[cw_serde]
+pub struct MaxLiquidatableResponse {
+    /// Max debt, in DSC, that a liquidation of collateral_asset can cover now, zero while the
+    /// user is healthy. Limited by the close factor and by the collateral_asset deposited
+    pub max_debt_to_cover: Decimal,
+    /// Collateral_asset seized for covering max_debt_to_cover, in atomic units, liquidation
+    /// bonus included
+    pub collateral_to_seize: Uint128,
+}
+
+#[cw_serde]
+pub struct AuctionResponse {
+    pub id: u64,
+    pub auction: Auction,
+    /// Usd price of one whole token of collateral at the current block
+    pub price: Decimal,
+    /// The auction cannot be taken until it is reset
+    pub needs_reset: bool,
+}
@@ -1,20 +1,47 @@
+#[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Decimal, Deps, Env, QuerierWrapper, QueryRequest, StdResult,
-    Uint128, WasmQuery,
+    to_json_binary, Addr, Binary, Decimal, Deps, Env, Order, QuerierWrapper, QueryRequest,
+    StdError, StdResult, Uint128, WasmQuery,
 };
-#[cfg(not(feature = "library"))]
-use cw_asset::AssetInfo;
 
-use crate::msg::{AccountInfoResponse, ConfigResponse, QueryMsg};
-use crate::state::{COLLATERAL_DEPOSITED, CONFIG, DSC_MINTED};
-use oracle::msg::{FetchPriceResponse, QueryMsg as OracleQueryMsg};
+use crate::contract::exec::to_atomics;
+use crate::contract::DSC_DECIMALS;
+use crate::msg::{
+    AccountInfoResponse, AuctionResponse, BalanceSheetAuctionResponse, CollateralParamsResponse,
+    ConfigResponse, DebtRateResponse, MaxLiquidatableResponse, OwnershipResponse, QueryMsg,
+    StatusResponse, SurplusResponse,
+};
+use crate::state::{
+    load_collateral_params, load_debt_rate, load_redemption_rate, Auction, BalanceSheetAuction,
+    CollateralParams, Config, PricingPolicy, AUCTIONS, BAD_DEBT, BALANCE_SHEET_AUCTIONS,
+    BALANCE_SHEET_AUCTION_PARAMS, COLLATERAL_DEPOSITED, CONFIG, DEBT_ON_AUCTION, DEBT_RATE,
+    DSC_MINTED, GLOBAL_SETTLEMENT, OWNERSHIP_PROPOSAL, PAUSE_FLAGS, SETTLEMENT_COLLATERAL, SURPLUS,
+    TOTAL_NORMALIZED_DEBT,
+};
+use cw_storage_plus::Bound;
+use oracle::msg::{
+    AssetPriceResponse, ConfigResponse as OracleConfigResponse, QueryMsg as OracleQueryMsg,
+    UsdPriceResponse,
+};
 use pyth_sdk_cw::PriceIdentifier;
+use std::collections::HashMap;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(&deps)?),
+        QueryMsg::Ownership {} => to_json_binary(&query_ownership(&deps)?),
+        QueryMsg::DebtRate {} => to_json_binary(&query_debt_rate(&deps, &env)?),
+        QueryMsg::Status {} => to_json_binary(&query_status(&deps)?),
+        QueryMsg::CollateralParams { asset } => {
+            to_json_binary(&query_collateral_params(&deps, asset)?)
+        }
+        QueryMsg::AllCollateralParams {} => to_json_binary(&query_all_collateral_params(&deps)?),
         QueryMsg::CollateralBalanceOfUser {
             user,
             collateral_asset,
@@ -24,10 +51,10 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             collateral_asset,
         )?),
         QueryMsg::UserHealthFactor { user } => {
-            to_json_binary(&query_user_health_factor(&deps, user)?)
+            to_json_binary(&query_user_health_factor(&deps, &env, user)?)
         }
         QueryMsg::AccountInformation { user } => {
-            to_json_binary(&query_account_information(&deps, user)?)
+            to_json_binary(&query_account_information(&deps, &env, user)?)
         }
         QueryMsg::AccountCollateralValueUsd { user } => {
             to_json_binary(&query_account_collateral_value_usd(&deps, user)?)
@@ -40,8 +67,39 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             total_dsc_minted,
             collateral_value_usd,
         )?),
+        QueryMsg::MaxLiquidatable {
+            user,
+            collateral_asset,
+        } => to_json_binary(&query_max_liquidatable(
+            &deps,
+            &env,
+            user,
+            collateral_asset,
+        )?),
+        QueryMsg::Auction { id } => to_json_binary(&query_auction(&deps, &env, id)?),
+        QueryMsg::Auctions { start_after, limit } => {
+            to_json_binary(&query_auctions(&deps, &env, start_after, limit)?)
+        }
+        QueryMsg::TotalBadDebt {} => {
+            to_json_binary(&BAD_DEBT.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::Surplus { asset } => to_json_binary(&query_surplus(&deps, &env, asset)?),
+        QueryMsg::AllSurplus {} => to_json_binary(&query_all_surplus(&deps, &env)?),
+        QueryMsg::BalanceSheetAuctionParams {} => {
+            to_json_binary(&BALANCE_SHEET_AUCTION_PARAMS.may_load(deps.storage)?)
+        }
+        QueryMsg::BalanceSheetAuction { id } => {
+            to_json_binary(&query_balance_sheet_auction(&deps, id)?)
+        }
+        QueryMsg::BalanceSheetAuctions { start_after, limit } => {
+            to_json_binary(&query_balance_sheet_auctions(&deps, start_after, limit)?)
+        }
+        QueryMsg::DebtOnAuction {} => {
+            to_json_binary(&DEBT_ON_AUCTION.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::RedemptionFeeRate {} => to_json_binary(&query_redemption_fee_rate(&deps, &env)?),
         QueryMsg::GetUsdValue { token, amount } => {
-            to_json_binary(&get_usd_value(&deps, token, amount)?)
+            to_json_binary(&get_usd_value(&deps, token, amount, PriceFreshness::Fresh)?)
         }
         QueryMsg::GetTokenAmountFromUsd { token, usd_amount } => {
             to_json_binary(&get_token_amount_from_usd(&deps, token, usd_amount)?)
@@ -59,18 +117,37 @@ pub fn query_config(deps: &Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.may_load(deps.storage)?.unwrap();
     let config_res = ConfigResponse {
         owner: config.owner,
+        guardian: config.guardian,
         assets: config.assets,
-        assets_to_feeds: config.assets_to_feeds,
+        assets_decimals: config.assets_decimals,
         oracle_address: config.oracle_address,
-        pyth_oracle_address: config.pyth_oracle_address,
         dsc_address: config.dsc_address,
         liquidation_threshold: config.liquidation_threshold,
         liquidation_bonus: config.liquidation_bonus,
         min_health_factor: config.min_health_factor,
+        stability_fee: config.stability_fee,
+        close_factor: config.close_factor,
+        full_liquidation_health_factor: config.full_liquidation_health_factor,
+        liquidation_dust: config.liquidation_dust,
+        liquidation_fee_share: config.liquidation_fee_share,
+        stability_fee_share: config.stability_fee_share,
+        origination_fee: config.origination_fee,
+        redemption_fee_floor: config.redemption_fee_floor,
     };
     Ok(config_res)
 }
 
+pub fn query_ownership(deps: &Deps) -> StdResult<OwnershipResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let proposal = OWNERSHIP_PROPOSAL.may_load(deps.storage)?;
+    let ownership_res = OwnershipResponse {
+        owner: config.owner,
+        pending_owner: proposal.as_ref().map(|proposal| proposal.owner.clone()),
+        pending_expiry: proposal.and_then(|proposal| proposal.expiry),
+    };
+    Ok(ownership_res)
+}
+
 pub fn query_collateral_balance_of_user(
     deps: &Deps,
     user: String,
@@ -85,51 +162,356 @@ pub fn query_collateral_balance_of_user(
     }
 }
 
+pub fn query_debt_rate(deps: &Deps, env: &Env) -> StdResult<DebtRateResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let debt_rate = load_debt_rate(deps.storage, &config, env.block.time.seconds())?;
+    let total_normalized_debt = TOTAL_NORMALIZED_DEBT
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let debt_rate_res = DebtRateResponse {
+        rate: debt_rate.rate,
+        last_accrual: debt_rate.last_accrual,
+        total_normalized_debt,
+        total_debt: debt_rate.debt(total_normalized_debt)?,
+    };
+    Ok(debt_rate_res)
+}
+
+pub fn query_status(deps: &Deps) -> StdResult<StatusResponse> {
+    let status_res = StatusResponse {
+        pause_flags: PAUSE_FLAGS.may_load(deps.storage)?.unwrap_or_default(),
+        global_settlement: GLOBAL_SETTLEMENT.may_load(deps.storage)?,
+        settlement_collateral: SETTLEMENT_COLLATERAL
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?,
+    };
+    Ok(status_res)
+}
+
+pub fn query_collateral_params(deps: &Deps, asset: String) -> StdResult<CollateralParams> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.is_collateral_asset(&asset) {
+        return Err(StdError::not_found(format!("collateral asset {asset}")));
+    }
+    load_collateral_params(deps.storage, &config, &asset)
+}
+
+pub fn query_all_collateral_params(deps: &Deps) -> StdResult<Vec<CollateralParamsResponse>> {
+    let config = CONFIG.load(deps.storage)?;
+    config
+        .assets
+        .iter()
+        .map(|asset| {
+            Ok(CollateralParamsResponse {
+                asset: asset.clone(),
+                params: load_collateral_params(deps.storage, &config, &asset.inner())?,
+            })
+        })
+        .collect()
+}
+
 pub fn query_account_collateral_value_usd(deps: &Deps, user_addr: String) -> StdResult<Decimal> {
-    get_account_collateral_value(deps, user_addr)
+    Ok(get_account_collateral_value(deps, user_addr, PriceFreshness::Fresh)?.total)
 }
 
-pub fn query_account_information(deps: &Deps, user_addr: String) -> StdResult<AccountInfoResponse> {
-    get_account_information(deps, user_addr)
+pub fn query_account_information(
+    deps: &Deps,
+    env: &Env,
+    user_addr: String,
+) -> StdResult<AccountInfoResponse> {
+    get_account_information(deps, env, user_addr)
 }
 
-pub fn query_user_health_factor(deps: &Deps, user: String) -> StdResult<Decimal> {
-    Ok(get_health_factor(deps, user)?)
+pub fn query_user_health_factor(deps: &Deps, env: &Env, user: String) -> StdResult<Decimal> {
+    get_health_factor(deps, env, user, PriceFreshness::Fresh)
 }
 
+/// Health factor of a position backed by `collateral_value_in_usd`, assuming every collateral
+/// uses the default liquidation threshold
 pub fn calculate_health_factor(
     deps: &Deps,
     total_dsc_minted: Uint128,
     collateral_value_in_usd: Decimal,
+) -> StdResult<Decimal> {
+    let config = CONFIG.load(deps.storage)?;
+    let liquidation_threshold = config
+        .default_collateral_params()
+        .liquidation_threshold_ratio();
+    health_factor(
+        total_dsc_minted,
+        collateral_value_in_usd.checked_mul(liquidation_threshold)?,
+    )
+}
+
+pub fn query_max_liquidatable(
+    deps: &Deps,
+    env: &Env,
+    user: String,
+    collateral_asset: String,
+) -> StdResult<MaxLiquidatableResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let user_addr = deps.api.addr_validate(&user)?;
+    let quote = get_liquidation_quote(deps, env, &config, &user_addr, &collateral_asset)?;
+    if quote.health_factor >= config.min_health_factor {
+        return Ok(MaxLiquidatableResponse {
+            max_debt_to_cover: Decimal::zero(),
+            collateral_to_seize: Uint128::zero(),
+        });
+    }
+    Ok(MaxLiquidatableResponse {
+        max_debt_to_cover: quote.max_debt_to_cover,
+        collateral_to_seize: quote.collateral_to_seize,
+    })
+}
+
+/// Surplus of an asset, with the share of the stability fees accrued up to the current block
+pub fn query_surplus(deps: &Deps, env: &Env, asset: String) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let surplus = SURPLUS
+        .may_load(deps.storage, asset.clone())?
+        .unwrap_or_default();
+    if asset != config.dsc_address.as_str() {
+        return Ok(surplus);
+    }
+    let Some(previous_debt_rate) = DEBT_RATE.may_load(deps.storage)? else {
+        return Ok(surplus);
+    };
+    let debt_rate = load_debt_rate(deps.storage, &config, env.block.time.seconds())?;
+    let total_normalized_debt = TOTAL_NORMALIZED_DEBT
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let fees_accrued = debt_rate
+        .debt(total_normalized_debt)?
+        .saturating_sub(previous_debt_rate.debt(total_normalized_debt)?);
+    Ok(surplus + fees_accrued * config.stability_fee_share)
+}
+
+pub fn query_all_surplus(deps: &Deps, env: &Env) -> StdResult<Vec<SurplusResponse>> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut assets: Vec<String> = SURPLUS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    if !assets
+        .iter()
+        .any(|asset| asset == config.dsc_address.as_str())
+    {
+        assets.push(config.dsc_address.to_string());
+    }
+    assets
+        .into_iter()
+        .map(|asset| {
+            Ok(SurplusResponse {
+                amount: query_surplus(deps, env, asset.clone())?,
+                asset,
+            })
+        })
+        .collect()
+}
+
+pub fn query_redemption_fee_rate(deps: &Deps, env: &Env) -> StdResult<Decimal> {
+    let config = CONFIG.load(deps.storage)?;
+    let redemption_rate = load_redemption_rate(deps.storage, env.block.time.seconds())?;
+    Ok(redemption_fee_rate(&config, redemption_rate.base_rate))
+}
+
+/// Redemption fee rate for a base rate, capped at 100%
+pub fn redemption_fee_rate(config: &Config, base_rate: Decimal) -> Decimal {
+    (config.redemption_fee_floor + base_rate).min(Decimal::one())
+}
+
+pub fn query_auction(deps: &Deps, env: &Env, id: u64) -> StdResult<AuctionResponse> {
+    let auction = AUCTIONS
+        .may_load(deps.storage, id)?
+        .ok_or_else(|| StdError::not_found(format!("auction {id}")))?;
+    Ok(auction_response(env, id, auction))
+}
+
+pub fn query_auctions(
+    deps: &Deps,
+    env: &Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AuctionResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    AUCTIONS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(id, auction)| auction_response(env, id, auction)))
+        .collect()
+}
+
+pub fn query_balance_sheet_auction(deps: &Deps, id: u64) -> StdResult<BalanceSheetAuction> {
+    BALANCE_SHEET_AUCTIONS
+        .may_load(deps.storage, id)?
+        .ok_or_else(|| StdError::not_found(format!("surplus or debt auction {id}")))
+}
+
+pub fn query_balance_sheet_auctions(
+    deps: &Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BalanceSheetAuctionResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    BALANCE_SHEET_AUCTIONS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(id, auction)| BalanceSheetAuctionResponse { id, auction }))
+        .collect()
+}
+
+fn auction_response(env: &Env, id: u64, auction: Auction) -> AuctionResponse {
+    let now = env.block.time.seconds();
+    AuctionResponse {
+        id,
+        price: auction.price(now),
+        needs_reset: auction.needs_reset(now),
+        auction,
+    }
+}
+
+/// A position is underwater once its collateral is worth no more than its debt plus the
+/// liquidation bonus: liquidations can no longer improve its health factor, so its collateral
+/// can be seized in full and the debt it leaves uncovered becomes bad debt
+pub fn is_underwater(
+    collateral_value: Decimal,
+    total_dsc_minted: Uint128,
+    liquidation_bonus: Decimal,
+) -> StdResult<bool> {
+    let debt = dsc_to_decimal(total_dsc_minted)?;
+    Ok(!debt.is_zero()
+        && collateral_value <= debt.checked_mul(Decimal::one() + liquidation_bonus)?)
+}
+
+/// Max debt, in DSC, that one liquidation can cover on a position with `total_dsc_minted` of
+/// debt and an unhealthy `health_factor`
+pub fn max_liquidatable_debt(
+    config: &Config,
+    total_dsc_minted: Uint128,
+    health_factor: Decimal,
+    underwater: bool,
+) -> StdResult<Decimal> {
+    let debt = dsc_to_decimal(total_dsc_minted)?;
+    if underwater || health_factor < config.full_liquidation_health_factor {
+        return Ok(debt);
+    }
+    let max_debt_to_cover = debt.checked_mul(config.close_factor)?;
+    // leftover dust would cost liquidators more than it yields
+    if debt - max_debt_to_cover < dsc_to_decimal(config.liquidation_dust)? {
+        return Ok(debt);
+    }
+    Ok(max_debt_to_cover)
+}
+
+/// Largest liquidation of one collateral of a user, at fresh prices
+pub struct LiquidationQuote {
+    pub health_factor: Decimal,
+    /// See is_underwater
+    pub underwater: bool,
+    /// Usd price of one whole token of the collateral, at which debt is converted into it
+    pub price: Decimal,
+    /// Debt, in DSC, limited by the close factor and by the collateral deposited
+    pub max_debt_to_cover: Decimal,
+    /// Collateral, in atomic units, seized for covering max_debt_to_cover at the liquidation bonus
+    pub collateral_to_seize: Uint128,
+    /// Prices of the collateral and of every asset deposited by the user
+    pub prices: AssetPrices,
+}
+
+pub fn get_liquidation_quote(
+    deps: &Deps,
+    env: &Env,
+    config: &Config,
+    user_addr: &Addr,
+    collateral_asset: &str,
+) -> StdResult<LiquidationQuote> {
+    if !config.is_collateral_asset(collateral_asset) {
+        return Err(StdError::not_found(format!(
+            "collateral asset {collateral_asset}"
+        )));
+    }
+    let total_dsc_minted = get_dsc_minted(deps, env, user_addr.as_str())?;
+    let balances = get_collateral_balances(deps, config, user_addr)?;
+    let mut assets: Vec<String> = balances.iter().map(|(denom, _)| denom.clone()).collect();
+    if !assets.iter().any(|denom| denom == collateral_asset) {
+        assets.push(collateral_asset.to_string());
+    }
+    let prices = get_asset_prices(deps, config, assets, PriceFreshness::Fresh)?;
+    let account_value = value_collateral(deps, config, &balances, &prices)?;
+    let health_factor = health_factor(total_dsc_minted, account_value.liquidation_value)?;
+
+    let params = load_collateral_params(deps.storage, config, collateral_asset)?;
+    let price = price_of(&prices, collateral_asset)?.debt_price(&params.pricing_policy);
+    let liquidation_bonus = params.liquidation_bonus_ratio();
+    let underwater = is_underwater(account_value.total, total_dsc_minted, liquidation_bonus)?;
+    let decimals = config.asset_decimals(collateral_asset);
+    let balance = balances
+        .iter()
+        .find(|(denom, _)| denom == collateral_asset)
+        .map(|(_, balance)| *balance)
+        .unwrap_or_default();
+    // the collateral seized cannot exceed the one deposited
+    let debt_backed_by_balance =
+        usd_value(balance, decimals, price)? / (Decimal::one() + liquidation_bonus);
+    let max_debt_to_cover =
+        max_liquidatable_debt(config, total_dsc_minted, health_factor, underwater)?
+            .min(debt_backed_by_balance);
+    let max_debt_to_cover = dsc_to_decimal(to_atomics(max_debt_to_cover, DSC_DECIMALS))?;
+    let token_amount_from_debt_covered = max_debt_to_cover / price;
+    let collateral_to_seize =
+        token_amount_from_debt_covered + token_amount_from_debt_covered * liquidation_bonus;
+    Ok(LiquidationQuote {
+        health_factor,
+        underwater,
+        price,
+        max_debt_to_cover,
+        collateral_to_seize: to_atomics(collateral_to_seize, decimals).min(balance),
+        prices,
+    })
+}
+
+/// Collateral value adjusted for liquidation thresholds divided by the DSC minted
+pub fn health_factor(
+    total_dsc_minted: Uint128,
+    collateral_adjusted_for_threshold: Decimal,
 ) -> StdResult<Decimal> {
     if total_dsc_minted == Uint128::new(0) {
         Ok(Decimal::new(Uint128::MAX))
     } else {
-        let config = CONFIG.load(deps.storage)?;
-        let liquidation_threshold = Decimal::percent(config.liquidation_threshold.u128() as u64);
-        let collateral_adjusted_for_threshold =
-            collateral_value_in_usd.checked_mul(liquidation_threshold)?;
-        Ok(collateral_adjusted_for_threshold / Decimal::from_atomics(total_dsc_minted, 6).unwrap())
+        Ok(collateral_adjusted_for_threshold / dsc_to_decimal(total_dsc_minted)?)
     }
 }
 
-pub fn get_usd_value(deps: &Deps, asset_denom: String, amount: Uint128) -> StdResult<Decimal> {
-    let amount_decimals: u32 = 6;
+pub fn get_usd_value(
+    deps: &Deps,
+    asset_denom: String,
+    amount: Uint128,
+    freshness: PriceFreshness,
+) -> StdResult<Decimal> {
     let config = CONFIG.load(deps.storage)?;
-    let price_feed_id = config.assets_to_feeds.get(&asset_denom).unwrap();
-    let oracle_res = query_price_from_oracle(
-        &deps.querier,
-        config.oracle_address.to_string(),
-        config.pyth_oracle_address.to_string(),
-        PriceIdentifier::from_hex(price_feed_id).unwrap(),
-    )?;
-    let asset_price_usd = Decimal::from_atomics(
-        Uint128::from(oracle_res.current_price.price as u64),
-        oracle_res.current_price.expo.abs() as u32,
-    )
-    .unwrap();
-    let amount = Decimal::from_atomics(amount, amount_decimals).unwrap();
-    Ok(amount.checked_mul(asset_price_usd)?)
+    let params = load_collateral_params(deps.storage, &config, &asset_denom)?;
+    let asset_price_usd = get_asset_price(deps, &config, &asset_denom, freshness)?
+        .collateral_price(&params.pricing_policy);
+    usd_value(amount, config.asset_decimals(&asset_denom), asset_price_usd)
+}
+
+fn usd_value(
+    amount: Uint128,
+    amount_decimals: u32,
+    asset_price_usd: Decimal,
+) -> StdResult<Decimal> {
+    let amount = Decimal::from_atomics(amount, amount_decimals)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    amount.checked_mul(asset_price_usd).map_err(Into::into)
 }
 
 pub fn get_token_amount_from_usd(
@@ -138,28 +520,25 @@ pub fn get_token_amount_from_usd(
     usd_amount: Decimal,
 ) -> StdResult<Decimal> {
     let config = CONFIG.load(deps.storage)?;
-    let price_feed_id = config.assets_to_feeds.get(&asset_denom).unwrap();
-    let oracle_res = query_price_from_oracle(
-        &deps.querier,
-        config.oracle_address.to_string(),
-        config.pyth_oracle_address.to_string(),
-        PriceIdentifier::from_hex(price_feed_id).unwrap(),
-    )?;
-    let asset_price_usd = Decimal::from_atomics(
-        Uint128::from(oracle_res.current_price.price as u64),
-        oracle_res.current_price.expo.abs() as u32,
-    )
-    .unwrap();
+    let params = load_collateral_params(deps.storage, &config, &asset_denom)?;
+    let asset_price_usd = get_asset_price(deps, &config, &asset_denom, PriceFreshness::Fresh)?
+        .debt_price(&params.pricing_policy);
     Ok(usd_amount / asset_price_usd)
 }
 
+/// Price feed id registered for a collateral asset in the oracle
 pub fn get_collateral_token_price_feed(deps: &Deps, asset_denom: String) -> StdResult<String> {
     let config = CONFIG.load(deps.storage)?;
-    return Ok(config
-        .assets_to_feeds
-        .get(&asset_denom)
-        .unwrap()
-        .to_string());
+    if !config.is_collateral_asset(&asset_denom) {
+        return Err(StdError::not_found(format!(
+            "collateral asset {asset_denom}"
+        )));
+    }
+    let price_feed_id: PriceIdentifier = deps.querier.query_wasm_smart(
+        config.oracle_address.to_string(),
+        &OracleQueryMsg::Feed { asset: asset_denom },
+    )?;
+    Ok(price_feed_id.to_hex())
 }
 
 pub fn get_collateral_balance_of_user(
@@ -175,69 +554,265 @@ pub fn get_collateral_balance_of_user(
     }
 }
 
-fn get_account_collateral_value(deps: &Deps, user_addr: String) -> StdResult<Decimal> {
-    let config = CONFIG.load(deps.storage)?;
+/// Usd value of the collateral deposited by a user
+pub struct AccountCollateralValue {
+    /// Plain usd value of every collateral
+    pub total: Decimal,
+    /// Usd value weighted by each collateral liquidation threshold, backs the health factor
+    pub liquidation_value: Decimal,
+    /// Usd value weighted by each enabled collateral max ltv, caps the DSC that can be minted
+    pub borrowing_power: Decimal,
+    /// Borrowing power of each enabled collateral, splits the debt between them
+    pub borrowing_power_by_asset: Vec<(String, Decimal)>,
+}
 
-    let collateral_list = config.assets;
+/// Spot and EMA usd prices of one whole token of an asset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AssetPrice {
+    pub spot: Decimal,
+    pub ema: Decimal,
+}
 
-    let mut user_deposited_balance_usd = Decimal::new(Uint128::zero());
+impl AssetPrice {
+    /// Price valuing deposited collateral
+    pub fn collateral_price(&self, policy: &PricingPolicy) -> Decimal {
+        match policy {
+            PricingPolicy::Spot => self.spot,
+            PricingPolicy::Ema => self.ema,
+            PricingPolicy::Conservative => self.spot.min(self.ema),
+        }
+    }
 
-    for collateral_asset in collateral_list {
-        let user_collateral_balance = COLLATERAL_DEPOSITED.may_load(
-            deps.storage,
-            (
-                &deps.api.addr_validate(&user_addr)?,
-                collateral_asset.inner(),
-            ),
-        )?;
-        let user_collateral_balance_usd: Decimal = match user_collateral_balance {
-            Some(balance) => get_usd_value(&deps, collateral_asset.inner(), balance)?,
-            None => Decimal::new(Uint128::zero()),
-        };
-        user_deposited_balance_usd += user_collateral_balance_usd;
+    /// Price converting a usd debt into collateral tokens
+    pub fn debt_price(&self, policy: &PricingPolicy) -> Decimal {
+        match policy {
+            PricingPolicy::Spot => self.spot,
+            PricingPolicy::Ema => self.ema,
+            PricingPolicy::Conservative => self.spot.max(self.ema),
+        }
+    }
+}
+
+impl From<UsdPriceResponse> for AssetPrice {
+    fn from(price: UsdPriceResponse) -> Self {
+        AssetPrice {
+            spot: price.price,
+            ema: price.ema_price,
+        }
+    }
+}
+
+/// Usd prices of collateral assets, keyed by denom or address
+pub type AssetPrices = HashMap<String, AssetPrice>;
+
+/// Whether prices must be current or may be the last good ones cached by the oracle. Actions that
+/// can only reduce the risk of a position may go on with cached prices while the oracle is stale
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceFreshness {
+    Fresh,
+    CachedAllowed,
+}
+
+/// Collateral balances of a user, zero balances skipped
+pub fn get_collateral_balances(
+    deps: &Deps,
+    config: &Config,
+    user_addr: &Addr,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let mut balances = vec![];
+    for collateral_asset in &config.assets {
+        let balance = COLLATERAL_DEPOSITED
+            .may_load(deps.storage, (user_addr, collateral_asset.inner()))?
+            .unwrap_or_default();
+        if !balance.is_zero() {
+            balances.push((collateral_asset.inner(), balance));
+        }
+    }
+    Ok(balances)
+}
+
+/// Usd prices of several assets, fetched from the oracle in a single query
+pub fn get_asset_prices(
+    deps: &Deps,
+    config: &Config,
+    assets: Vec<String>,
+    freshness: PriceFreshness,
+) -> StdResult<AssetPrices> {
+    if assets.is_empty() {
+        return Ok(AssetPrices::new());
     }
+    let msg = match freshness {
+        PriceFreshness::Fresh => OracleQueryMsg::Prices { assets },
+        PriceFreshness::CachedAllowed => OracleQueryMsg::LastGoodPrices { assets },
+    };
+    let prices: Vec<AssetPriceResponse> = deps
+        .querier
+        .query_wasm_smart(config.oracle_address.to_string(), &msg)?;
+    Ok(prices
+        .into_iter()
+        .map(|asset_price| (asset_price.asset, asset_price.price.into()))
+        .collect())
+}
+
+pub fn price_of(prices: &AssetPrices, asset_denom: &str) -> StdResult<AssetPrice> {
+    prices
+        .get(asset_denom)
+        .copied()
+        .ok_or_else(|| StdError::not_found(format!("price of {asset_denom}")))
+}
 
-    Ok(user_deposited_balance_usd)
+pub fn get_account_collateral_value(
+    deps: &Deps,
+    user_addr: String,
+    freshness: PriceFreshness,
+) -> StdResult<AccountCollateralValue> {
+    let config = CONFIG.load(deps.storage)?;
+    let user_addr = deps.api.addr_validate(&user_addr)?;
+    let balances = get_collateral_balances(deps, &config, &user_addr)?;
+    let assets = balances.iter().map(|(denom, _)| denom.clone()).collect();
+    let prices = get_asset_prices(deps, &config, assets, freshness)?;
+    value_collateral(deps, &config, &balances, &prices)
 }
 
-fn get_account_information(deps: &Deps, user_addr: String) -> StdResult<AccountInfoResponse> {
-    let total_dsc_minted =
-        DSC_MINTED.may_load(deps.storage, &deps.api.addr_validate(&user_addr)?)?;
-    let total_dsc_minted_parsed = match total_dsc_minted {
-        Some(amount) => amount,
-        None => Uint128::new(0),
+/// Values collateral balances at already fetched prices
+pub fn value_collateral(
+    deps: &Deps,
+    config: &Config,
+    balances: &[(String, Uint128)],
+    prices: &AssetPrices,
+) -> StdResult<AccountCollateralValue> {
+    let mut account_value = AccountCollateralValue {
+        total: Decimal::zero(),
+        liquidation_value: Decimal::zero(),
+        borrowing_power: Decimal::zero(),
+        borrowing_power_by_asset: vec![],
     };
+
+    for (denom, user_collateral_balance) in balances {
+        let params = load_collateral_params(deps.storage, config, denom)?;
+        let user_collateral_balance_usd = usd_value(
+            *user_collateral_balance,
+            config.asset_decimals(denom),
+            price_of(prices, denom)?.collateral_price(&params.pricing_policy),
+        )?;
+
+        account_value.total += user_collateral_balance_usd;
+        account_value.liquidation_value +=
+            user_collateral_balance_usd.checked_mul(params.liquidation_threshold_ratio())?;
+        if params.enabled {
+            let borrowing_power =
+                user_collateral_balance_usd.checked_mul(params.max_ltv_ratio())?;
+            account_value.borrowing_power += borrowing_power;
+            account_value
+                .borrowing_power_by_asset
+                .push((denom.clone(), borrowing_power));
+        }
+    }
+
+    Ok(account_value)
+}
+
+pub fn get_account_information(
+    deps: &Deps,
+    env: &Env,
+    user_addr: String,
+) -> StdResult<AccountInfoResponse> {
     let acc_info = AccountInfoResponse {
-        deposited_collateral_in_usd: get_account_collateral_value(&deps, user_addr.clone())?,
-        total_dsc_minted: total_dsc_minted_parsed,
+        deposited_collateral_in_usd: get_account_collateral_value(
+            deps,
+            user_addr.clone(),
+            PriceFreshness::Fresh,
+        )?
+        .total,
+        total_dsc_minted: get_dsc_minted(deps, env, &user_addr)?,
     };
     Ok(acc_info)
 }
 
-fn get_health_factor(deps: &Deps, user: String) -> StdResult<Decimal> {
-    let AccountInfoResponse {
-        deposited_collateral_in_usd,
-        total_dsc_minted,
-    } = get_account_information(deps, user)?;
-    Ok(calculate_health_factor(
-        deps,
-        total_dsc_minted,
-        deposited_collateral_in_usd,
-    )?)
+pub fn get_health_factor(
+    deps: &Deps,
+    env: &Env,
+    user: String,
+    freshness: PriceFreshness,
+) -> StdResult<Decimal> {
+    let total_dsc_minted = get_dsc_minted(deps, env, &user)?;
+    if total_dsc_minted.is_zero() {
+        // no need to price the collateral
+        return health_factor(total_dsc_minted, Decimal::zero());
+    }
+    let account_value = get_account_collateral_value(deps, user, freshness)?;
+    health_factor(total_dsc_minted, account_value.liquidation_value)
+}
+
+/// Health factor of a user at already fetched prices, which must cover every collateral it holds
+pub fn get_health_factor_with_prices(
+    deps: &Deps,
+    env: &Env,
+    user_addr: &Addr,
+    prices: &AssetPrices,
+) -> StdResult<Decimal> {
+    let total_dsc_minted = get_dsc_minted(deps, env, user_addr.as_str())?;
+    if total_dsc_minted.is_zero() {
+        return health_factor(total_dsc_minted, Decimal::zero());
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let balances = get_collateral_balances(deps, &config, user_addr)?;
+    let account_value = value_collateral(deps, &config, &balances, prices)?;
+    health_factor(total_dsc_minted, account_value.liquidation_value)
+}
+
+/// DSC debt of a user, stability fees accrued up to the current block included
+pub fn get_dsc_minted(deps: &Deps, env: &Env, user_addr: &str) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let normalized_debt = DSC_MINTED
+        .may_load(deps.storage, &deps.api.addr_validate(user_addr)?)?
+        .unwrap_or_default();
+    load_debt_rate(deps.storage, &config, env.block.time.seconds())?.debt(normalized_debt)
+}
+
+pub fn dsc_to_decimal(amount: Uint128) -> StdResult<Decimal> {
+    Decimal::from_atomics(amount, DSC_DECIMALS)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+/// Pyth contract the oracle reads prices from, price updates are sent to it
+pub fn get_pyth_contract(deps: &Deps, config: &Config) -> StdResult<Addr> {
+    let oracle_config: OracleConfigResponse = deps.querier.query_wasm_smart(
+        config.oracle_address.to_string(),
+        &OracleQueryMsg::Config {},
+    )?;
+    Ok(oracle_config.pyth_contract_addr)
+}
+
+/// Spot and EMA usd prices of one whole token of a collateral asset
+pub fn get_asset_price(
+    deps: &Deps,
+    config: &Config,
+    asset_denom: &str,
+    freshness: PriceFreshness,
+) -> StdResult<AssetPrice> {
+    let oracle_res = query_price_from_oracle(
+        &deps.querier,
+        config.oracle_address.to_string(),
+        asset_denom.to_string(),
+        freshness,
+    )?;
+    Ok(oracle_res.into())
 }
 
 fn query_price_from_oracle(
     querier: &QuerierWrapper,
     oracle_address: String,
-    pyth_oracle_address: String,
-    price_feed_id: PriceIdentifier,
-) -> StdResult<FetchPriceResponse> {
+    asset_denom: String,
+    freshness: PriceFreshness,
+) -> StdResult<UsdPriceResponse> {
+    let msg = match freshness {
+        PriceFreshness::Fresh => OracleQueryMsg::UsdPrice { asset: asset_denom },
+        PriceFreshness::CachedAllowed => OracleQueryMsg::LastGoodPrice { asset: asset_denom },
+    };
     let asset_price_usd = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: oracle_address,
-        msg: to_json_binary(&OracleQueryMsg::FetchPrice {
-            pyth_contract_addr: pyth_oracle_address,
-            price_feed_id: price_feed_id,
-        })?,
+        msg: to_json_binary(&msg)?,
     }))?;
     Ok(asset_price_usd)
 }
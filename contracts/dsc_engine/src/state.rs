@@ -1,42 +1,605 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use cosmwasm_std::{Addr, Decimal, Empty, Order, StdError, StdResult, Storage, Uint128};
 use cw_asset::AssetInfo;
 use cw_storage_plus::{Item, Map, SnapshotMap};
+use cw_utils::Expiration;
 use std::collections::HashMap;
 
 /// This structure holds the main contract parameters.
 #[cw_serde]
 pub struct Config {
-    /// Address allowed to change contract parameters
-    pub owner: Addr,
+    /// Address allowed to change contract parameters, None once ownership is renounced
+    pub owner: Option<Addr>,
+    /// Address allowed to pause actions, but not to unpause them
+    #[serde(default)]
+    pub guardian: Option<Addr>,
     /// list of depositable asset infos
     pub assets: Vec<AssetInfo>,
-    /// key is asset deposited denom or address, value is price_feed_id (https://pyth.network/developers/price-feed-ids#cosmwasm-stable)
+    /// Deprecated, price feeds are registered in the oracle. Only kept for loading configs saved
+    /// before, it is no longer read nor updated.
+    #[serde(default)]
     pub assets_to_feeds: HashMap<String, String>,
+    /// key is asset deposited denom or address, value is the number of decimals of the asset
+    #[serde(default)]
+    pub assets_decimals: HashMap<String, u8>,
     /// address of protocol wrapper for pyth oracles
     pub oracle_address: Addr,
-    /// pyth oracle contract address
-    /// https://docs.pyth.network/documentation/pythnet-price-feeds/cosmwasm
-    pub pyth_oracle_address: Addr,
+    /// Deprecated, the Pyth contract is the one of the oracle. Only kept for loading configs
+    /// saved before, it is no longer read nor updated.
+    #[serde(default)]
+    pub pyth_oracle_address: Option<Addr>,
     /// address of stable asset to be minted
     pub dsc_address: Addr,
+    /// default liquidation threshold of new collateral assets
     /// liquidation threshold = 50 means you need to be 200% over-collateralized
     pub liquidation_threshold: Uint128,
+    /// default liquidation bonus of new collateral assets
     /// liquidation_bonus = 10 means you get assets at a 10% discount when liquidating
     pub liquidation_bonus: Uint128,
     /// health factor that leads to liquidation
     pub min_health_factor: Decimal,
+    /// annualized fee accrued on minted DSC, 0.02 means debts grow by 2% a year
+    #[serde(default)]
+    pub stability_fee: Decimal,
+    /// max share of the debt of a user that can be covered in one liquidation, 0.5 means half
+    /// of it. Configs saved before it existed allowed covering all of it.
+    #[serde(default = "Decimal::one")]
+    pub close_factor: Decimal,
+    /// below this health factor the whole debt of a user can be covered in one liquidation
+    #[serde(default)]
+    pub full_liquidation_health_factor: Decimal,
+    /// debt, in DSC atomic units, below which a position is not worth liquidating again: the
+    /// whole debt can be covered when the close factor would leave less than it
+    #[serde(default)]
+    pub liquidation_dust: Uint128,
+    /// share of the liquidation bonus kept as surplus, 0.1 means a tenth of the bonus collateral
+    #[serde(default)]
+    pub liquidation_fee_share: Decimal,
+    /// share of the stability fees accrued that goes to the surplus
+    #[serde(default)]
+    pub stability_fee_share: Decimal,
+    /// fee on minted DSC, added to the debt of the minter and kept as surplus, 0.005 means 0.5%
+    #[serde(default)]
+    pub origination_fee: Decimal,
+    /// min fee on the collateral drawn by DSC redemptions, 0.005 means 0.5%. The redemption base
+    /// rate is added to it
+    #[serde(default)]
+    pub redemption_fee_floor: Decimal,
 }
 
+impl Config {
+    /// Whether an asset denom or address is a registered collateral
+    pub fn is_collateral_asset(&self, asset_denom: &str) -> bool {
+        self.assets.iter().any(|asset| asset.inner() == asset_denom)
+    }
+
+    /// Decimals of a collateral asset. Assets registered before decimals were tracked were
+    /// assumed to have 6 decimals.
+    pub fn asset_decimals(&self, asset_denom: &str) -> u32 {
+        self.assets_decimals
+            .get(asset_denom)
+            .copied()
+            .unwrap_or(LEGACY_ASSET_DECIMALS) as u32
+    }
+
+    /// Risk parameters given to a collateral asset registered without its own, built from the
+    /// global liquidation threshold and bonus
+    pub fn default_collateral_params(&self) -> CollateralParams {
+        CollateralParams {
+            liquidation_threshold: self.liquidation_threshold,
+            max_ltv: self.liquidation_threshold,
+            liquidation_bonus: self.liquidation_bonus,
+            debt_ceiling: None,
+            enabled: true,
+            pricing_policy: PricingPolicy::default(),
+            liquidation_mode: LiquidationMode::default(),
+        }
+    }
+}
+
+/// Decimals assumed for every collateral asset by dsc-engine 1.0.0
+pub const LEGACY_ASSET_DECIMALS: u8 = 6;
+
 /// Saves dsc-engine settings
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Risk parameters of a single collateral asset
+#[cw_serde]
+pub struct CollateralParams {
+    /// share of the collateral value counted in the health factor
+    /// liquidation threshold = 50 means you need to be 200% over-collateralized
+    pub liquidation_threshold: Uint128,
+    /// share of the collateral value that can be minted as DSC, at most the liquidation threshold
+    /// max_ltv = 40 means you can mint 40 usd of DSC for every 100 usd deposited
+    pub max_ltv: Uint128,
+    /// liquidation_bonus = 10 means you get this asset at a 10% discount when liquidating
+    pub liquidation_bonus: Uint128,
+    /// max DSC debt (in DSC atomic units) backed by this asset across all positions. The debt of
+    /// a position is split between its enabled collaterals pro rata to their borrowing power when
+    /// it mints. None means no ceiling.
+    pub debt_ceiling: Option<Uint128>,
+    /// disabled assets can no longer be deposited nor back new DSC, but still count for the
+    /// health factor of existing positions
+    pub enabled: bool,
+    /// price the asset is valued at, spot for assets registered before policies existed
+    #[serde(default)]
+    pub pricing_policy: PricingPolicy,
+    /// how unhealthy positions holding the asset are liquidated, fixed bonus for assets
+    /// registered before auctions existed
+    #[serde(default)]
+    pub liquidation_mode: LiquidationMode,
+}
+
+/// Which oracle price a collateral asset is valued at
+#[cw_serde]
+#[derive(Default)]
+pub enum PricingPolicy {
+    /// latest price, follows the market closely
+    #[default]
+    Spot,
+    /// exponentially-weighted moving average price, ignores short wicks
+    Ema,
+    /// lower of spot and EMA when valuing collateral, higher of the two when converting debt
+    /// into collateral in liquidations
+    Conservative,
+}
+
+/// How the collateral of unhealthy positions is sold
+#[cw_serde]
+#[derive(Default)]
+pub enum LiquidationMode {
+    /// liquidators cover debt right away and get the collateral at the liquidation bonus discount
+    #[default]
+    FixedBonus,
+    /// positions are kicked into a Dutch auction whose collateral price decays over time
+    Auction(AuctionParams),
+}
+
+/// Price curve of collateral auctions
+#[cw_serde]
+pub struct AuctionParams {
+    /// starting price as a multiple of the oracle price, 1.2 starts 20% above it
+    pub starting_premium: Decimal,
+    /// seconds for the price to decay linearly from the starting price to zero
+    pub duration: u64,
+    /// share of the starting price at or below which the auction must be reset, 0.4 means once
+    /// the price lost 60%
+    pub reset_price_ratio: Decimal,
+}
+
+impl CollateralParams {
+    pub fn liquidation_threshold_ratio(&self) -> Decimal {
+        Decimal::percent(self.liquidation_threshold.u128() as u64)
+    }
+
+    pub fn max_ltv_ratio(&self) -> Decimal {
+        Decimal::percent(self.max_ltv.u128() as u64)
+    }
+
+    pub fn liquidation_bonus_ratio(&self) -> Decimal {
+        Decimal::percent(self.liquidation_bonus.u128() as u64)
+    }
+}
+
+/// Key is collateral token denom/ address
+pub const COLLATERAL_PARAMS: Map<String, CollateralParams> = Map::new("collateral_params");
+
+/// Risk parameters of a collateral asset, falling back to the global ones for assets registered
+/// before per asset parameters existed
+pub fn load_collateral_params(
+    storage: &dyn Storage,
+    config: &Config,
+    asset_denom: &str,
+) -> StdResult<CollateralParams> {
+    Ok(COLLATERAL_PARAMS
+        .may_load(storage, asset_denom.to_string())?
+        .unwrap_or_else(|| config.default_collateral_params()))
+}
+
+/// Pending transfer of the contract ownership
+#[cw_serde]
+pub struct OwnershipProposal {
+    /// Address that can accept the ownership
+    pub owner: Addr,
+    /// Proposal can no longer be accepted after it expires
+    pub expiry: Option<Expiration>,
+}
+
+/// Saves the pending ownership transfer, if any
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+
 /// First key is user address, second key is collateral token denom/ address
 pub const COLLATERAL_DEPOSITED: Map<(&Addr, String), Uint128> = Map::new("collateral_deposited");
 
+/// Key is collateral token denom/ address, value is the sum deposited by all users
+pub const TOTAL_COLLATERAL_DEPOSITED: Map<String, Uint128> = Map::new("total_collateral_deposited");
+
+/// Key is user address, value is its normalized debt: DSC debt including stability fees is
+/// normalized debt * `DebtRate.rate`
 pub const DSC_MINTED: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
     "balances",
     "balances_check",
     "balances_change",
     cw_storage_plus::Strategy::EveryBlock,
 );
+
+/// Sum of the normalized debt of every user
+pub const TOTAL_NORMALIZED_DEBT: Item<Uint128> = Item::new("total_normalized_debt");
+
+/// First key is user address, second key is collateral token denom/ address, value is the part
+/// of the user normalized debt backed by this collateral
+pub const COLLATERAL_DEBT: Map<(&Addr, String), Uint128> = Map::new("collateral_debt");
+
+/// Key is collateral token denom/ address, value is the normalized debt it backs for all users
+pub const TOTAL_COLLATERAL_DEBT: Map<String, Uint128> = Map::new("total_collateral_debt");
+
+/// Sets the normalized debt of `user` backed by `denom`, keeping the total of the asset in sync
+pub fn set_collateral_debt(
+    storage: &mut dyn Storage,
+    user: &Addr,
+    denom: String,
+    normalized_debt: Uint128,
+) -> StdResult<()> {
+    let previous = COLLATERAL_DEBT
+        .may_load(storage, (user, denom.clone()))?
+        .unwrap_or_default();
+    let total = TOTAL_COLLATERAL_DEBT
+        .may_load(storage, denom.clone())?
+        .unwrap_or_default();
+    TOTAL_COLLATERAL_DEBT.save(
+        storage,
+        denom.clone(),
+        &(total.saturating_sub(previous) + normalized_debt),
+    )?;
+    if normalized_debt.is_zero() {
+        COLLATERAL_DEBT.remove(storage, (user, denom));
+        Ok(())
+    } else {
+        COLLATERAL_DEBT.save(storage, (user, denom), &normalized_debt)
+    }
+}
+
+/// Removes `reduction` from the normalized debt backed by the collaterals of `user`: first from
+/// `backing`, the collateral it was repaid with if any, then pro rata from the others
+pub fn reduce_collateral_debt(
+    storage: &mut dyn Storage,
+    user: &Addr,
+    mut reduction: Uint128,
+    backing: Option<&str>,
+) -> StdResult<()> {
+    if let Some(backing) = backing {
+        let backed = COLLATERAL_DEBT
+            .may_load(storage, (user, backing.to_string()))?
+            .unwrap_or_default();
+        let reduced = backed.min(reduction);
+        set_collateral_debt(storage, user, backing.to_string(), backed - reduced)?;
+        reduction -= reduced;
+    }
+    if reduction.is_zero() {
+        return Ok(());
+    }
+    let debts = COLLATERAL_DEBT
+        .prefix(user)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let total = debts.iter().map(|(_, debt)| *debt).sum::<Uint128>();
+    let remaining = total.saturating_sub(reduction);
+    for (denom, debt) in debts {
+        set_collateral_debt(storage, user, denom, debt.multiply_ratio(remaining, total))?;
+    }
+    Ok(())
+}
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Cumulative stability fee index
+#[cw_serde]
+pub struct DebtRate {
+    /// Starts at 1 and grows with the stability fee
+    pub rate: Decimal,
+    /// Block time, in seconds, up to which fees have been accrued
+    pub last_accrual: u64,
+}
+
+impl DebtRate {
+    /// Rate once the annualized `stability_fee` has been accrued up to `now`. Fees are simple
+    /// interest between two accruals, and compound at every accrual.
+    pub fn accrue(&self, stability_fee: Decimal, now: u64) -> StdResult<DebtRate> {
+        if now <= self.last_accrual {
+            return Ok(self.clone());
+        }
+        let elapsed = Decimal::from_ratio(now - self.last_accrual, SECONDS_PER_YEAR);
+        let rate = self
+            .rate
+            .checked_mul(Decimal::one() + stability_fee.checked_mul(elapsed)?)?;
+        Ok(DebtRate {
+            rate,
+            last_accrual: now,
+        })
+    }
+
+    /// DSC owed for a normalized debt, rounded up
+    pub fn debt(&self, normalized_debt: Uint128) -> StdResult<Uint128> {
+        normalized_debt
+            .checked_mul_ceil(self.rate)
+            .map_err(|err| StdError::generic_err(err.to_string()))
+    }
+
+    /// Normalized debt owed for a DSC amount, rounded up
+    pub fn normalize(&self, debt: Uint128) -> StdResult<Uint128> {
+        debt.checked_div_ceil(self.rate)
+            .map_err(|err| StdError::generic_err(err.to_string()))
+    }
+}
+
+/// Saves the stability fee index
+pub const DEBT_RATE: Item<DebtRate> = Item::new("debt_rate");
+
+/// Stability fee index accrued up to `now`, without saving it
+pub fn load_debt_rate(storage: &dyn Storage, config: &Config, now: u64) -> StdResult<DebtRate> {
+    DEBT_RATE
+        .may_load(storage)?
+        .unwrap_or(DebtRate {
+            rate: Decimal::one(),
+            last_accrual: now,
+        })
+        .accrue(config.stability_fee, now)
+}
+
+/// Actions stopped by the owner or the guardian
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseFlags {
+    /// minting DSC
+    pub mint: bool,
+    /// depositing collateral
+    pub deposit: bool,
+    /// redeeming collateral
+    pub redeem: bool,
+    /// liquidating unhealthy positions
+    pub liquidate: bool,
+}
+
+/// Saves the paused actions, nothing is paused if missing
+pub const PAUSE_FLAGS: Item<PauseFlags> = Item::new("pause_flags");
+
+/// Global settlement state, set once the protocol is shut down
+#[cw_serde]
+pub struct GlobalSettlement {
+    /// Block time, in seconds, of the shutdown
+    pub shutdown_time: u64,
+    /// Key is collateral token denom/ address, value is its usd price frozen at shutdown
+    pub prices: HashMap<String, Decimal>,
+}
+
+/// Saves the global settlement, the protocol is live while missing
+pub const GLOBAL_SETTLEMENT: Item<GlobalSettlement> = Item::new("global_settlement");
+
+/// Key is collateral token denom/ address, value is the amount seized from settled positions
+/// and redeemable by DSC holders
+pub const SETTLEMENT_COLLATERAL: Map<String, Uint128> = Map::new("settlement_collateral");
+
+/// Collateral of an unhealthy position sold to cover part of its debt
+#[cw_serde]
+pub struct Auction {
+    /// user whose collateral is sold, gets back what is left once the debt is raised
+    pub user: Addr,
+    pub collateral_asset: AssetInfo,
+    /// DSC, in atomic units, still to be raised
+    pub debt: Uint128,
+    /// collateral, in atomic units, still for sale
+    pub collateral: Uint128,
+    /// usd price of one whole token of collateral when the auction started or was last reset
+    pub starting_price: Decimal,
+    /// block time, in seconds, the price started decaying at
+    pub start_time: u64,
+    /// price curve, fixed when the auction is kicked
+    pub params: AuctionParams,
+}
+
+impl Auction {
+    /// Usd price of one whole token of collateral at block time `now`
+    pub fn price(&self, now: u64) -> Decimal {
+        let elapsed = now.saturating_sub(self.start_time);
+        if elapsed >= self.params.duration {
+            return Decimal::zero();
+        }
+        Decimal::new(
+            self.starting_price
+                .atomics()
+                .multiply_ratio(self.params.duration - elapsed, self.params.duration),
+        )
+    }
+
+    /// The price decayed too far and must restart from the oracle price
+    pub fn needs_reset(&self, now: u64) -> bool {
+        self.price(now) <= self.starting_price * self.params.reset_price_ratio
+    }
+}
+
+/// Live auctions, keyed by id
+pub const AUCTIONS: Map<u64, Auction> = Map::new("auctions");
+
+/// Id of the next auction kicked
+pub const NEXT_AUCTION_ID: Item<u64> = Item::new("next_auction_id");
+
+/// DSC, in atomic units, owed by positions whose collateral ran out and not yet settled
+pub const BAD_DEBT: Item<Uint128> = Item::new("bad_debt");
+
+/// Protocol surplus, key is asset denom or address. Collateral surplus is held by DSC Engine,
+/// DSC surplus is only minted once withdrawn. DSC surplus settles bad debt first.
+pub const SURPLUS: Map<String, Uint128> = Map::new("surplus");
+
+/// Adds `amount` of an asset to the protocol surplus
+pub fn add_surplus(storage: &mut dyn Storage, asset: String, amount: Uint128) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    SURPLUS.update(storage, asset, |surplus| -> StdResult<_> {
+        Ok(surplus.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Parameters of surplus and debt auctions, which trade DSC against a governance token
+#[cw_serde]
+pub struct BalanceSheetAuctionParams {
+    /// cw20 bought and burnt by surplus auctions, minted by debt auctions: DSC Engine must be
+    /// its minter
+    pub governance_token: Addr,
+    /// DSC surplus, in atomic units, kept to settle bad debt: only the surplus above it is sold
+    pub surplus_buffer: Uint128,
+    /// DSC, in atomic units, sold by one surplus auction
+    pub surplus_lot: Uint128,
+    /// DSC, in atomic units, raised by one debt auction
+    pub debt_lot: Uint128,
+    /// governance tokens, in atomic units, first offered by debt auctions
+    pub debt_initial_lot: Uint128,
+    /// min improvement of each bid, 0.05 means 5% more governance tokens offered for surplus,
+    /// or 5% less asked for debt
+    pub min_bid_increment: Decimal,
+    /// seconds after a bid for the auction to end, unless outbid
+    pub bid_duration: u64,
+    /// seconds after the kick for the auction to end, whatever the bids
+    pub auction_duration: u64,
+}
+
+/// Surplus and debt auctions are disabled while missing
+pub const BALANCE_SHEET_AUCTION_PARAMS: Item<BalanceSheetAuctionParams> =
+    Item::new("balance_sheet_auction_params");
+
+/// What a balance sheet auction trades
+#[cw_serde]
+pub enum BalanceSheetAuctionKind {
+    /// sells a fixed lot of DSC surplus for the most governance tokens
+    Surplus,
+    /// raises a fixed bid of DSC for the fewest governance tokens
+    Debt,
+}
+
+/// English auction of DSC against the governance token
+#[cw_serde]
+pub struct BalanceSheetAuction {
+    pub kind: BalanceSheetAuctionKind,
+    /// amount sold, DSC for surplus auctions and governance tokens for debt auctions
+    pub lot: Uint128,
+    /// amount paid by the winning bidder, governance tokens for surplus auctions and DSC for
+    /// debt auctions
+    pub bid: Uint128,
+    /// winning bidder, None until the first bid
+    pub bidder: Option<Addr>,
+    /// block time, in seconds, at which the last bid wins unless outbid
+    pub bid_expiry: Option<u64>,
+    /// block time, in seconds, at which the auction ends whatever the bids
+    pub end: u64,
+}
+
+impl BalanceSheetAuction {
+    /// No more bids are accepted and the auction can be settled
+    pub fn has_ended(&self, now: u64) -> bool {
+        now >= self.end || self.bid_expiry.is_some_and(|bid_expiry| now >= bid_expiry)
+    }
+}
+
+/// Live surplus and debt auctions, keyed by id
+pub const BALANCE_SHEET_AUCTIONS: Map<u64, BalanceSheetAuction> =
+    Map::new("balance_sheet_auctions");
+
+/// Id of the next surplus or debt auction kicked
+pub const NEXT_BALANCE_SHEET_AUCTION_ID: Item<u64> = Item::new("next_balance_sheet_auction_id");
+
+/// Bad debt, in DSC atomic units, that debt auctions are raising
+pub const DEBT_ON_AUCTION: Item<Uint128> = Item::new("debt_on_auction");
+
+/// Key is collateral token denom/ address, nominal collateral ratio and user address, for every
+/// position with debt and this collateral. The nominal collateral ratio is the collateral
+/// deposited per unit of the normalized debt it backs in `COLLATERAL_DEBT`: it orders positions
+/// by their health in this collateral without any price, and redemptions draw collateral from the
+/// lowest ratios first.
+pub const REDEMPTION_INDEX: Map<(String, u128, &Addr), Empty> = Map::new("redemption_index");
+
+/// First key is user address, second key is collateral token denom/ address, value is the
+/// nominal collateral ratio the position is indexed at in `REDEMPTION_INDEX`
+pub const REDEMPTION_INDEX_RATIOS: Map<(&Addr, String), u128> = Map::new("redemption_index_ratios");
+
+/// Collateral atomic units per normalized debt unit, as the atomics of a Decimal. Saturates for
+/// dust debts backed by huge deposits.
+pub fn nominal_collateral_ratio(collateral: Uint128, normalized_debt: Uint128) -> u128 {
+    Decimal::checked_from_ratio(collateral, normalized_debt)
+        .unwrap_or(Decimal::MAX)
+        .atomics()
+        .u128()
+}
+
+/// Indexes every collateral of `user` again, once its deposits or its debt changed. Collaterals
+/// backing none of the debt of the position are indexed last.
+pub fn update_redemption_index(storage: &mut dyn Storage, user: &Addr) -> StdResult<()> {
+    let has_debt = !DSC_MINTED
+        .may_load(storage, user)?
+        .unwrap_or_default()
+        .is_zero();
+    let deposits = COLLATERAL_DEPOSITED
+        .prefix(user)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (asset, collateral) in deposits {
+        if let Some(ratio) = REDEMPTION_INDEX_RATIOS.may_load(storage, (user, asset.clone()))? {
+            REDEMPTION_INDEX.remove(storage, (asset.clone(), ratio, user));
+            REDEMPTION_INDEX_RATIOS.remove(storage, (user, asset.clone()));
+        }
+        if !collateral.is_zero() && has_debt {
+            let backed_debt = COLLATERAL_DEBT
+                .may_load(storage, (user, asset.clone()))?
+                .unwrap_or_default();
+            let ratio = nominal_collateral_ratio(collateral, backed_debt);
+            REDEMPTION_INDEX.save(storage, (asset.clone(), ratio, user), &Empty {})?;
+            REDEMPTION_INDEX_RATIOS.save(storage, (user, asset), &ratio)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decay of the redemption base rate per minute, 0.5^(1/720): it halves every 12 hours
+pub const REDEMPTION_MINUTE_DECAY_FACTOR: Decimal = Decimal::raw(999_037_758_833_783_000);
+
+/// The base rate grows by the share of the DSC supply redeemed divided by it
+pub const REDEMPTION_BETA: u128 = 2;
+
+/// Redemption base rate, raised by every redemption and decaying over time
+#[cw_serde]
+pub struct RedemptionRate {
+    /// Added to the redemption fee floor
+    pub base_rate: Decimal,
+    /// Block time, in seconds, up to which the base rate has decayed
+    pub last_update: u64,
+}
+
+impl RedemptionRate {
+    /// Base rate once decayed up to `now`. It decays by whole minutes, so that frequent
+    /// redemptions cannot prevent it from decaying.
+    pub fn decay(&self, now: u64) -> StdResult<RedemptionRate> {
+        // a thousand years are enough to decay any base rate to zero
+        let minutes = (now.saturating_sub(self.last_update) / 60).min(525_600_000);
+        let base_rate = self
+            .base_rate
+            .checked_mul(REDEMPTION_MINUTE_DECAY_FACTOR.checked_pow(minutes as u32)?)?;
+        Ok(RedemptionRate {
+            base_rate,
+            last_update: self.last_update + minutes * 60,
+        })
+    }
+}
+
+/// Saves the redemption base rate, zero if missing
+pub const REDEMPTION_RATE: Item<RedemptionRate> = Item::new("redemption_rate");
+
+/// Redemption base rate decayed up to `now`, without saving it
+pub fn load_redemption_rate(storage: &dyn Storage, now: u64) -> StdResult<RedemptionRate> {
+    REDEMPTION_RATE
+        .may_load(storage)?
+        .unwrap_or(RedemptionRate {
+            base_rate: Decimal::zero(),
+            last_update: now,
+        })
+        .decay(now)
+}
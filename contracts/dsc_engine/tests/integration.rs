@@ -1,27 +1,46 @@
+use cosmwasm_schema::cw_serde;
 #[cfg(test)]
-use cosmwasm_std::{coins, Addr, Coin, Decimal, Empty, Uint128};
+use cosmwasm_std::{
+    coins, to_json_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    Uint128,
+};
+use cw2::{query_contract_info, set_contract_version};
 use cw20::Cw20ExecuteMsg;
 use cw20::{BalanceResponse, Cw20Coin, MinterResponse, TokenInfoResponse};
 use cw20_base::contract::{
     execute as cw20_execute, instantiate as cw20_instantiate, query as cw20_query,
 };
 use cw20_base::msg::{InstantiateMsg as Cw20InstantiateMsg, QueryMsg as Cw20QueryMsg};
-use cw_asset::AssetInfo;
+use cw_asset::{Asset, AssetInfo};
 use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use cw_utils::Expiration;
 use dsc::contract::{execute as dsc_execute, instantiate as dsc_instantiate, query as dsc_query};
-use dsc_engine::contract::{execute, instantiate};
-use dsc_engine::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use dsc_engine::contract::{execute, instantiate, migrate};
+use dsc_engine::msg::{
+    AccountInfoResponse, AuctionResponse, BalanceSheetAuctionResponse, CollateralParamsResponse,
+    ConfigResponse, DebtRateResponse, ExecuteMsg, InstantiateMsg, MaxLiquidatableResponse,
+    MigrateMsg, OwnershipResponse, QueryMsg, ReceiveMsg, StatusResponse, SurplusResponse,
+};
 use dsc_engine::queries::query;
+use dsc_engine::state::{
+    AuctionParams, BalanceSheetAuctionParams, CollateralParams, LiquidationMode, PauseFlags,
+    PricingPolicy, SECONDS_PER_YEAR,
+};
+use dsc_engine::ContractError;
 use mock_pyth::contract::{
     execute as mock_pyth_execute, instantiate as mock_pyth_instantiate, query as mock_pyth_query,
 };
-use mock_pyth::msg::ExecuteMsg as MockPythExecuteMsg;
+use mock_pyth::contract::{UPDATE_FEE_DENOM, UPDATE_FEE_PER_UPDATE};
+use mock_pyth::msg::{ExecuteMsg as MockPythExecuteMsg, InstantiateMsg as MockPythInstantiateMsg};
 use oracle::contract::{
     execute as oracle_execute, instantiate as oracle_instantiate, query as oracle_query,
 };
-use oracle::msg::InstantiateMsg as OracleInstantiateMsg;
+use oracle::msg::{ExecuteMsg as OracleExecuteMsg, InstantiateMsg as OracleInstantiateMsg};
 use oracle::msg::{FetchPriceResponse, QueryMsg as OracleQueryMsg};
-use pyth_sdk_cw::PriceIdentifier;
+use oracle::ContractError as OracleContractError;
+use pyth_sdk_cw::{Price, PriceFeed, PriceIdentifier};
+use std::cell::Cell;
 use std::collections::HashMap;
 
 const OWNER: &str = "neutron17ykn47jnxnn83ceh95grafvtjx7xzsstw0jq9d";
@@ -37,6 +56,7 @@ const ORACLE_ADDRESS: &str = "oracle_addr";
 const PYTH_ORACLE_ADDRESS: &str = "pyth_oracle_addr";
 const PRICE_FEED_ID_1: &str = "63f341689d98a12ef60a5cff1d7f85c70a9e17bf1575f0e7c0b2512d48b1c8b3";
 const PRICE_FEED_ID_2: &str = "2b9ab1e972a281585084148ba1389800799bd4be63b957507db1349314e47445";
+const MOCK_PRICE: i64 = 680_000; // 6.8 usd, price of PRICE_FEED_ID_1 when mock-pyth is instantiated
 const DSC_ADDR: &str = "dsc_addr";
 const AMOUNT_COLLATERAL_OK: Uint128 = Uint128::new(2_000_000); // 2_000_000/1_000_000 * 680_000/100_000 = 13.6 usd at mock oracle price
 const AMOUNT_DSC_TO_MINT_OK: Uint128 = Uint128::new(1_000_000); // health factor = 6.8
@@ -50,12 +70,101 @@ const FINAL_DSC_SUPPLY: Uint128 = Uint128::new(1_100_000); // 2_000_000 - 900_00
 const FINAL_BALANCE_OF_LIQUIDATOR: Uint128 = Uint128::new(1_020_618); // (900_000/1_000_000 * 100_000/97_000) * 1.1
 const FINAL_NATIVE_BALANCE_OF_LIQUIDATED: Uint128 = Uint128::new(9_000_000); // 15_000_000 - 4_000_000 - 2_000_000
 const FINAL_CW20_BALANCE_OF_LIQUIDATED: Uint128 = Uint128::new(13_000_000); // 15_000_000 - 2_000_000
+const NOT_OWNER: &str = "neutron1ppvs6lkh6wxx2ylfh75q3c4kfaxa2lcnnhcqnk";
+const NEW_COLLATERAL_DENOM: &str = "new_native";
+const AMOUNT_18_DECIMALS_COLLATERAL_OK: Uint128 = Uint128::new(2_000_000_000_000_000_000); // 2 tokens = 13.6 usd at mock oracle price
+const FINAL_18_DECIMALS_COLLATERAL_BALANCE_OF_LIQUIDATED: Uint128 =
+    Uint128::new(979_381_443_298_969_073); // 2 - (0.9 * 1/0.97) * 1.1
+const FINAL_18_DECIMALS_BALANCE_OF_LIQUIDATOR: Uint128 = Uint128::new(1_020_618_556_701_030_927); // (0.9 * 1/0.97) * 1.1
+const CW20_COLLATERAL_PARAMS: CollateralParams = CollateralParams {
+    liquidation_threshold: Uint128::new(80),
+    max_ltv: Uint128::new(70),
+    liquidation_bonus: Uint128::new(5),
+    debt_ceiling: None,
+    enabled: true,
+    pricing_policy: PricingPolicy::Spot,
+    liquidation_mode: LiquidationMode::FixedBonus,
+};
+const STABILITY_FEE: Decimal = Decimal::percent(10);
+const SETTLED_COLLATERAL: Uint128 = Uint128::new(147_058); // 1 usd of debt / 6.8 usd
+const SETTLED_AT_CACHED_PRICE: Uint128 = Uint128::new(132_352); // 0.9 usd of debt / 6.8 usd
+const REDEEMED_AFTER_SHUTDOWN: Uint128 = Uint128::new(73_529); // 147_058 * 1_000_000 / 2_000_000
+const DSC_TO_REPAY_BY_SEND: Uint128 = Uint128::new(100_000);
+const RECEIVE_LIQUIDATION_PRICE: i64 = 80_000;
+const DEBT_TO_COVER_BY_SEND: Uint128 = Uint128::new(500_000);
+const COLLATERAL_SEIZED_BY_SEND: Uint128 = Uint128::new(687_500); // 0.5 usd / 0.8 usd * 1.1
+const BATCH_PRICED_DSC_MINTED: Uint128 = Uint128::new(10_000_000); // health factor = 27.2 * 0.5 / 10 = 1.36
+const BATCH_PRICED_LIQUIDATION_PRICE: i64 = 400_000; // health factor = 16 * 0.5 / 10 = 0.8
+const BATCH_PRICED_COLLATERAL_SEIZED: Uint128 = Uint128::new(275_000); // 1 usd / 4 usd * 1.1
+const EMA_PRICE: i64 = 500_000; // 5 usd
+const WICK_PRICE: i64 = 100_000; // 1 usd
+const RALLY_PRICE: i64 = 600_000; // 6 usd
+const COLLATERAL_SEIZED_AT_EMA: Uint128 = Uint128::new(145_588); // 0.9 usd / 6.8 usd * 1.1
+const DEBT_CEILING: Uint128 = Uint128::new(100_000_000);
+const CW20_DEBT_CEILING: Uint128 = Uint128::new(5_000_000);
+const INITIAL_LIQUIDATOR_FEE_BALANCE: u128 = 10;
+const CLOSE_FACTOR: Decimal = Decimal::percent(50);
+const FULL_LIQUIDATION_HEALTH_FACTOR: Decimal = Decimal::percent(90);
+const COLLATERAL_SEIZED_AT_CLOSE_FACTOR: Uint128 = Uint128::new(567_010); // 0.5 usd / 0.97 usd * 1.1
+const LIQUIDATION_DUST: Uint128 = Uint128::new(600_000); // more than the 0.5 DSC left by the close factor
+const COLLATERAL_SEIZED_ABOVE_DUST: Uint128 = Uint128::new(1_134_020); // 1 usd / 0.97 usd * 1.1
+const FULL_LIQUIDATION_PRICE: i64 = 80_000; // health factor = 2 * 0.8 * 0.5 / 1 = 0.8
+const COLLATERAL_SEIZED_IN_FULL: Uint128 = Uint128::new(1_375_000); // 1 usd / 0.8 usd * 1.1
+const AUCTION_PARAMS: AuctionParams = AuctionParams {
+    starting_premium: Decimal::percent(120),
+    duration: 3_600,
+    reset_price_ratio: Decimal::percent(50),
+};
+const AUCTION_STARTING_PRICE: Decimal = Decimal::permille(1_164); // 0.97 usd * 1.2
+const AUCTIONED_COLLATERAL: Uint128 = Uint128::new(1_134_020); // 1 usd / 0.97 usd * 1.1
+const AUCTION_TAKE_DELAY: u64 = 600; // 1.164 usd * (3_600 - 600) / 3_600 = 0.97 usd
+const FIRST_TAKE_COLLATERAL: Uint128 = Uint128::new(500_000);
+const FIRST_TAKE_DSC: Uint128 = Uint128::new(485_000); // 0.5 * 0.97 usd
+const SECOND_TAKE_COLLATERAL: Uint128 = Uint128::new(530_927); // 0.515 usd of debt left / 0.97 usd
+const COLLATERAL_RETURNED_BY_AUCTION: Uint128 = Uint128::new(103_093); // 1_134_020 - 500_000 - 530_927
+const LIQUIDATION_PRICE_DECIMAL: Decimal = Decimal::permille(970);
+const YANKED_COLLATERAL_SETTLED: Uint128 = Uint128::new(1_030_927); // 1 usd of debt / 0.97 usd
+const UNDERWATER_PRICE: i64 = 52_000; // collateral = 2 * 0.52 = 1.04 usd, below 1 usd of debt * 1.1
+const UNDERWATER_MAX_DEBT_TO_COVER: Uint128 = Uint128::new(945_454); // 1.04 usd / 1.1
+const UNDERWATER_DEBT_COVERED: Uint128 = Uint128::new(945_454); // 2 / 1.1 * 0.52 usd
+const BAD_DEBT: Uint128 = Uint128::new(54_546); // 1_000_000 - 945_454
+const SURPLUS_DEPOSITED: Uint128 = Uint128::new(20_000);
+const LIQUIDATION_FEE_SHARE: Decimal = Decimal::percent(50);
+const ORIGINATION_FEE: Decimal = Decimal::percent(1);
+const ORIGINATION_FEE_PAID: Uint128 = Uint128::new(10_000); // 1_000_000 * 1%
+const LIQUIDATION_FEE_COLLATERAL: Uint128 = Uint128::new(46_391); // 0.9 usd / 0.97 usd * 10% * 50%
+const GOVERNANCE_AMOUNT_MINTED_TO_OWNER: Uint128 = Uint128::new(1_000_000);
+const GOVERNANCE_SENT_TO_LIQUIDATOR: Uint128 = Uint128::new(1_000);
+const SURPLUS_BUFFER: Uint128 = Uint128::new(5_000);
+const SURPLUS_LOT: Uint128 = Uint128::new(10_000);
+const DEBT_LOT: Uint128 = Uint128::new(40_000);
+const YANKED_DEBT_LOT: Uint128 = Uint128::new(4_000); // below the 4_546 bad debt left
+const DEBT_INITIAL_LOT: Uint128 = Uint128::new(1_000_000);
+const MIN_BID_INCREMENT: Decimal = Decimal::percent(5);
+const BID_DURATION: u64 = 600;
+const BALANCE_SHEET_AUCTION_DURATION: u64 = 3_600;
+const FIRST_SURPLUS_BID: Uint128 = Uint128::new(100);
+const WINNING_SURPLUS_BID: Uint128 = Uint128::new(105); // 100 * 1.05
+const FIRST_DEBT_LOT_BID: Uint128 = Uint128::new(900_000);
+const TOO_HIGH_DEBT_LOT_BID: Uint128 = Uint128::new(860_000); // 860_000 * 1.05 > 900_000
+const WINNING_DEBT_LOT_BID: Uint128 = Uint128::new(850_000);
+const REDEMPTION_FEE_FLOOR: Decimal = Decimal::permille(5);
+const DSC_REDEEMED: Uint128 = Uint128::new(1_200_000);
+const DSC_SENT_TO_REDEEMER: Uint128 = Uint128::new(200_000);
+const REDEMPTION_FEE_RATE: Decimal = Decimal::permille(305); // 0.005 + 1.2 / (2 DSC supply * 2)
+const COLLATERAL_REDEEMED_FROM_OWNER: Uint128 = Uint128::new(147_058); // 1 usd / 6.8 usd
+const COLLATERAL_REDEEMED_FROM_LIQUIDATOR: Uint128 = Uint128::new(29_411); // 0.2 usd / 6.8 usd
+const REDEMPTION_FEE: Uint128 = Uint128::new(53_823); // (147_058 + 29_411) * 0.305
+const SECOND_DSC_REDEEMED: Uint128 = Uint128::new(100_000);
+const UNDERWATER_REDEMPTION_PRICE: i64 = 40_000; // owner collateral = 2 * 0.4 = 0.8 usd for 1 DSC of debt
+const REDEMPTION_BAD_DEBT: Uint128 = Uint128::new(200_000); // 1 DSC - 0.8 usd
+const COLLATERAL_REDEEMED_AFTER_BAD_DEBT: Uint128 = Uint128::new(500_000); // 0.2 usd / 0.4 usd
+const MULTISIG: &str = "neutron1f3hyvtqcnpvx0p8jnnvm0kvxn8ua6cn8jq0ql9wl9cgqzw7alfnsc0x3gx";
 
 fn get_default_instantiate_msg(
     cw20_address: Option<&str>,
     dsc_address: Option<&str>,
     oracle_address: Option<&str>,
-    pyth_oracle_address: Option<&str>,
 ) -> InstantiateMsg {
     let native_collateral: AssetInfo = AssetInfo::Native(NATIVE_COLLATERAL_DENOM.to_string());
     let cw20_denom = cw20_address.unwrap_or(CW20_COLLATERAL_DENOM);
@@ -64,19 +173,23 @@ fn get_default_instantiate_msg(
     let assets: Vec<AssetInfo> = vec![native_collateral, cw20_collateral];
     let dsc_address = dsc_address.unwrap_or(DSC_ADDR);
     let oracle_address = oracle_address.unwrap_or(ORACLE_ADDRESS);
-    let pyth_oracle_address = pyth_oracle_address.unwrap_or(PYTH_ORACLE_ADDRESS);
-    let price_feed_ids: Vec<String> =
-        vec![String::from(PRICE_FEED_ID_1), String::from(PRICE_FEED_ID_2)];
     InstantiateMsg {
         owner: String::from(OWNER),
-        assets: assets,
+        assets,
         oracle_address: String::from(oracle_address),
-        pyth_oracle_address: String::from(pyth_oracle_address),
-        price_feed_ids: price_feed_ids,
         dsc_address: String::from(dsc_address),
         liquidation_threshold: LIQ_THRESHOLD,
         liquidation_bonus: LIQ_BONUS,
         min_health_factor: MIN_HEALTH_FACTOR,
+        native_assets_decimals: HashMap::from([(String::from(NATIVE_COLLATERAL_DENOM), 6)]),
+        stability_fee: Decimal::zero(),
+        close_factor: Decimal::one(),
+        full_liquidation_health_factor: Decimal::zero(),
+        liquidation_dust: Uint128::zero(),
+        liquidation_fee_share: Decimal::zero(),
+        stability_fee_share: Decimal::zero(),
+        origination_fee: Decimal::zero(),
+        redemption_fee_floor: Decimal::zero(),
     }
 }
 
@@ -108,8 +221,108 @@ fn get_dsc_instantiate_msg() -> Cw20InstantiateMsg {
     }
 }
 
-fn dsc_engine_setup(cw20_address: Option<&str>) -> (App, Addr) {
+thread_local! {
+    /// Queries served by the oracle of full_setup. multi-test does not meter gas, so oracle round
+    /// trips are counted instead
+    static ORACLE_QUERIES: Cell<u32> = const { Cell::new(0) };
+}
+
+fn counting_oracle_query(
+    deps: Deps,
+    env: Env,
+    msg: OracleQueryMsg,
+) -> Result<Binary, OracleContractError> {
+    ORACLE_QUERIES.with(|queries| queries.set(queries.get() + 1));
+    oracle_query(deps, env, msg)
+}
+
+/// Number of oracle queries made while running f
+fn count_oracle_queries<T>(f: impl FnOnce() -> T) -> (T, u32) {
+    ORACLE_QUERIES.with(|queries| queries.set(0));
+    let result = f();
+    (result, ORACLE_QUERIES.with(Cell::get))
+}
+
+fn get_oracle_instantiate_msg(pyth_contract_addr: &Addr) -> OracleInstantiateMsg {
+    OracleInstantiateMsg {
+        owner: None,
+        pyth_contract_addr: pyth_contract_addr.to_string(),
+    }
+}
+
+/// Mock-pyth feed with 5 decimals, its EMA price 100 above its price
+fn mock_price_feed(price_feed_id: &str, price: i64, publish_time: i64) -> PriceFeed {
+    PriceFeed::new(
+        PriceIdentifier::from_hex(price_feed_id).unwrap(),
+        Price {
+            price,
+            conf: 510_000,
+            expo: -5,
+            publish_time,
+        },
+        Price {
+            price: price + 100,
+            conf: 400_000,
+            expo: -5,
+            publish_time,
+        },
+    )
+}
+
+/// Serves MOCK_PRICE on PRICE_FEED_ID_1, published at the current block time
+fn get_mock_pyth_instantiate_msg(app: &App) -> MockPythInstantiateMsg {
+    MockPythInstantiateMsg {
+        price_feeds: vec![mock_price_feed(
+            PRICE_FEED_ID_1,
+            MOCK_PRICE,
+            app.block_info().time.seconds() as i64,
+        )],
+    }
+}
+
+/// Registers the price feed of each asset in the oracle, all of them on PRICE_FEED_ID_1
+fn register_oracle_feeds(app: &mut App, oracle_addr: &Addr, assets: &[&str]) {
+    for asset in assets {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            oracle_addr.clone(),
+            &OracleExecuteMsg::RegisterFeed {
+                asset: asset.to_string(),
+                price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+}
+
+/// Instantiates a cw20 token with `decimals` decimals, minting to OWNER the same amount of whole
+/// tokens as `get_cw20_instantiate_msg` (`decimals` must be at least 6)
+fn instantiate_cw20(app: &mut App, decimals: u8) -> Addr {
+    let cw20_code = ContractWrapper::new(cw20_execute, cw20_instantiate, cw20_query);
+    let cw20_code_id: u64 = app.store_code(Box::new(cw20_code));
+    app.instantiate_contract(
+        cw20_code_id,
+        Addr::unchecked(OWNER),
+        &Cw20InstantiateMsg {
+            decimals,
+            initial_balances: vec![Cw20Coin {
+                address: String::from(OWNER),
+                amount: CW20_AMOUNT_MINTED_TO_OWNER * Uint128::new(10).pow(decimals as u32 - 6),
+            }],
+            ..get_cw20_instantiate_msg()
+        },
+        &[],
+        "cw20",
+        Some(String::from(OWNER)),
+    )
+    .unwrap()
+}
+
+/// Instantiates a cw20 collateral and the DSC engine, returns the engine and cw20 addresses
+fn dsc_engine_setup() -> (App, Addr, Addr) {
     let mut app = App::default();
+    let cw20_addr = instantiate_cw20(&mut app, 6);
     let code = ContractWrapper::new(execute, instantiate, query);
     let code_id: u64 = app.store_code(Box::new(code));
 
@@ -117,35 +330,175 @@ fn dsc_engine_setup(cw20_address: Option<&str>) -> (App, Addr) {
         .instantiate_contract(
             code_id,
             Addr::unchecked(OWNER),
-            &get_default_instantiate_msg(cw20_address, None, None, None),
+            &get_default_instantiate_msg(Some(cw20_addr.as_str()), None, None),
+            &[],
+            "dsc_engine",
+            Some(String::from(OWNER)),
+        )
+        .unwrap();
+    (app, addr, cw20_addr)
+}
+
+/// Addresses of every contract instantiated by `full_setup`
+struct TestContracts {
+    mock_pyth: Addr,
+    oracle: Addr,
+    cw20: Addr,
+    dsc: Addr,
+    dsce: Addr,
+}
+
+/// Sets the pricing policy of the native collateral, its other risk parameters unchanged
+fn set_native_pricing_policy(app: &mut App, dsce: &Addr, pricing_policy: PricingPolicy) {
+    let params: CollateralParams = app
+        .wrap()
+        .query_wasm_smart(
+            dsce.clone(),
+            &QueryMsg::CollateralParams {
+                asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            params: CollateralParams {
+                pricing_policy,
+                ..params
+            },
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+/// Instantiates mock-pyth, oracle, cw20 collateral, DSC and DSC engine, sets the engine as DSC
+/// minter and gives it unlimited allowance over the cw20 collateral of OWNER
+fn full_setup() -> (App, TestContracts) {
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked(OWNER),
+                coins(INITIAL_OWNER_NATIVE_BALANCE, NATIVE_COLLATERAL_DENOM),
+            )
+            .unwrap()
+    });
+
+    let mock_pyth_code =
+        ContractWrapper::new(mock_pyth_execute, mock_pyth_instantiate, mock_pyth_query);
+    let mock_pyth_code_id: u64 = app.store_code(Box::new(mock_pyth_code));
+    let mock_pyth_addr = app
+        .instantiate_contract(
+            mock_pyth_code_id,
+            Addr::unchecked(OWNER),
+            &get_mock_pyth_instantiate_msg(&app),
+            &[],
+            "mock-pyth",
+            Some(String::from(OWNER)),
+        )
+        .unwrap();
+
+    let oracle_code =
+        ContractWrapper::new(oracle_execute, oracle_instantiate, counting_oracle_query);
+    let oracle_code_id: u64 = app.store_code(Box::new(oracle_code));
+    let oracle_addr = app
+        .instantiate_contract(
+            oracle_code_id,
+            Addr::unchecked(OWNER),
+            &get_oracle_instantiate_msg(&mock_pyth_addr),
+            &[],
+            "oracle",
+            Some(String::from(OWNER)),
+        )
+        .unwrap();
+
+    let cw20_addr = instantiate_cw20(&mut app, 6);
+
+    let dsc_code = ContractWrapper::new(dsc_execute, dsc_instantiate, dsc_query);
+    let dsc_code_id: u64 = app.store_code(Box::new(dsc_code));
+    let dsc_addr = app
+        .instantiate_contract(
+            dsc_code_id,
+            Addr::unchecked(OWNER),
+            &get_dsc_instantiate_msg(),
+            &[],
+            "dsc",
+            Some(String::from(OWNER)),
+        )
+        .unwrap();
+
+    let code = ContractWrapper::new(execute, instantiate, query);
+    let code_id: u64 = app.store_code(Box::new(code));
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
+    );
+
+    let dsce_addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(OWNER),
+            &get_default_instantiate_msg(
+                Some(cw20_addr.as_str()),
+                Some(dsc_addr.as_str()),
+                Some(oracle_addr.as_str()),
+            ),
             &[],
             "dsc_engine",
             Some(String::from(OWNER)),
         )
         .unwrap();
-    (app, addr)
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        dsc_addr.clone(),
+        &Cw20ExecuteMsg::UpdateMinter {
+            new_minter: Some(dsce_addr.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        cw20_addr.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: dsce_addr.to_string(),
+            amount: CW20_AMOUNT_MINTED_TO_OWNER,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let contracts = TestContracts {
+        mock_pyth: mock_pyth_addr,
+        oracle: oracle_addr,
+        cw20: cw20_addr,
+        dsc: dsc_addr,
+        dsce: dsce_addr,
+    };
+    (app, contracts)
 }
 
 #[test]
 fn proper_instantiation() {
-    let (app, addr) = dsc_engine_setup(None);
+    let (app, addr, cw20_addr) = dsc_engine_setup();
 
-    let config_input = get_default_instantiate_msg(None, None, None, None);
+    let config_input = get_default_instantiate_msg(Some(cw20_addr.as_str()), None, None);
 
     let config_res: ConfigResponse = app
         .wrap()
         .query_wasm_smart(addr.clone(), &QueryMsg::Config {})
         .unwrap();
 
-    let assets_to_feeds: HashMap<String, String> = config_input
-        .assets
-        .into_iter()
-        .map(|asset| asset.inner())
-        .zip(config_input.price_feed_ids.into_iter())
-        .collect();
-
-    assert_eq!(&config_res.owner, &config_input.owner);
-    assert_eq!(config_res.assets_to_feeds, assets_to_feeds);
+    assert_eq!(config_res.owner.unwrap(), config_input.owner);
+    assert_eq!(config_res.assets, config_input.assets);
     assert_eq!(&config_res.oracle_address, &config_input.oracle_address);
     assert_eq!(&config_res.dsc_address, &config_input.dsc_address);
 }
@@ -164,7 +517,7 @@ fn proper_deposit_valid_cw20_collateral_and_mint() {
         .instantiate_contract(
             mock_pyth_code_id,
             Addr::unchecked(OWNER),
-            &Empty {},
+            &get_mock_pyth_instantiate_msg(&app),
             &[],
             "mock-pyth",
             Some(String::from(OWNER)),
@@ -178,7 +531,7 @@ fn proper_deposit_valid_cw20_collateral_and_mint() {
         .instantiate_contract(
             oracle_code_id,
             Addr::unchecked(OWNER),
-            &OracleInstantiateMsg {},
+            &get_oracle_instantiate_msg(&mock_pyth_price_feed_addr),
             &[],
             "oracle",
             Some(String::from(OWNER)),
@@ -190,7 +543,6 @@ fn proper_deposit_valid_cw20_collateral_and_mint() {
         .query_wasm_smart(
             oracle_addr.clone(),
             &OracleQueryMsg::FetchPrice {
-                pyth_contract_addr: mock_pyth_price_feed_addr.to_string(),
                 price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
             },
         )
@@ -237,7 +589,12 @@ fn proper_deposit_valid_cw20_collateral_and_mint() {
         Some(cw20_addr.as_str()),
         Some(dsc_addr.as_str()),
         Some(oracle_addr.as_str()),
-        Some(mock_pyth_price_feed_addr.as_str()),
+    );
+
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
     );
 
     let dsce_addr = app
@@ -305,6 +662,7 @@ fn proper_deposit_valid_cw20_collateral_and_mint() {
                 collateral_asset: AssetInfo::Cw20(Addr::unchecked(cw20_addr.as_str())),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[],
         )
@@ -402,7 +760,7 @@ fn proper_deposit_valid_native_collateral_and_mint() {
         .instantiate_contract(
             mock_pyth_code_id,
             Addr::unchecked(OWNER),
-            &Empty {},
+            &get_mock_pyth_instantiate_msg(&app),
             &[],
             "mock-pyth",
             Some(String::from(OWNER)),
@@ -416,7 +774,7 @@ fn proper_deposit_valid_native_collateral_and_mint() {
         .instantiate_contract(
             oracle_code_id,
             Addr::unchecked(OWNER),
-            &OracleInstantiateMsg {},
+            &get_oracle_instantiate_msg(&mock_pyth_price_feed_addr),
             &[],
             "oracle",
             Some(String::from(OWNER)),
@@ -428,7 +786,6 @@ fn proper_deposit_valid_native_collateral_and_mint() {
         .query_wasm_smart(
             oracle_addr.clone(),
             &OracleQueryMsg::FetchPrice {
-                pyth_contract_addr: mock_pyth_price_feed_addr.to_string(),
                 price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
             },
         )
@@ -455,11 +812,17 @@ fn proper_deposit_valid_native_collateral_and_mint() {
     let code = ContractWrapper::new(execute, instantiate, query);
     let code_id: u64 = app.store_code(Box::new(code));
 
+    let cw20_addr = instantiate_cw20(&mut app, 6);
     let dsce_init_msg = &get_default_instantiate_msg(
-        None,
+        Some(cw20_addr.as_str()),
         Some(dsc_addr.as_str()),
         Some(oracle_addr.as_str()),
-        Some(mock_pyth_price_feed_addr.as_str()),
+    );
+
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
     );
 
     let dsce_addr = app
@@ -512,6 +875,7 @@ fn proper_deposit_valid_native_collateral_and_mint() {
                 collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[Coin {
                 denom: String::from(NATIVE_COLLATERAL_DENOM),
@@ -547,10 +911,7 @@ fn proper_deposit_valid_native_collateral_and_mint() {
         .unwrap();
 
     assert_eq!(initial_deposited_owner_native_balance, Uint128::zero());
-    assert_eq!(
-        final_deposited_owner_native_balance,
-        Uint128::from(AMOUNT_COLLATERAL_OK)
-    );
+    assert_eq!(final_deposited_owner_native_balance, AMOUNT_COLLATERAL_OK);
     assert_eq!(final_dsc_info.total_supply, AMOUNT_DSC_TO_MINT_OK);
     assert_eq!(final_owner_dsc_balance.balance, AMOUNT_DSC_TO_MINT_OK);
 }
@@ -569,7 +930,7 @@ fn proper_redeem_valid_cw20_collateral_and_burn_dsc() {
         .instantiate_contract(
             mock_pyth_code_id,
             Addr::unchecked(OWNER),
-            &Empty {},
+            &get_mock_pyth_instantiate_msg(&app),
             &[],
             "mock-pyth",
             Some(String::from(OWNER)),
@@ -583,7 +944,7 @@ fn proper_redeem_valid_cw20_collateral_and_burn_dsc() {
         .instantiate_contract(
             oracle_code_id,
             Addr::unchecked(OWNER),
-            &OracleInstantiateMsg {},
+            &get_oracle_instantiate_msg(&mock_pyth_price_feed_addr),
             &[],
             "oracle",
             Some(String::from(OWNER)),
@@ -631,7 +992,12 @@ fn proper_redeem_valid_cw20_collateral_and_burn_dsc() {
         Some(cw20_addr.as_str()),
         Some(dsc_addr.as_str()),
         Some(oracle_addr.as_str()),
-        Some(mock_pyth_price_feed_addr.as_str()),
+    );
+
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
     );
 
     let dsce_addr = app
@@ -683,6 +1049,7 @@ fn proper_redeem_valid_cw20_collateral_and_burn_dsc() {
                 collateral_asset: AssetInfo::Cw20(Addr::unchecked(cw20_addr.as_str())),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[],
         )
@@ -724,6 +1091,7 @@ fn proper_redeem_valid_cw20_collateral_and_burn_dsc() {
                 collateral_asset: AssetInfo::Cw20(Addr::unchecked(cw20_addr.as_str())),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_burn: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[],
         )
@@ -820,7 +1188,7 @@ fn proper_redeem_valid_native_collateral_and_burn_dsc() {
         .instantiate_contract(
             mock_pyth_code_id,
             Addr::unchecked(OWNER),
-            &Empty {},
+            &get_mock_pyth_instantiate_msg(&app),
             &[],
             "mock-pyth",
             Some(String::from(OWNER)),
@@ -834,7 +1202,7 @@ fn proper_redeem_valid_native_collateral_and_burn_dsc() {
         .instantiate_contract(
             oracle_code_id,
             Addr::unchecked(OWNER),
-            &OracleInstantiateMsg {},
+            &get_oracle_instantiate_msg(&mock_pyth_price_feed_addr),
             &[],
             "oracle",
             Some(String::from(OWNER)),
@@ -862,11 +1230,17 @@ fn proper_redeem_valid_native_collateral_and_burn_dsc() {
     let code = ContractWrapper::new(execute, instantiate, query);
     let code_id: u64 = app.store_code(Box::new(code));
 
+    let cw20_addr = instantiate_cw20(&mut app, 6);
     let dsce_init_msg = &get_default_instantiate_msg(
-        None,
+        Some(cw20_addr.as_str()),
         Some(dsc_addr.as_str()),
         Some(oracle_addr.as_str()),
-        Some(mock_pyth_price_feed_addr.as_str()),
+    );
+
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
     );
 
     let dsce_addr = app
@@ -903,6 +1277,7 @@ fn proper_redeem_valid_native_collateral_and_burn_dsc() {
                 collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[Coin {
                 denom: String::from(NATIVE_COLLATERAL_DENOM),
@@ -947,6 +1322,7 @@ fn proper_redeem_valid_native_collateral_and_burn_dsc() {
                 collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_burn: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[],
         )
@@ -1016,7 +1392,7 @@ fn proper_native_liquidation() {
         .instantiate_contract(
             mock_pyth_code_id,
             Addr::unchecked(OWNER),
-            &Empty {},
+            &get_mock_pyth_instantiate_msg(&app),
             &[],
             "mock-pyth",
             Some(String::from(OWNER)),
@@ -1030,7 +1406,7 @@ fn proper_native_liquidation() {
         .instantiate_contract(
             oracle_code_id,
             Addr::unchecked(OWNER),
-            &OracleInstantiateMsg {},
+            &get_oracle_instantiate_msg(&mock_pyth_price_feed_addr),
             &[],
             "oracle",
             Some(String::from(OWNER)),
@@ -1058,11 +1434,17 @@ fn proper_native_liquidation() {
     let code = ContractWrapper::new(execute, instantiate, query);
     let code_id: u64 = app.store_code(Box::new(code));
 
+    let cw20_addr = instantiate_cw20(&mut app, 6);
     let dsce_init_msg = &get_default_instantiate_msg(
-        None,
+        Some(cw20_addr.as_str()),
         Some(dsc_addr.as_str()),
         Some(oracle_addr.as_str()),
-        Some(mock_pyth_price_feed_addr.as_str()),
+    );
+
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
     );
 
     let dsce_addr = app
@@ -1099,6 +1481,7 @@ fn proper_native_liquidation() {
                 collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[Coin {
                 denom: String::from(NATIVE_COLLATERAL_DENOM),
@@ -1140,6 +1523,7 @@ fn proper_native_liquidation() {
             Addr::unchecked(OWNER),
             mock_pyth_price_feed_addr.clone(),
             &MockPythExecuteMsg::UpdateMockPrice {
+                id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
                 price: LIQUIDATION_PRICE,
             },
             &[],
@@ -1156,6 +1540,7 @@ fn proper_native_liquidation() {
                 collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
                 amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[Coin {
                 denom: String::from(NATIVE_COLLATERAL_DENOM),
@@ -1174,6 +1559,7 @@ fn proper_native_liquidation() {
                 collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
                 user: String::from(OWNER),
                 debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+                price_update_data: None,
             },
             &[],
         )
@@ -1312,7 +1698,7 @@ fn proper_cw20_liquidation() {
         .instantiate_contract(
             mock_pyth_code_id,
             Addr::unchecked(OWNER),
-            &Empty {},
+            &get_mock_pyth_instantiate_msg(&app),
             &[],
             "mock-pyth",
             Some(String::from(OWNER)),
@@ -1326,7 +1712,7 @@ fn proper_cw20_liquidation() {
         .instantiate_contract(
             oracle_code_id,
             Addr::unchecked(OWNER),
-            &OracleInstantiateMsg {},
+            &get_oracle_instantiate_msg(&mock_pyth_price_feed_addr),
             &[],
             "oracle",
             Some(String::from(OWNER)),
@@ -1358,7 +1744,12 @@ fn proper_cw20_liquidation() {
         Some(cw20_addr.as_str()),
         Some(dsc_addr.as_str()),
         Some(oracle_addr.as_str()),
-        Some(mock_pyth_price_feed_addr.as_str()),
+    );
+
+    register_oracle_feeds(
+        &mut app,
+        &oracle_addr,
+        &[NATIVE_COLLATERAL_DENOM, cw20_addr.as_str()],
     );
 
     let dsce_addr = app
@@ -1423,6 +1814,7 @@ fn proper_cw20_liquidation() {
                 collateral_asset: AssetInfo::Cw20(Addr::unchecked(cw20_addr.as_str())),
                 amount_collateral: AMOUNT_COLLATERAL_OK,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[],
         )
@@ -1461,6 +1853,7 @@ fn proper_cw20_liquidation() {
             Addr::unchecked(OWNER),
             mock_pyth_price_feed_addr.clone(),
             &MockPythExecuteMsg::UpdateMockPrice {
+                id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
                 price: LIQUIDATION_PRICE,
             },
             &[],
@@ -1477,6 +1870,7 @@ fn proper_cw20_liquidation() {
                 collateral_asset: AssetInfo::Cw20(Addr::unchecked(cw20_addr.as_str())),
                 amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
                 amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
             },
             &[],
         )
@@ -1492,6 +1886,7 @@ fn proper_cw20_liquidation() {
                 collateral_asset: AssetInfo::Cw20(Addr::unchecked(cw20_addr.as_str())),
                 user: String::from(OWNER),
                 debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+                price_update_data: None,
             },
             &[],
         )
@@ -1592,3 +1987,5471 @@ fn proper_cw20_liquidation() {
     );
     assert_eq!(final_dsc_info.total_supply, FINAL_DSC_SUPPLY);
 }
+
+#[test]
+fn proper_update_config_by_owner() {
+    let (mut app, addr, _) = dsc_engine_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            oracle_address: Some(String::from("new_oracle_addr")),
+            liquidation_threshold: Some(Uint128::new(40)),
+            liquidation_bonus: Some(Uint128::new(5)),
+            min_health_factor: None,
+            stability_fee: None,
+            close_factor: None,
+            full_liquidation_health_factor: None,
+            liquidation_dust: None,
+            liquidation_fee_share: None,
+            stability_fee_share: None,
+            origination_fee: None,
+            redemption_fee_floor: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(addr, &QueryMsg::Config {})
+        .unwrap();
+
+    assert_eq!(config_res.oracle_address, "new_oracle_addr");
+    assert_eq!(config_res.liquidation_threshold, Uint128::new(40));
+    assert_eq!(config_res.liquidation_bonus, Uint128::new(5));
+    assert_eq!(config_res.min_health_factor, MIN_HEALTH_FACTOR);
+}
+
+#[test]
+fn update_config_by_non_owner_fails() {
+    let (mut app, addr, _) = dsc_engine_setup();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                oracle_address: Some(String::from(NOT_OWNER)),
+                liquidation_threshold: None,
+                liquidation_bonus: None,
+                min_health_factor: None,
+                stability_fee: None,
+                close_factor: None,
+                full_liquidation_health_factor: None,
+                liquidation_dust: None,
+                liquidation_fee_share: None,
+                stability_fee_share: None,
+                origination_fee: None,
+                redemption_fee_floor: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            addr.clone(),
+            &ExecuteMsg::AddCollateralAsset {
+                asset: AssetInfo::Native(String::from(NEW_COLLATERAL_DENOM)),
+                decimals: Some(6),
+                params: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            addr,
+            &ExecuteMsg::RemoveCollateralAsset {
+                asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+}
+
+#[test]
+fn update_config_with_invalid_risk_params_fails() {
+    let (mut app, addr, _) = dsc_engine_setup();
+
+    let update_risk_params =
+        |liquidation_threshold: u128, liquidation_bonus: u128, min_health_factor: Decimal| {
+            ExecuteMsg::UpdateConfig {
+                oracle_address: None,
+                liquidation_threshold: Some(Uint128::new(liquidation_threshold)),
+                liquidation_bonus: Some(Uint128::new(liquidation_bonus)),
+                min_health_factor: Some(min_health_factor),
+                stability_fee: None,
+                close_factor: None,
+                full_liquidation_health_factor: None,
+                liquidation_dust: None,
+                liquidation_fee_share: None,
+                stability_fee_share: None,
+                origination_fee: None,
+                redemption_fee_floor: None,
+            }
+        };
+
+    // every position would have a zero health factor
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            addr.clone(),
+            &update_risk_params(0, 10, Decimal::one()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidLiquidationThreshold {}
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            addr.clone(),
+            &update_risk_params(50, 100, Decimal::one()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidLiquidationBonus {}
+    );
+
+    // 95% * 1.1 > 1: liquidating a position at the min health factor would lower it further
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            addr.clone(),
+            &update_risk_params(95, 10, Decimal::one()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMinHealthFactor {}
+    );
+
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config_res.liquidation_threshold, LIQ_THRESHOLD);
+    assert_eq!(config_res.liquidation_bonus, LIQ_BONUS);
+}
+
+#[test]
+fn proper_add_and_remove_collateral_asset() {
+    let (mut app, addr, _) = dsc_engine_setup();
+    let new_collateral = AssetInfo::Native(String::from(NEW_COLLATERAL_DENOM));
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::AddCollateralAsset {
+            asset: new_collateral.clone(),
+            decimals: Some(6),
+            params: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            addr.clone(),
+            &ExecuteMsg::AddCollateralAsset {
+                asset: new_collateral.clone(),
+                decimals: Some(6),
+                params: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CollateralAssetAlreadyRegistered {
+            denom: String::from(NEW_COLLATERAL_DENOM)
+        }
+    );
+
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config_res.assets.len(), 3);
+    assert!(config_res.assets.contains(&new_collateral));
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::RemoveCollateralAsset {
+            asset: new_collateral.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config_res.assets.len(), 2);
+    assert!(!config_res.assets.contains(&new_collateral));
+}
+
+#[test]
+fn remove_collateral_asset_with_deposits_fails() {
+    let (mut app, contracts) = full_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::RemoveCollateralAsset {
+                asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CollateralAssetInUse {
+            denom: contracts.cw20.to_string()
+        }
+    );
+
+    // once every deposit is redeemed the asset can be removed
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc,
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemCollateralForDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_burn: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce,
+        &ExecuteMsg::RemoveCollateralAsset {
+            asset: AssetInfo::Cw20(contracts.cw20),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn proper_ownership_transfer_to_multisig() {
+    let (mut app, addr, _) = dsc_engine_setup();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            addr.clone(),
+            &ExecuteMsg::ProposeNewOwner {
+                owner: String::from(NOT_OWNER),
+                expiry: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let expiry = Expiration::AtHeight(app.block_info().height + 10);
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::ProposeNewOwner {
+            owner: String::from(MULTISIG),
+            expiry: Some(expiry),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let ownership_res: OwnershipResponse = app
+        .wrap()
+        .query_wasm_smart(addr.clone(), &QueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(ownership_res.owner.unwrap(), OWNER);
+    assert_eq!(ownership_res.pending_owner.unwrap(), MULTISIG);
+    assert_eq!(ownership_res.pending_expiry, Some(expiry));
+
+    // only the proposed owner can accept
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        Addr::unchecked(MULTISIG),
+        addr.clone(),
+        &ExecuteMsg::AcceptOwnership {},
+        &[],
+    )
+    .unwrap();
+
+    let ownership_res: OwnershipResponse = app
+        .wrap()
+        .query_wasm_smart(addr.clone(), &QueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(ownership_res.owner.unwrap(), MULTISIG);
+    assert_eq!(ownership_res.pending_owner, None);
+    assert_eq!(ownership_res.pending_expiry, None);
+
+    // previous owner lost its permissions, multisig can now change the config
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        oracle_address: None,
+        liquidation_threshold: None,
+        liquidation_bonus: Some(Uint128::new(5)),
+        min_health_factor: None,
+        stability_fee: None,
+        close_factor: None,
+        full_liquidation_health_factor: None,
+        liquidation_dust: None,
+        liquidation_fee_share: None,
+        stability_fee_share: None,
+        origination_fee: None,
+        redemption_fee_floor: None,
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            addr.clone(),
+            &update_config_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(Addr::unchecked(MULTISIG), addr, &update_config_msg, &[])
+        .unwrap();
+}
+
+#[test]
+fn expired_or_cancelled_ownership_proposal_cannot_be_accepted() {
+    let (mut app, addr, _) = dsc_engine_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::ProposeNewOwner {
+            owner: String::from(MULTISIG),
+            expiry: Some(Expiration::AtHeight(app.block_info().height + 10)),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|block| block.height += 10);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(MULTISIG),
+            addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OwnershipProposalExpired {}
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::ProposeNewOwner {
+            owner: String::from(MULTISIG),
+            expiry: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::CancelOwnershipProposal {},
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(MULTISIG),
+            addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoOwnershipProposal {}
+    );
+
+    let ownership_res: OwnershipResponse = app
+        .wrap()
+        .query_wasm_smart(addr, &QueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(ownership_res.owner.unwrap(), OWNER);
+    assert_eq!(ownership_res.pending_owner, None);
+}
+
+#[test]
+fn proper_renounce_ownership() {
+    let (mut app, addr, _) = dsc_engine_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::ProposeNewOwner {
+            owner: String::from(MULTISIG),
+            expiry: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        addr.clone(),
+        &ExecuteMsg::RenounceOwnership {},
+        &[],
+    )
+    .unwrap();
+
+    let ownership_res: OwnershipResponse = app
+        .wrap()
+        .query_wasm_smart(addr.clone(), &QueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(ownership_res.owner, None);
+    assert_eq!(ownership_res.pending_owner, None);
+
+    // renouncing drops the pending proposal as well
+    let err = app
+        .execute_contract(
+            Addr::unchecked(MULTISIG),
+            addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoOwnershipProposal {}
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            addr,
+            &ExecuteMsg::RemoveCollateralAsset {
+                asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+}
+
+/// Layout of `Config` written by the 1.0.0 release of dsc-engine
+#[cw_serde]
+struct ConfigV1_0_0 {
+    owner: Addr,
+    assets: Vec<AssetInfo>,
+    assets_to_feeds: HashMap<String, String>,
+    oracle_address: Addr,
+    pyth_oracle_address: Addr,
+    dsc_address: Addr,
+    liquidation_threshold: Uint128,
+    liquidation_bonus: Uint128,
+    min_health_factor: Decimal,
+}
+
+/// Instantiates the engine leaving its storage as the 1.0.0 release would
+fn instantiate_v1_0_0(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg.clone())?;
+    let config = ConfigV1_0_0 {
+        owner: Addr::unchecked(msg.owner),
+        assets: msg.assets.clone(),
+        assets_to_feeds: msg
+            .assets
+            .into_iter()
+            .map(|asset| asset.inner())
+            .zip([String::from(PRICE_FEED_ID_1), String::from(PRICE_FEED_ID_2)])
+            .collect(),
+        oracle_address: Addr::unchecked(msg.oracle_address),
+        pyth_oracle_address: Addr::unchecked(PYTH_ORACLE_ADDRESS),
+        dsc_address: Addr::unchecked(msg.dsc_address),
+        liquidation_threshold: msg.liquidation_threshold,
+        liquidation_bonus: msg.liquidation_bonus,
+        min_health_factor: msg.min_health_factor,
+    };
+    Item::<ConfigV1_0_0>::new("config").save(deps.storage, &config)?;
+    set_contract_version(deps.storage, "dsc-engine", "1.0.0")?;
+    Ok(res)
+}
+
+type InstantiateFn =
+    fn(DepsMut, Env, MessageInfo, InstantiateMsg) -> Result<Response, ContractError>;
+
+fn instantiate_newer_version(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    set_contract_version(deps.storage, "dsc-engine", "99.0.0")?;
+    Ok(res)
+}
+
+fn instantiate_foreign_contract(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    set_contract_version(deps.storage, "crates.io:cw20-base", "0.16.0")?;
+    Ok(res)
+}
+
+#[test]
+fn proper_migration_from_v1_0_0_keeps_positions() {
+    let (mut app, contracts) = full_setup();
+    let config_before: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    // 1 - Instantiate engine v1 and make it DSC minter
+
+    let v1_code = ContractWrapper::new(execute, instantiate_v1_0_0, query);
+    let v1_code_id = app.store_code(Box::new(v1_code));
+    let v2_code = ContractWrapper::new(execute, instantiate, query).with_migrate(migrate);
+    let v2_code_id = app.store_code(Box::new(v2_code));
+
+    let dsce_v1_addr = app
+        .instantiate_contract(
+            v1_code_id,
+            Addr::unchecked(OWNER),
+            &get_default_instantiate_msg(
+                Some(contracts.cw20.as_str()),
+                Some(contracts.dsc.as_str()),
+                Some(config_before.oracle_address.as_str()),
+            ),
+            &[],
+            "dsc_engine_v1",
+            Some(String::from(OWNER)),
+        )
+        .unwrap();
+    // minter was handed to the engine instantiated by full_setup
+    app.execute_contract(
+        contracts.dsce.clone(),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::UpdateMinter {
+            new_minter: Some(dsce_v1_addr.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.cw20.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: dsce_v1_addr.to_string(),
+            amount: CW20_AMOUNT_MINTED_TO_OWNER,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Open positions with v1
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        dsce_v1_addr.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        dsce_v1_addr.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    let account_info_before: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            dsce_v1_addr.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+
+    // 3 - Migrate to v2
+
+    app.migrate_contract(
+        Addr::unchecked(OWNER),
+        dsce_v1_addr.clone(),
+        &MigrateMsg {},
+        v2_code_id,
+    )
+    .unwrap();
+
+    let version = query_contract_info(&app, dsce_v1_addr.to_string()).unwrap();
+    assert_eq!(version.contract, "dsc-engine");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(dsce_v1_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config_res.owner.unwrap(), OWNER);
+    assert_eq!(config_res.dsc_address, contracts.dsc);
+    assert_eq!(
+        config_res.assets_decimals.get(contracts.cw20.as_str()),
+        Some(&6)
+    );
+    assert_eq!(
+        config_res.assets_decimals.get(NATIVE_COLLATERAL_DENOM),
+        Some(&6)
+    );
+    let all_params: Vec<CollateralParamsResponse> = app
+        .wrap()
+        .query_wasm_smart(dsce_v1_addr.clone(), &QueryMsg::AllCollateralParams {})
+        .unwrap();
+    assert_eq!(all_params.len(), 2);
+    for collateral_params in all_params {
+        assert_eq!(
+            collateral_params.params,
+            CollateralParams {
+                liquidation_threshold: LIQ_THRESHOLD,
+                max_ltv: LIQ_THRESHOLD,
+                liquidation_bonus: LIQ_BONUS,
+                debt_ceiling: None,
+                enabled: true,
+                pricing_policy: PricingPolicy::Spot,
+                liquidation_mode: LiquidationMode::FixedBonus,
+            }
+        );
+    }
+
+    let account_info_after: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            dsce_v1_addr.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info_after, account_info_before);
+    assert_eq!(
+        account_info_after.total_dsc_minted,
+        AMOUNT_DSC_TO_MINT_OK + AMOUNT_DSC_TO_MINT_OK
+    );
+
+    for collateral_asset in [
+        contracts.cw20.to_string(),
+        String::from(NATIVE_COLLATERAL_DENOM),
+    ] {
+        let deposited_balance: Uint128 = app
+            .wrap()
+            .query_wasm_smart(
+                dsce_v1_addr.clone(),
+                &QueryMsg::CollateralBalanceOfUser {
+                    user: String::from(OWNER),
+                    collateral_asset,
+                },
+            )
+            .unwrap();
+        assert_eq!(deposited_balance, AMOUNT_COLLATERAL_OK);
+    }
+
+    // 4 - Positions opened with v1 keep working with v2
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: dsce_v1_addr.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        dsce_v1_addr.clone(),
+        &ExecuteMsg::RedeemCollateralForDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_burn: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        dsce_v1_addr,
+        &ExecuteMsg::RemoveCollateralAsset {
+            asset: AssetInfo::Cw20(contracts.cw20),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn migration_from_newer_or_foreign_contract_fails() {
+    let mut app = App::default();
+    let cw20_addr = instantiate_cw20(&mut app, 6);
+    let code = ContractWrapper::new(execute, instantiate, query).with_migrate(migrate);
+    let code_id = app.store_code(Box::new(code));
+
+    for (instantiate_fn, expected_err) in [
+        (
+            instantiate_newer_version as InstantiateFn,
+            ContractError::CannotDowngrade {
+                stored_version: String::from("99.0.0"),
+                new_version: String::from(env!("CARGO_PKG_VERSION")),
+            },
+        ),
+        (
+            instantiate_foreign_contract,
+            ContractError::CannotMigrate {
+                previous_contract: String::from("crates.io:cw20-base"),
+            },
+        ),
+    ] {
+        let old_code = ContractWrapper::new(execute, instantiate_fn, query);
+        let old_code_id = app.store_code(Box::new(old_code));
+        let addr = app
+            .instantiate_contract(
+                old_code_id,
+                Addr::unchecked(OWNER),
+                &get_default_instantiate_msg(Some(cw20_addr.as_str()), None, None),
+                &[],
+                "dsc_engine",
+                Some(String::from(OWNER)),
+            )
+            .unwrap();
+
+        let err = app
+            .migrate_contract(Addr::unchecked(OWNER), addr, &MigrateMsg {}, code_id)
+            .unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), expected_err);
+    }
+}
+
+#[test]
+fn proper_collateral_value_with_mixed_decimals() {
+    let (mut app, contracts) = full_setup();
+    let cw20_18_decimals_addr = instantiate_cw20(&mut app, 18);
+    register_oracle_feeds(
+        &mut app,
+        &contracts.oracle,
+        &[cw20_18_decimals_addr.as_str()],
+    );
+
+    // 1 - Register 18 decimals collateral, decimals are queried from the token contract
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::AddCollateralAsset {
+                asset: AssetInfo::Cw20(cw20_18_decimals_addr.clone()),
+                decimals: Some(6),
+                params: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAssetDecimals {
+            denom: cw20_18_decimals_addr.to_string()
+        }
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::AddCollateralAsset {
+                asset: AssetInfo::Native(String::from(NEW_COLLATERAL_DENOM)),
+                decimals: None,
+                params: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MissingNativeDecimals {
+            denom: String::from(NEW_COLLATERAL_DENOM)
+        }
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::AddCollateralAsset {
+            asset: AssetInfo::Cw20(cw20_18_decimals_addr.clone()),
+            decimals: None,
+            params: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        config_res
+            .assets_decimals
+            .get(cw20_18_decimals_addr.as_str()),
+        Some(&18)
+    );
+    assert_eq!(
+        config_res.assets_decimals.get(contracts.cw20.as_str()),
+        Some(&6)
+    );
+    assert_eq!(
+        config_res.assets_decimals.get(NATIVE_COLLATERAL_DENOM),
+        Some(&6)
+    );
+
+    // 2 - Deposit the same value of 6 and 18 decimals collaterals
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        cw20_18_decimals_addr.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_18_DECIMALS_COLLATERAL_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(cw20_18_decimals_addr.clone()),
+            amount_collateral: AMOUNT_18_DECIMALS_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    // 3 - Both collaterals are worth 13.6 usd
+
+    let usd_value_18_decimals: Decimal = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::GetUsdValue {
+                token: cw20_18_decimals_addr.to_string(),
+                amount: AMOUNT_18_DECIMALS_COLLATERAL_OK,
+            },
+        )
+        .unwrap();
+    let usd_value_6_decimals: Decimal = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::GetUsdValue {
+                token: String::from(NATIVE_COLLATERAL_DENOM),
+                amount: AMOUNT_COLLATERAL_OK,
+            },
+        )
+        .unwrap();
+    assert_eq!(usd_value_18_decimals, Decimal::from_ratio(136u128, 10u128));
+    assert_eq!(usd_value_6_decimals, usd_value_18_decimals);
+
+    let collateral_value: Decimal = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountCollateralValueUsd {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(collateral_value, Decimal::from_ratio(272u128, 10u128));
+
+    let health_factor: Decimal = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce,
+            &QueryMsg::UserHealthFactor {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(health_factor, Decimal::from_ratio(68u128, 10u128));
+}
+
+#[test]
+fn proper_18_decimals_cw20_liquidation() {
+    let (mut app, contracts) = full_setup();
+    let cw20_18_decimals_addr = instantiate_cw20(&mut app, 18);
+    register_oracle_feeds(
+        &mut app,
+        &contracts.oracle,
+        &[cw20_18_decimals_addr.as_str()],
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::AddCollateralAsset {
+            asset: AssetInfo::Cw20(cw20_18_decimals_addr.clone()),
+            decimals: None,
+            params: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+
+    // 1 - Deposit 18 decimals collateral and mint by user that will be liquidated
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        cw20_18_decimals_addr.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_18_DECIMALS_COLLATERAL_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(cw20_18_decimals_addr.clone()),
+            amount_collateral: AMOUNT_18_DECIMALS_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Drop price and mint DSC by liquidator using 6 decimals collateral
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 3 - Liquidate
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Liquidate {
+            collateral_asset: AssetInfo::Cw20(cw20_18_decimals_addr.clone()),
+            user: String::from(OWNER),
+            debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let final_deposited_owner_balance: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce,
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: cw20_18_decimals_addr.to_string(),
+            },
+        )
+        .unwrap();
+    let final_liquidator_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            cw20_18_decimals_addr,
+            &Cw20QueryMsg::Balance {
+                address: String::from(LIQUIDATOR),
+            },
+        )
+        .unwrap();
+    let final_liquidator_dsc_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc,
+            &Cw20QueryMsg::Balance {
+                address: String::from(LIQUIDATOR),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        final_deposited_owner_balance,
+        FINAL_18_DECIMALS_COLLATERAL_BALANCE_OF_LIQUIDATED
+    );
+    assert_eq!(
+        final_liquidator_balance.balance,
+        FINAL_18_DECIMALS_BALANCE_OF_LIQUIDATOR
+    );
+    assert_eq!(
+        final_liquidator_dsc_balance.balance,
+        FINAL_DSC_BALANCE_OF_LIQUIDATOR
+    );
+}
+
+#[test]
+fn proper_health_factor_weighted_by_collateral_params() {
+    let (mut app, contracts) = full_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            params: CW20_COLLATERAL_PARAMS,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let cw20_params: CollateralParams = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralParams {
+                asset: contracts.cw20.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(cw20_params, CW20_COLLATERAL_PARAMS);
+
+    let all_params: Vec<CollateralParamsResponse> = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::AllCollateralParams {})
+        .unwrap();
+    assert_eq!(
+        all_params,
+        vec![
+            CollateralParamsResponse {
+                asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                params: CollateralParams {
+                    liquidation_threshold: LIQ_THRESHOLD,
+                    max_ltv: LIQ_THRESHOLD,
+                    liquidation_bonus: LIQ_BONUS,
+                    debt_ceiling: None,
+                    enabled: true,
+                    pricing_policy: PricingPolicy::Spot,
+                    liquidation_mode: LiquidationMode::FixedBonus,
+                },
+            },
+            CollateralParamsResponse {
+                asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                params: CW20_COLLATERAL_PARAMS,
+            },
+        ]
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    // (13.6 * 0.8 + 13.6 * 0.5) / 2
+    let health_factor: Decimal = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce,
+            &QueryMsg::UserHealthFactor {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(health_factor, Decimal::from_ratio(884u128, 100u128));
+}
+
+#[test]
+fn mint_is_limited_by_collateral_params() {
+    let (mut app, contracts) = full_setup();
+
+    // 1 - Invalid or unauthorized params updates
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::UpdateCollateralParams {
+                asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                params: CW20_COLLATERAL_PARAMS,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::UpdateCollateralParams {
+                asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                params: CollateralParams {
+                    max_ltv: Uint128::new(90),
+                    ..CW20_COLLATERAL_PARAMS
+                },
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMaxLtv {}
+    );
+
+    // 2 - Minting above max ltv fails even if the health factor stays above min
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            params: CollateralParams {
+                liquidation_threshold: LIQ_THRESHOLD,
+                max_ltv: Uint128::new(40),
+                liquidation_bonus: LIQ_BONUS,
+                debt_ceiling: None,
+                enabled: true,
+                pricing_policy: PricingPolicy::Spot,
+                liquidation_mode: LiquidationMode::FixedBonus,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 13.6 * 0.4 = 5.44 usd of borrowing power, 13.6 * 0.5 = 6.8 usd before liquidation
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral: AMOUNT_COLLATERAL_OK,
+                amount_dsc_to_mint: Uint128::new(6_000_000),
+                price_update_data: None,
+            },
+            &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MaxLtvExceeded {
+            dsc_minted: Decimal::from_ratio(6u128, 1u128),
+            borrowing_power: Decimal::from_ratio(544u128, 100u128),
+        }
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: Uint128::new(5_000_000),
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    // withdrawing down to 1.8 * 6.8 * 0.4 = 4.896 usd of borrowing power keeps the health factor at 1.224
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::RedeemCollateral {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral: Uint128::new(200_000),
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MaxLtvExceeded {
+            dsc_minted: Decimal::from_ratio(5u128, 1u128),
+            borrowing_power: Decimal::from_ratio(4896u128, 1000u128),
+        }
+    );
+
+    // 3 - Minting cannot back more DSC with an asset than its debt ceiling, deposits still can
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            params: CollateralParams {
+                debt_ceiling: Some(CW20_DEBT_CEILING),
+                ..CW20_COLLATERAL_PARAMS
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 6 DSC split between 5.44 usd of native and 13.6 * 0.7 = 9.52 usd of cw20 borrowing power:
+    // 6 * 9.52 / 14.96 = 3.8 DSC backed by cw20
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    // backs more than the debt ceiling once valued at max_ltv, but only improves the position
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateral {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+        },
+        &[],
+    )
+    .unwrap();
+    // 7 * 19.04 / 24.48 = 5.44 DSC backed by cw20
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::MintDsc {
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DebtCeilingExceeded {
+            denom: contracts.cw20.to_string()
+        }
+    );
+
+    // 4 - Disabled assets cannot be deposited
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            params: CollateralParams {
+                enabled: false,
+                pricing_policy: PricingPolicy::Spot,
+                liquidation_mode: LiquidationMode::FixedBonus,
+                ..CW20_COLLATERAL_PARAMS
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce,
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                amount_collateral: AMOUNT_COLLATERAL_OK,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CollateralAssetDisabled {
+            denom: contracts.cw20.to_string()
+        }
+    );
+}
+
+#[test]
+fn withdrawals_split_the_debt_again() {
+    let (mut app, contracts) = full_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            params: CollateralParams {
+                liquidation_threshold: LIQ_THRESHOLD,
+                max_ltv: Uint128::new(40),
+                liquidation_bonus: LIQ_BONUS,
+                debt_ceiling: Some(CW20_DEBT_CEILING),
+                enabled: true,
+                pricing_policy: PricingPolicy::Spot,
+                liquidation_mode: LiquidationMode::FixedBonus,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            params: CW20_COLLATERAL_PARAMS,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 1 - 4 DSC split between 5.44 usd of native and 9.52 usd of cw20 borrowing power:
+    // 4 * 5.44 / 14.96 = 1.45 DSC backed by native
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: Uint128::new(3_000_000),
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Once the cw20 is withdrawn, native backs the whole 4 DSC
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemCollateral {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 3 - Other positions can only mint the 1 DSC left under the native debt ceiling
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral: AMOUNT_COLLATERAL_OK,
+                amount_dsc_to_mint: Uint128::new(2_000_000),
+                price_update_data: None,
+            },
+            &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DebtCeilingExceeded {
+            denom: String::from(NATIVE_COLLATERAL_DENOM)
+        }
+    );
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce,
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+}
+
+#[test]
+fn proper_stability_fee_accrual() {
+    let (mut app, contracts) = full_setup();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::UpdateConfig {
+                oracle_address: None,
+                liquidation_threshold: None,
+                liquidation_bonus: None,
+                min_health_factor: None,
+                stability_fee: Some(Decimal::percent(101)),
+                close_factor: None,
+                full_liquidation_health_factor: None,
+                liquidation_dust: None,
+                liquidation_fee_share: None,
+                stability_fee_share: None,
+                origination_fee: None,
+                redemption_fee_floor: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidStabilityFee {}
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateConfig {
+            oracle_address: None,
+            liquidation_threshold: None,
+            liquidation_bonus: None,
+            min_health_factor: None,
+            stability_fee: Some(STABILITY_FEE),
+            close_factor: None,
+            full_liquidation_health_factor: None,
+            liquidation_dust: None,
+            liquidation_fee_share: None,
+            stability_fee_share: None,
+            origination_fee: None,
+            redemption_fee_floor: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 1 - Mint and let a year of fees accrue
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(SECONDS_PER_YEAR);
+        block.height += 1;
+    });
+    let block_time = app.block_info().time.seconds();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPublishTime {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            publish_time: block_time as i64,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, Uint128::new(1_100_000));
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Drip {},
+            &[],
+        )
+        .unwrap();
+    let wasm = res.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "accrued_fees" && attr.value == "100000"));
+
+    let debt_rate: DebtRateResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::DebtRate {})
+        .unwrap();
+    assert_eq!(
+        debt_rate,
+        DebtRateResponse {
+            rate: Decimal::percent(110),
+            last_accrual: block_time,
+            total_normalized_debt: AMOUNT_DSC_TO_MINT_OK,
+            total_debt: Uint128::new(1_100_000),
+        }
+    );
+
+    // 2 - Repaying the accrued debt needs more DSC than was minted
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: Uint128::new(100_000),
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::Transfer {
+            recipient: String::from(OWNER),
+            amount: Uint128::new(100_000),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: Uint128::new(1_100_000),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::BurnDsc {
+            amount_dsc_to_burn: Uint128::new(1_100_000),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, Uint128::zero());
+}
+
+#[test]
+fn proper_pause_by_guardian() {
+    let (mut app, contracts) = full_setup();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateGuardian {
+            guardian: Some(String::from(MULTISIG)),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let pause_mint = ExecuteMsg::UpdatePauseFlags {
+        mint: Some(true),
+        deposit: None,
+        redeem: None,
+        liquidate: Some(true),
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            contracts.dsce.clone(),
+            &pause_mint,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(
+        Addr::unchecked(MULTISIG),
+        contracts.dsce.clone(),
+        &pause_mint,
+        &[],
+    )
+    .unwrap();
+
+    let status: StatusResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status.pause_flags,
+        PauseFlags {
+            mint: true,
+            deposit: false,
+            redeem: false,
+            liquidate: true,
+        }
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                amount_collateral: AMOUNT_COLLATERAL_OK,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ActionPaused {
+            action: String::from("mint")
+        }
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Liquidate {
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                user: String::from(OWNER),
+                debt_to_cover: Decimal::one(),
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ActionPaused {
+            action: String::from("liquidate")
+        }
+    );
+
+    // only the owner can unpause
+    let unpause_mint = ExecuteMsg::UpdatePauseFlags {
+        mint: Some(false),
+        deposit: None,
+        redeem: None,
+        liquidate: None,
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(MULTISIG),
+            contracts.dsce.clone(),
+            &unpause_mint,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &unpause_mint,
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce,
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn proper_global_settlement() {
+    let (mut app, contracts) = full_setup();
+
+    // 1 - Open two positions
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    // 2 - Shut down
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Shutdown {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Shutdown {},
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                amount_collateral: AMOUNT_COLLATERAL_OK,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractShutdown {}
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::RedeemCollateral {
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                amount_collateral: AMOUNT_COLLATERAL_OK,
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PositionNotSettled {}
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::RedeemAfterShutdown {
+                amount: AMOUNT_DSC_TO_MINT_OK,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DebtNotSettled {}
+    );
+
+    // 3 - Settle positions, prices stay frozen
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    for user in [OWNER, LIQUIDATOR] {
+        app.execute_contract(
+            Addr::unchecked(NOT_OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::SettlePosition {
+                user: String::from(user),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let status: StatusResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status.settlement_collateral,
+        HashMap::from([
+            (contracts.cw20.to_string(), SETTLED_COLLATERAL),
+            (String::from(NATIVE_COLLATERAL_DENOM), SETTLED_COLLATERAL),
+        ])
+    );
+
+    // pausing redemptions cannot lock the collateral of settled positions
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdatePauseFlags {
+            mint: None,
+            deposit: None,
+            redeem: Some(true),
+            liquidate: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemCollateral {
+            collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            amount_collateral: AMOUNT_COLLATERAL_OK - SETTLED_COLLATERAL,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 4 - DSC holders redeem their share of the settled collateral
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemAfterShutdown {
+            amount: AMOUNT_DSC_TO_MINT_OK,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let cw20_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.cw20,
+            &Cw20QueryMsg::Balance {
+                address: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        cw20_balance.balance,
+        CW20_AMOUNT_MINTED_TO_OWNER - SETTLED_COLLATERAL + REDEEMED_AFTER_SHUTDOWN
+    );
+    let native_balance = app
+        .wrap()
+        .query_balance(OWNER, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(
+        native_balance.amount,
+        Uint128::new(INITIAL_OWNER_NATIVE_BALANCE) - AMOUNT_COLLATERAL_OK + REDEEMED_AFTER_SHUTDOWN
+    );
+    let dsc_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc,
+            &Cw20QueryMsg::Balance {
+                address: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(dsc_balance.balance, Uint128::zero());
+}
+
+#[test]
+fn proper_deposit_repay_and_liquidate_through_cw20_send() {
+    let (mut app, contracts) = full_setup();
+
+    // 1 - Deposit cw20 collateral and mint without any allowance
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contracts.dsce.to_string(),
+            amount: AMOUNT_COLLATERAL_OK,
+            msg: to_json_binary(&ReceiveMsg::DepositCollateralAndMint {
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, AMOUNT_DSC_TO_MINT_OK);
+
+    // 2 - Only DSC can repay a debt, and only registered collaterals can be deposited
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.cw20.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.dsce.to_string(),
+                amount: DSC_TO_REPAY_BY_SEND,
+                msg: to_json_binary(&ReceiveMsg::RepayDebt { on_behalf_of: None }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCw20Token {
+            token: contracts.cw20.to_string()
+        }
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsc.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.dsce.to_string(),
+                amount: DSC_TO_REPAY_BY_SEND,
+                msg: to_json_binary(&ReceiveMsg::DepositCollateral {}).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCollateralAsset {
+            denom: contracts.dsc.to_string()
+        }
+    );
+
+    // 3 - Repay part of the debt by sending DSC
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contracts.dsce.to_string(),
+            amount: DSC_TO_REPAY_BY_SEND,
+            msg: to_json_binary(&ReceiveMsg::RepayDebt { on_behalf_of: None }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        account_info.total_dsc_minted,
+        AMOUNT_DSC_TO_MINT_OK - DSC_TO_REPAY_BY_SEND
+    );
+
+    // 4 - Liquidate by sending DSC
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: RECEIVE_LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contracts.dsce.to_string(),
+            amount: DEBT_TO_COVER_BY_SEND,
+            msg: to_json_binary(&ReceiveMsg::Liquidate {
+                user: String::from(OWNER),
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        account_info.total_dsc_minted,
+        AMOUNT_DSC_TO_MINT_OK - DSC_TO_REPAY_BY_SEND - DEBT_TO_COVER_BY_SEND
+    );
+    let cw20_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.cw20,
+            &Cw20QueryMsg::Balance {
+                address: String::from(LIQUIDATOR),
+            },
+        )
+        .unwrap();
+    assert_eq!(cw20_balance.balance, COLLATERAL_SEIZED_BY_SEND);
+    let dsc_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc.clone(),
+            &Cw20QueryMsg::Balance {
+                address: String::from(LIQUIDATOR),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        dsc_balance.balance,
+        AMOUNT_DSC_TO_MINT_OK - DEBT_TO_COVER_BY_SEND
+    );
+    let dsc_info: TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsc, &Cw20QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(
+        dsc_info.total_supply,
+        AMOUNT_DSC_TO_MINT_OK + AMOUNT_DSC_TO_MINT_OK
+            - DSC_TO_REPAY_BY_SEND
+            - DEBT_TO_COVER_BY_SEND
+    );
+}
+
+#[test]
+fn proper_standalone_deposit_mint_and_redeem() {
+    let (mut app, contracts) = full_setup();
+    let cw20_collateral = AssetInfo::Cw20(contracts.cw20.clone());
+
+    // 1 - Zero amounts are rejected
+
+    let zero_amount_msgs = [
+        ExecuteMsg::DepositCollateral {
+            collateral_asset: cw20_collateral.clone(),
+            amount_collateral: Uint128::zero(),
+        },
+        ExecuteMsg::MintDsc {
+            amount_dsc_to_mint: Uint128::zero(),
+            price_update_data: None,
+        },
+        ExecuteMsg::RedeemCollateral {
+            collateral_asset: cw20_collateral.clone(),
+            amount_collateral: Uint128::zero(),
+            price_update_data: None,
+        },
+    ];
+    for msg in zero_amount_msgs {
+        let err = app
+            .execute_contract(Addr::unchecked(OWNER), contracts.dsce.clone(), &msg, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidZeroAmount {}
+        );
+    }
+
+    // 2 - Deposit without minting, then mint against the deposit
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateral {
+            collateral_asset: cw20_collateral.clone(),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+        },
+        &[],
+    )
+    .unwrap();
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, Uint128::zero());
+    assert_eq!(
+        account_info.deposited_collateral_in_usd,
+        Decimal::from_atomics(136u128, 1).unwrap()
+    );
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::MintDsc {
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let dsc_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc.clone(),
+            &Cw20QueryMsg::Balance {
+                address: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(dsc_balance.balance, AMOUNT_DSC_TO_MINT_OK);
+
+    // 3 - Minting past the health factor fails, redeeming the free collateral succeeds
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::MintDsc {
+                amount_dsc_to_mint: Uint128::new(6_000_000),
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BreaksHealthFactor { .. }
+    ));
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemCollateral {
+            collateral_asset: cw20_collateral,
+            amount_collateral: AMOUNT_COLLATERAL_OK / Uint128::new(2),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let cw20_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.cw20,
+            &Cw20QueryMsg::Balance {
+                address: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        cw20_balance.balance,
+        CW20_AMOUNT_MINTED_TO_OWNER - AMOUNT_COLLATERAL_OK / Uint128::new(2)
+    );
+}
+
+#[test]
+fn proper_deposit_of_several_collaterals() {
+    let (mut app, contracts) = full_setup();
+    let native_collateral = Asset::native(NATIVE_COLLATERAL_DENOM, AMOUNT_COLLATERAL_OK);
+    let cw20_collateral = Asset::cw20(contracts.cw20.clone(), AMOUNT_COLLATERAL_OK);
+
+    // 1 - Unregistered denoms, undeclared or missing funds are rejected
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollaterals {
+                assets: vec![
+                    cw20_collateral.clone(),
+                    Asset::native(NEW_COLLATERAL_DENOM, AMOUNT_COLLATERAL_OK),
+                ],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCollateralAsset {
+            denom: String::from(NEW_COLLATERAL_DENOM)
+        }
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollaterals {
+                assets: vec![cw20_collateral.clone()],
+            },
+            &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnexpectedNativeFunds {
+            denom: String::from(NATIVE_COLLATERAL_DENOM)
+        }
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollaterals {
+                assets: vec![cw20_collateral.clone(), native_collateral.clone()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MissingNativeFunds {
+            denom: String::from(NATIVE_COLLATERAL_DENOM)
+        }
+    );
+
+    // 2 - Deposit native and cw20 collaterals at once
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollaterals {
+            assets: vec![cw20_collateral, native_collateral],
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    for token in [
+        contracts.cw20.to_string(),
+        String::from(NATIVE_COLLATERAL_DENOM),
+    ] {
+        let balance: Uint128 = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::GetCollateralBalanceOfUser {
+                    user: String::from(OWNER),
+                    token,
+                },
+            )
+            .unwrap();
+        assert_eq!(balance, AMOUNT_COLLATERAL_OK);
+    }
+    let native_balance = app
+        .wrap()
+        .query_balance(contracts.dsce, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(native_balance.amount, AMOUNT_COLLATERAL_OK);
+}
+
+#[test]
+fn collateral_is_priced_in_one_oracle_query() {
+    let (mut app, contracts) = full_setup();
+
+    // 1 - Open a position backed by two collaterals
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollaterals {
+            assets: vec![
+                Asset::cw20(contracts.cw20.clone(), AMOUNT_COLLATERAL_OK),
+                Asset::native(NATIVE_COLLATERAL_DENOM, AMOUNT_COLLATERAL_OK),
+            ],
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+
+    // health factor and max ltv checks used to price each collateral twice: 4 queries
+    let (res, oracle_queries) = count_oracle_queries(|| {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::MintDsc {
+                amount_dsc_to_mint: BATCH_PRICED_DSC_MINTED,
+                price_update_data: None,
+            },
+            &[],
+        )
+    });
+    res.unwrap();
+    assert_eq!(oracle_queries, 1);
+
+    // 2 - Liquidate it
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: BATCH_PRICED_LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // health factors before and after, seized amount and liquidator health factor used to
+    // query one price per collateral each: 6 queries
+    let (res, oracle_queries) = count_oracle_queries(|| {
+        app.execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Liquidate {
+                collateral_asset: AssetInfo::Cw20(contracts.cw20.clone()),
+                user: String::from(OWNER),
+                debt_to_cover: Decimal::one(),
+                price_update_data: None,
+            },
+            &[],
+        )
+    });
+    res.unwrap();
+    assert_eq!(oracle_queries, 1);
+
+    let cw20_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.cw20,
+            &Cw20QueryMsg::Balance {
+                address: String::from(LIQUIDATOR),
+            },
+        )
+        .unwrap();
+    assert_eq!(cw20_balance.balance, BATCH_PRICED_COLLATERAL_SEIZED);
+}
+
+#[test]
+fn collateral_is_valued_at_its_pricing_policy() {
+    let (mut app, contracts) = full_setup();
+    let update_mock_prices = |app: &mut App, spot: i64, ema: i64| {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.mock_pyth.clone(),
+            &MockPythExecuteMsg::UpdateMockPrice {
+                id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+                price: spot,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.mock_pyth.clone(),
+            &MockPythExecuteMsg::UpdateMockEmaPrice {
+                id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+                price: ema,
+            },
+            &[],
+        )
+        .unwrap();
+    };
+    let usd_value_of_collateral = |app: &App| -> Decimal {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::GetUsdValue {
+                    token: String::from(NATIVE_COLLATERAL_DENOM),
+                    amount: AMOUNT_COLLATERAL_OK,
+                },
+            )
+            .unwrap()
+    };
+    let token_amount_of_six_usd = |app: &App| -> Decimal {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::GetTokenAmountFromUsd {
+                    token: String::from(NATIVE_COLLATERAL_DENOM),
+                    usd_amount: Decimal::from_atomics(6u128, 0).unwrap(),
+                },
+            )
+            .unwrap()
+    };
+
+    // 1 - Spot wicks below the EMA
+
+    update_mock_prices(&mut app, WICK_PRICE, EMA_PRICE);
+
+    // assets registered without a policy are priced at spot
+    assert_eq!(usd_value_of_collateral(&app), Decimal::percent(200));
+    assert_eq!(token_amount_of_six_usd(&app), Decimal::percent(600));
+
+    set_native_pricing_policy(&mut app, &contracts.dsce, PricingPolicy::Ema);
+    assert_eq!(usd_value_of_collateral(&app), Decimal::percent(1000));
+    assert_eq!(token_amount_of_six_usd(&app), Decimal::percent(120));
+
+    // collateral is valued at the lower spot, debt converted at the higher EMA
+    set_native_pricing_policy(&mut app, &contracts.dsce, PricingPolicy::Conservative);
+    assert_eq!(usd_value_of_collateral(&app), Decimal::percent(200));
+    assert_eq!(token_amount_of_six_usd(&app), Decimal::percent(120));
+
+    // 2 - Spot rallies above the EMA
+
+    update_mock_prices(&mut app, RALLY_PRICE, EMA_PRICE);
+
+    // collateral is valued at the lower EMA, debt converted at the higher spot
+    assert_eq!(usd_value_of_collateral(&app), Decimal::percent(1000));
+    assert_eq!(token_amount_of_six_usd(&app), Decimal::percent(100));
+
+    set_native_pricing_policy(&mut app, &contracts.dsce, PricingPolicy::Spot);
+    assert_eq!(usd_value_of_collateral(&app), Decimal::percent(1200));
+}
+
+#[test]
+fn proper_liquidation_with_ema_and_conservative_pricing() {
+    let (mut app, contracts) = full_setup();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockEmaPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: 680_000,
+        },
+        &[],
+    )
+    .unwrap();
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+
+    // 1 - Open a position and the liquidator one, both backed by native collateral
+
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Spot wicks down while the EMA holds: 2 * 0.97 * 0.5 / 1 = 0.97 at spot
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    let liquidate = |app: &mut App| {
+        app.execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Liquidate {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                user: String::from(OWNER),
+                debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+                price_update_data: None,
+            },
+            &[],
+        )
+    };
+
+    // valued at EMA the position stays healthy
+    set_native_pricing_policy(&mut app, &contracts.dsce, PricingPolicy::Ema);
+    let err = liquidate(&mut app).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::HealthFactorOk {}
+    );
+
+    // 3 - Conservative pricing values it at spot, but seizes collateral at the higher EMA
+
+    set_native_pricing_policy(&mut app, &contracts.dsce, PricingPolicy::Conservative);
+    liquidate(&mut app).unwrap();
+
+    let owner_collateral: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        owner_collateral,
+        AMOUNT_COLLATERAL_OK - COLLATERAL_SEIZED_AT_EMA
+    );
+    let liquidator_balance = app
+        .wrap()
+        .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(liquidator_balance.amount, COLLATERAL_SEIZED_AT_EMA);
+}
+
+#[test]
+fn risk_reducing_actions_use_last_good_prices_while_oracle_is_stale() {
+    let (mut app, contracts) = full_setup();
+    let native_collateral = AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+
+    // a debt ceiling only prices the collateral when minting
+    let params: CollateralParams = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralParams {
+                asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: native_collateral.clone(),
+            params: CollateralParams {
+                debt_ceiling: Some(DEBT_CEILING),
+                ..params
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: native_collateral.clone(),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 1 - Anyone caches the last good price, then Pyth goes stale
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.oracle.clone(),
+        &OracleExecuteMsg::UpdatePriceCache {
+            assets: vec![
+                String::from(NATIVE_COLLATERAL_DENOM),
+                contracts.cw20.to_string(),
+            ],
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(61));
+
+    // 2 - Risk-increasing actions require fresh prices
+
+    for msg in [
+        ExecuteMsg::MintDsc {
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        ExecuteMsg::RedeemCollateral {
+            collateral_asset: native_collateral.clone(),
+            amount_collateral: Uint128::new(1),
+            price_update_data: None,
+        },
+        ExecuteMsg::Liquidate {
+            collateral_asset: native_collateral.clone(),
+            user: String::from(OWNER),
+            debt_to_cover: Decimal::one(),
+            price_update_data: None,
+        },
+    ] {
+        let err = app
+            .execute_contract(Addr::unchecked(OWNER), contracts.dsce.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(err
+            .root_cause()
+            .to_string()
+            .contains("older than 60 seconds"));
+    }
+
+    // 3 - Risk-reducing ones go on with the cached price
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateral {
+            collateral_asset: native_collateral,
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::BurnDsc {
+            amount_dsc_to_burn: DSC_TO_REPAY_BY_SEND,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let collateral_balance: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        collateral_balance,
+        AMOUNT_COLLATERAL_OK + AMOUNT_COLLATERAL_OK
+    );
+    let dsc_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc,
+            &Cw20QueryMsg::Balance {
+                address: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        dsc_balance.balance,
+        AMOUNT_DSC_TO_MINT_OK - DSC_TO_REPAY_BY_SEND
+    );
+
+    // 4 - Shutdown freezes the last good prices
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Shutdown {},
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::SettlePosition {
+            user: String::from(OWNER),
+        },
+        &[],
+    )
+    .unwrap();
+    let collateral_balance: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce,
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        collateral_balance,
+        AMOUNT_COLLATERAL_OK + AMOUNT_COLLATERAL_OK - SETTLED_AT_CACHED_PRICE
+    );
+}
+
+#[test]
+fn proper_liquidation_with_pyth_price_update() {
+    let (mut app, contracts) = full_setup();
+    let native_collateral = AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+    app.init_modules(|router, _, storage| {
+        router.bank.init_balance(
+            storage,
+            &Addr::unchecked(LIQUIDATOR),
+            vec![
+                Coin::new(
+                    FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+                    NATIVE_COLLATERAL_DENOM,
+                ),
+                Coin::new(INITIAL_LIQUIDATOR_FEE_BALANCE, UPDATE_FEE_DENOM),
+            ],
+        )
+    })
+    .unwrap();
+    let price_update = |app: &App, price: i64| {
+        Some(vec![to_json_binary(&mock_price_feed(
+            PRICE_FEED_ID_1,
+            price,
+            app.block_info().time.seconds() as i64,
+        ))
+        .unwrap()])
+    };
+    let update_fee = Coin::new(UPDATE_FEE_PER_UPDATE, UPDATE_FEE_DENOM);
+
+    // 1 - Open a position, then Pyth goes stale
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: native_collateral.clone(),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(61));
+
+    // 2 - The liquidator updates the price while depositing and minting, the fee is paid out of
+    // the funds sent and the rest of them is deposited
+
+    let liquidator_funds = vec![
+        Coin::new(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+        update_fee.clone(),
+    ];
+    let mut deposit_and_mint_msg = ExecuteMsg::DepositCollateralAndMintDsc {
+        collateral_asset: native_collateral.clone(),
+        amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
+        amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+        price_update_data: None,
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &deposit_and_mint_msg,
+            &coins(
+                FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+                NATIVE_COLLATERAL_DENOM,
+            ),
+        )
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("older than 60 seconds"));
+    if let ExecuteMsg::DepositCollateralAndMintDsc {
+        price_update_data, ..
+    } = &mut deposit_and_mint_msg
+    {
+        *price_update_data = price_update(&app, LIQUIDATION_PRICE);
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &deposit_and_mint_msg,
+        &liquidator_funds,
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 3 - Pyth goes stale again, the liquidator updates and liquidates atomically
+
+    app.update_block(|block| block.time = block.time.plus_seconds(61));
+    let liquidate_msg = ExecuteMsg::Liquidate {
+        collateral_asset: native_collateral,
+        user: String::from(OWNER),
+        debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+        price_update_data: price_update(&app, LIQUIDATION_PRICE),
+    };
+
+    // only DSC Engine can run an action on behalf of someone else
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::AfterPriceUpdate {
+                sender: Addr::unchecked(OWNER),
+                msg: Box::new(ExecuteMsg::MintDsc {
+                    amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                    price_update_data: None,
+                }),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &liquidate_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientUpdateFee {
+            fee: update_fee.clone()
+        }
+    );
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &liquidate_msg,
+        &[update_fee],
+    )
+    .unwrap();
+
+    let owner_collateral: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(owner_collateral, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATED);
+    let liquidator_collateral: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce,
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(LIQUIDATOR),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        liquidator_collateral,
+        FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR
+    );
+    let fees_paid = app
+        .wrap()
+        .query_balance(contracts.mock_pyth, UPDATE_FEE_DENOM)
+        .unwrap();
+    assert_eq!(fees_paid.amount.u128(), 2 * UPDATE_FEE_PER_UPDATE);
+}
+
+#[test]
+fn collaterals_are_priced_by_their_own_price_feed() {
+    let (mut app, contracts) = full_setup();
+    let usd_value = |app: &App, token: &str| {
+        app.wrap().query_wasm_smart::<Decimal>(
+            contracts.dsce.clone(),
+            &QueryMsg::GetUsdValue {
+                token: String::from(token),
+                amount: AMOUNT_COLLATERAL_OK,
+            },
+        )
+    };
+
+    // 1 - The cw20 collateral moves to a feed that mock-pyth does not serve yet
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.oracle.clone(),
+        &OracleExecuteMsg::RegisterFeed {
+            asset: contracts.cw20.to_string(),
+            price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID_2).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let err = usd_value(&app, contracts.cw20.as_str()).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&format!("Price feed {} not found", PRICE_FEED_ID_2)));
+
+    // 2 - Each collateral is valued at the price of its feed
+
+    let block_time = app.block_info().time.seconds() as i64;
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::SetPriceFeed {
+            price_feed: mock_price_feed(PRICE_FEED_ID_2, 200_000, block_time),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        usd_value(&app, NATIVE_COLLATERAL_DENOM).unwrap(),
+        Decimal::from_atomics(136u128, 1).unwrap()
+    );
+    assert_eq!(
+        usd_value(&app, contracts.cw20.as_str()).unwrap(),
+        Decimal::from_atomics(4u128, 0).unwrap()
+    );
+
+    // 3 - Only the collateral of a stale feed can no longer be valued
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPublishTime {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_2).unwrap(),
+            publish_time: block_time - 61,
+        },
+        &[],
+    )
+    .unwrap();
+    let err = usd_value(&app, contracts.cw20.as_str()).unwrap_err();
+    assert!(err.to_string().contains("older than 60 seconds"));
+    assert_eq!(
+        usd_value(&app, NATIVE_COLLATERAL_DENOM).unwrap(),
+        Decimal::from_atomics(136u128, 1).unwrap()
+    );
+}
+
+#[test]
+fn liquidation_is_limited_by_close_factor() {
+    let (mut app, contracts) = full_setup();
+    let update_liquidation_limits =
+        |app: &mut App,
+         close_factor: Decimal,
+         full_liquidation_health_factor: Decimal,
+         liquidation_dust: Uint128| {
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                contracts.dsce.clone(),
+                &ExecuteMsg::UpdateConfig {
+                    oracle_address: None,
+                    liquidation_threshold: None,
+                    liquidation_bonus: None,
+                    min_health_factor: None,
+                    stability_fee: None,
+                    close_factor: Some(close_factor),
+                    full_liquidation_health_factor: Some(full_liquidation_health_factor),
+                    liquidation_dust: Some(liquidation_dust),
+                    liquidation_fee_share: None,
+                    stability_fee_share: None,
+                    origination_fee: None,
+                    redemption_fee_floor: None,
+                },
+                &[],
+            )
+        };
+    let max_liquidatable = |app: &App| -> MaxLiquidatableResponse {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::MaxLiquidatable {
+                    user: String::from(OWNER),
+                    collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+                },
+            )
+            .unwrap()
+    };
+    let liquidate = |app: &mut App, debt_to_cover: Decimal| {
+        app.execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Liquidate {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                user: String::from(OWNER),
+                debt_to_cover,
+                price_update_data: None,
+            },
+            &[],
+        )
+    };
+    let update_mock_price = |app: &mut App, price: i64| {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.mock_pyth.clone(),
+            &MockPythExecuteMsg::UpdateMockPrice {
+                id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+                price,
+            },
+            &[],
+        )
+        .unwrap();
+    };
+
+    // 1 - Liquidation limits are validated
+
+    let err = update_liquidation_limits(
+        &mut app,
+        Decimal::zero(),
+        FULL_LIQUIDATION_HEALTH_FACTOR,
+        Uint128::zero(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCloseFactor {}
+    );
+    let err = update_liquidation_limits(
+        &mut app,
+        CLOSE_FACTOR,
+        MIN_HEALTH_FACTOR + Decimal::percent(1),
+        Uint128::zero(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFullLiquidationHealthFactor {}
+    );
+    update_liquidation_limits(
+        &mut app,
+        CLOSE_FACTOR,
+        FULL_LIQUIDATION_HEALTH_FACTOR,
+        Uint128::zero(),
+    )
+    .unwrap();
+
+    // 2 - Open a position and the liquidator one, nothing is liquidatable while healthy
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        max_liquidatable(&app),
+        MaxLiquidatableResponse {
+            max_debt_to_cover: Decimal::zero(),
+            collateral_to_seize: Uint128::zero(),
+        }
+    );
+
+    // 3 - Health factor 0.97: half of the debt can be covered
+
+    update_mock_price(&mut app, LIQUIDATION_PRICE);
+    assert_eq!(
+        max_liquidatable(&app),
+        MaxLiquidatableResponse {
+            max_debt_to_cover: CLOSE_FACTOR,
+            collateral_to_seize: COLLATERAL_SEIZED_AT_CLOSE_FACTOR,
+        }
+    );
+    let err = liquidate(&mut app, Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap()).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DebtToCoverExceedsMax {
+            max_debt_to_cover: CLOSE_FACTOR
+        }
+    );
+
+    // 4 - The whole debt can be covered when the close factor would leave dust
+
+    update_liquidation_limits(
+        &mut app,
+        CLOSE_FACTOR,
+        FULL_LIQUIDATION_HEALTH_FACTOR,
+        LIQUIDATION_DUST,
+    )
+    .unwrap();
+    assert_eq!(
+        max_liquidatable(&app),
+        MaxLiquidatableResponse {
+            max_debt_to_cover: Decimal::one(),
+            collateral_to_seize: COLLATERAL_SEIZED_ABOVE_DUST,
+        }
+    );
+    update_liquidation_limits(
+        &mut app,
+        CLOSE_FACTOR,
+        FULL_LIQUIDATION_HEALTH_FACTOR,
+        Uint128::zero(),
+    )
+    .unwrap();
+
+    // 5 - Below the full liquidation health factor the whole debt is covered at once
+
+    update_mock_price(&mut app, FULL_LIQUIDATION_PRICE);
+    let max = max_liquidatable(&app);
+    assert_eq!(
+        max,
+        MaxLiquidatableResponse {
+            max_debt_to_cover: Decimal::one(),
+            collateral_to_seize: COLLATERAL_SEIZED_IN_FULL,
+        }
+    );
+    liquidate(&mut app, max.max_debt_to_cover).unwrap();
+
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, Uint128::zero());
+    let liquidator_balance = app
+        .wrap()
+        .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(liquidator_balance.amount, COLLATERAL_SEIZED_IN_FULL);
+}
+
+#[test]
+fn proper_auction_liquidation() {
+    let (mut app, contracts) = full_setup();
+    let native_collateral = AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+    let params: CollateralParams = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralParams {
+                asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    let update_liquidation_mode = |app: &mut App, liquidation_mode: LiquidationMode| {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::UpdateCollateralParams {
+                asset: native_collateral.clone(),
+                params: CollateralParams {
+                    liquidation_mode,
+                    ..params.clone()
+                },
+            },
+            &[],
+        )
+    };
+    let query_auction = |app: &App| {
+        app.wrap().query_wasm_smart::<AuctionResponse>(
+            contracts.dsce.clone(),
+            &QueryMsg::Auction { id: 1 },
+        )
+    };
+    let take_auction = |app: &mut App, max_collateral: Uint128, max_price: Decimal| {
+        app.execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::TakeAuction {
+                id: 1,
+                max_collateral,
+                max_price,
+            },
+            &[],
+        )
+    };
+    let reset_auction = |app: &mut App| {
+        app.execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::ResetAuction { id: 1 },
+            &[],
+        )
+    };
+
+    // 1 - Auction params are validated, then native collateral is liquidated through auctions
+
+    let err = update_liquidation_mode(
+        &mut app,
+        LiquidationMode::Auction(AuctionParams {
+            starting_premium: Decimal::percent(90),
+            ..AUCTION_PARAMS
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAuctionParams {}
+    );
+    update_liquidation_mode(&mut app, LiquidationMode::Auction(AUCTION_PARAMS)).unwrap();
+
+    // 2 - Open a position and the bidder one, then the position becomes unhealthy
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: native_collateral.clone(),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 3 - Fixed bonus liquidations are rejected, the position is kicked into an auction
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::Liquidate {
+                collateral_asset: native_collateral.clone(),
+                user: String::from(OWNER),
+                debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+                price_update_data: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AuctionLiquidationOnly {
+            denom: String::from(NATIVE_COLLATERAL_DENOM)
+        }
+    );
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::KickAuction {
+            user: String::from(OWNER),
+            collateral_asset: native_collateral.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let auction = query_auction(&app).unwrap();
+    assert_eq!(auction.price, AUCTION_STARTING_PRICE);
+    assert!(!auction.needs_reset);
+    assert_eq!(auction.auction.debt, AMOUNT_DSC_TO_MINT_OK);
+    assert_eq!(auction.auction.collateral, AUCTIONED_COLLATERAL);
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, Uint128::zero());
+
+    // 4 - Bids above the max price fail, the auction is reset once its price decayed too far
+
+    let err = take_auction(&mut app, FIRST_TAKE_COLLATERAL, LIQUIDATION_PRICE_DECIMAL).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AuctionPriceTooHigh {
+            price: AUCTION_STARTING_PRICE
+        }
+    );
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(AUCTION_PARAMS.duration / 2);
+        block.height += 1;
+    });
+    assert!(query_auction(&app).unwrap().needs_reset);
+    let err = take_auction(&mut app, FIRST_TAKE_COLLATERAL, LIQUIDATION_PRICE_DECIMAL).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AuctionNeedsReset { id: 1 }
+    );
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPublishTime {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            publish_time: app.block_info().time.seconds() as i64,
+        },
+        &[],
+    )
+    .unwrap();
+    reset_auction(&mut app).unwrap();
+    assert_eq!(query_auction(&app).unwrap().price, AUCTION_STARTING_PRICE);
+    let err = reset_auction(&mut app).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AuctionNotResettable { id: 1 }
+    );
+
+    // 5 - Bids burn DSC at the decayed price until the debt is raised
+
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(AUCTION_TAKE_DELAY);
+        block.height += 1;
+    });
+    take_auction(&mut app, FIRST_TAKE_COLLATERAL, LIQUIDATION_PRICE_DECIMAL).unwrap();
+    let auction = query_auction(&app).unwrap();
+    assert_eq!(auction.auction.debt, AMOUNT_DSC_TO_MINT_OK - FIRST_TAKE_DSC);
+    assert_eq!(
+        auction.auction.collateral,
+        AUCTIONED_COLLATERAL - FIRST_TAKE_COLLATERAL
+    );
+    take_auction(&mut app, AUCTIONED_COLLATERAL, LIQUIDATION_PRICE_DECIMAL).unwrap();
+
+    // 6 - The auction is over and the collateral left is back in the position
+
+    query_auction(&app).unwrap_err();
+    let auctions: Vec<AuctionResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::Auctions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(auctions.is_empty());
+    let owner_collateral: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        owner_collateral,
+        AMOUNT_COLLATERAL_OK - AUCTIONED_COLLATERAL + COLLATERAL_RETURNED_BY_AUCTION
+    );
+    let bidder_collateral = app
+        .wrap()
+        .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(
+        bidder_collateral.amount,
+        FIRST_TAKE_COLLATERAL + SECOND_TAKE_COLLATERAL
+    );
+    let bidder_dsc: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc.clone(),
+            &Cw20QueryMsg::Balance {
+                address: String::from(LIQUIDATOR),
+            },
+        )
+        .unwrap();
+    assert_eq!(bidder_dsc.balance, Uint128::zero());
+}
+
+#[test]
+fn underwater_liquidation_records_and_settles_bad_debt() {
+    let (mut app, contracts) = full_setup();
+    let query_uint = |app: &App, msg: &QueryMsg| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(contracts.dsce.clone(), msg)
+            .unwrap()
+    };
+    let send_dsc = |app: &mut App, sender: &str, amount: Uint128, msg: &ReceiveMsg| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            contracts.dsc.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.dsce.to_string(),
+                amount,
+                msg: to_json_binary(msg).unwrap(),
+            },
+            &[],
+        )
+    };
+    let dsc_balance_of_liquidator = |app: &App| -> Uint128 {
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsc.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: String::from(LIQUIDATOR),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+
+    // 1 - Open a position and the liquidator one, the liquidation is limited by a close factor
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateConfig {
+            oracle_address: None,
+            liquidation_threshold: None,
+            liquidation_bonus: None,
+            min_health_factor: None,
+            stability_fee: None,
+            close_factor: Some(CLOSE_FACTOR),
+            full_liquidation_health_factor: Some(Decimal::zero()),
+            liquidation_dust: None,
+            liquidation_fee_share: None,
+            stability_fee_share: None,
+            origination_fee: None,
+            redemption_fee_floor: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Collateral crashes below the debt plus the liquidation bonus, the close factor no
+    // longer applies and all of it can be seized
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: UNDERWATER_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    let max: MaxLiquidatableResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::MaxLiquidatable {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        max.max_debt_to_cover,
+        Decimal::from_atomics(UNDERWATER_MAX_DEBT_TO_COVER, 6).unwrap()
+    );
+
+    // 3 - Covering the whole debt seizes the collateral left for the debt it is worth, the rest
+    // becomes bad debt
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Liquidate {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            user: String::from(OWNER),
+            debt_to_cover: Decimal::one(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(account_info.total_dsc_minted, Uint128::zero());
+    assert_eq!(account_info.deposited_collateral_in_usd, Decimal::zero());
+    let liquidator_collateral = app
+        .wrap()
+        .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(liquidator_collateral.amount, AMOUNT_COLLATERAL_OK);
+    assert_eq!(
+        dsc_balance_of_liquidator(&app),
+        AMOUNT_DSC_TO_MINT_OK - UNDERWATER_DEBT_COVERED
+    );
+    assert_eq!(query_uint(&app, &QueryMsg::TotalBadDebt {}), BAD_DEBT);
+
+    // 4 - Bad debt is settled with the surplus first, then with the DSC sent
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::SettleBadDebt {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoSurplus {}
+    );
+    send_dsc(
+        &mut app,
+        OWNER,
+        SURPLUS_DEPOSITED,
+        &ReceiveMsg::DepositSurplus {},
+    )
+    .unwrap();
+    assert_eq!(
+        query_uint(
+            &app,
+            &QueryMsg::Surplus {
+                asset: contracts.dsc.to_string(),
+            }
+        ),
+        SURPLUS_DEPOSITED
+    );
+    send_dsc(
+        &mut app,
+        LIQUIDATOR,
+        BAD_DEBT,
+        &ReceiveMsg::SettleBadDebt {},
+    )
+    .unwrap();
+    assert_eq!(
+        query_uint(&app, &QueryMsg::TotalBadDebt {}),
+        Uint128::zero()
+    );
+    assert_eq!(
+        query_uint(
+            &app,
+            &QueryMsg::Surplus {
+                asset: contracts.dsc.to_string(),
+            }
+        ),
+        Uint128::zero()
+    );
+    // DSC sent beyond the bad debt left by the surplus is sent back
+    assert_eq!(dsc_balance_of_liquidator(&app), SURPLUS_DEPOSITED);
+    let dsc_info: TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsc.clone(), &Cw20QueryMsg::TokenInfo {})
+        .unwrap();
+    // the bad debt is gone from the supply, only the DSC backed by the liquidator position is left
+    assert_eq!(dsc_info.total_supply, AMOUNT_DSC_TO_MINT_OK);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::SettleBadDebt {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoBadDebt {}
+    );
+}
+
+#[test]
+fn protocol_fees_accumulate_in_surplus() {
+    let (mut app, contracts) = full_setup();
+    let dsc_surplus = |app: &App| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::Surplus {
+                    asset: contracts.dsc.to_string(),
+                },
+            )
+            .unwrap()
+    };
+    let total_debt = |app: &App| -> Uint128 {
+        let debt_rate: DebtRateResponse = app
+            .wrap()
+            .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::DebtRate {})
+            .unwrap();
+        debt_rate.total_debt
+    };
+    let withdraw_surplus = |app: &mut App, sender: &str, amount: Asset| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            contracts.dsce.clone(),
+            &ExecuteMsg::WithdrawSurplus {
+                amount,
+                recipient: String::from(MULTISIG),
+            },
+            &[],
+        )
+    };
+
+    // 1 - Protocol fees are validated
+
+    let update_fees = |app: &mut App, liquidation_fee_share: Decimal, origination_fee: Decimal| {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::UpdateConfig {
+                oracle_address: None,
+                liquidation_threshold: None,
+                liquidation_bonus: None,
+                min_health_factor: None,
+                stability_fee: Some(STABILITY_FEE),
+                close_factor: None,
+                full_liquidation_health_factor: None,
+                liquidation_dust: None,
+                liquidation_fee_share: Some(liquidation_fee_share),
+                stability_fee_share: Some(Decimal::one()),
+                origination_fee: Some(origination_fee),
+                redemption_fee_floor: None,
+            },
+            &[],
+        )
+    };
+    let err = update_fees(&mut app, LIQUIDATION_FEE_SHARE, Decimal::one()).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidProtocolFees {}
+    );
+    update_fees(&mut app, LIQUIDATION_FEE_SHARE, ORIGINATION_FEE).unwrap();
+
+    // 2 - Minting adds the origination fee to the debt and to the surplus
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    let account_info: AccountInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::AccountInformation {
+                user: String::from(OWNER),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        account_info.total_dsc_minted,
+        AMOUNT_DSC_TO_MINT_OK + ORIGINATION_FEE_PAID
+    );
+    assert_eq!(
+        dsc_surplus(&app),
+        ORIGINATION_FEE_PAID + ORIGINATION_FEE_PAID
+    );
+
+    // 3 - The protocol keeps its share of the liquidation bonus
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: DEBT_TO_COVER,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Liquidate {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            user: String::from(OWNER),
+            debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let liquidator_collateral = app
+        .wrap()
+        .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(
+        liquidator_collateral.amount,
+        FINAL_BALANCE_OF_LIQUIDATOR - LIQUIDATION_FEE_COLLATERAL
+    );
+    let all_surplus: Vec<SurplusResponse> = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::AllSurplus {})
+        .unwrap();
+    assert!(all_surplus.contains(&SurplusResponse {
+        asset: String::from(NATIVE_COLLATERAL_DENOM),
+        amount: LIQUIDATION_FEE_COLLATERAL,
+    }));
+
+    // 4 - Only the owner withdraws the surplus, up to what was collected
+
+    let native_surplus = Asset::native(NATIVE_COLLATERAL_DENOM, LIQUIDATION_FEE_COLLATERAL);
+    let err = withdraw_surplus(&mut app, NOT_OWNER, native_surplus.clone()).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    let err = withdraw_surplus(
+        &mut app,
+        OWNER,
+        Asset::native(
+            NATIVE_COLLATERAL_DENOM,
+            LIQUIDATION_FEE_COLLATERAL + Uint128::one(),
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SurplusExceeded {
+            asset: String::from(NATIVE_COLLATERAL_DENOM),
+            available: LIQUIDATION_FEE_COLLATERAL,
+        }
+    );
+    withdraw_surplus(&mut app, OWNER, native_surplus).unwrap();
+    let multisig_collateral = app
+        .wrap()
+        .query_balance(MULTISIG, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(multisig_collateral.amount, LIQUIDATION_FEE_COLLATERAL);
+
+    // 5 - Stability fees accrued go to the DSC surplus, minted once withdrawn
+
+    let surplus_before = dsc_surplus(&app);
+    let debt_before = total_debt(&app);
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(SECONDS_PER_YEAR);
+        block.height += 1;
+    });
+    let stability_fees = total_debt(&app) - debt_before;
+    assert!(!stability_fees.is_zero());
+    let surplus = dsc_surplus(&app);
+    assert_eq!(surplus, surplus_before + stability_fees);
+    withdraw_surplus(&mut app, OWNER, Asset::cw20(contracts.dsc.clone(), surplus)).unwrap();
+    let multisig_dsc: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsc.clone(),
+            &Cw20QueryMsg::Balance {
+                address: String::from(MULTISIG),
+            },
+        )
+        .unwrap();
+    assert_eq!(multisig_dsc.balance, surplus);
+    assert_eq!(dsc_surplus(&app), Uint128::zero());
+}
+
+#[test]
+fn collateral_surplus_goes_to_the_settlement_pot() {
+    let (mut app, contracts) = full_setup();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateConfig {
+            oracle_address: None,
+            liquidation_threshold: None,
+            liquidation_bonus: None,
+            min_health_factor: None,
+            stability_fee: None,
+            close_factor: None,
+            full_liquidation_health_factor: None,
+            liquidation_dust: None,
+            liquidation_fee_share: Some(LIQUIDATION_FEE_SHARE),
+            stability_fee_share: None,
+            origination_fee: None,
+            redemption_fee_floor: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 1 - A liquidation leaves part of its bonus in the collateral surplus
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: DEBT_TO_COVER,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Liquidate {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            user: String::from(OWNER),
+            debt_to_cover: Decimal::from_atomics(DEBT_TO_COVER, 6).unwrap(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Shutdown moves it to the settlement pot, for DSC holders to redeem
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Shutdown {},
+        &[],
+    )
+    .unwrap();
+    let native_surplus: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::Surplus {
+                asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(native_surplus, Uint128::zero());
+    let status: StatusResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce, &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status.settlement_collateral,
+        HashMap::from([(
+            String::from(NATIVE_COLLATERAL_DENOM),
+            LIQUIDATION_FEE_COLLATERAL
+        )])
+    );
+}
+
+#[test]
+fn auctions_are_yanked_after_shutdown() {
+    let (mut app, contracts) = full_setup();
+    let native_collateral = AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+    let params: CollateralParams = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralParams {
+                asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::UpdateCollateralParams {
+            asset: native_collateral.clone(),
+            params: CollateralParams {
+                liquidation_mode: LiquidationMode::Auction(AUCTION_PARAMS),
+                ..params
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 1 - An unhealthy position is kicked into an auction, then the system is shut down
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: native_collateral.clone(),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: LIQUIDATION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::KickAuction {
+            user: String::from(OWNER),
+            collateral_asset: native_collateral,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Shutdown {},
+        &[],
+    )
+    .unwrap();
+
+    // 2 - The auction can neither be taken nor reset
+
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(AUCTION_TAKE_DELAY);
+        block.height += 1;
+    });
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::TakeAuction {
+                id: 1,
+                max_collateral: FIRST_TAKE_COLLATERAL,
+                max_price: LIQUIDATION_PRICE_DECIMAL,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractShutdown {}
+    );
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(AUCTION_PARAMS.duration);
+        block.height += 1;
+    });
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::ResetAuction { id: 1 },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractShutdown {}
+    );
+
+    // 3 - DSC cannot be redeemed while debt is still on auction
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::RedeemAfterShutdown {
+                amount: AMOUNT_DSC_TO_MINT_OK,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DebtNotSettled {}
+    );
+
+    // 4 - Yanking gives the collateral and debt back to the position, which is then settled
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::YankAuction { id: 1 },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            contracts.dsce.clone(),
+            &ExecuteMsg::YankAuction { id: 1 },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AuctionNotFound { id: 1 }
+    );
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::SettlePosition {
+            user: String::from(OWNER),
+        },
+        &[],
+    )
+    .unwrap();
+    let collateral_balance: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::CollateralBalanceOfUser {
+                user: String::from(OWNER),
+                collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        collateral_balance,
+        AMOUNT_COLLATERAL_OK - YANKED_COLLATERAL_SETTLED
+    );
+
+    // 5 - DSC holders redeem the auctioned collateral
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce,
+        &ExecuteMsg::RedeemAfterShutdown {
+            amount: AMOUNT_DSC_TO_MINT_OK,
+        },
+        &[],
+    )
+    .unwrap();
+    let native_balance = app
+        .wrap()
+        .query_balance(OWNER, NATIVE_COLLATERAL_DENOM)
+        .unwrap();
+    assert_eq!(
+        native_balance.amount.u128(),
+        INITIAL_OWNER_NATIVE_BALANCE - AMOUNT_COLLATERAL_OK.u128()
+            + YANKED_COLLATERAL_SETTLED.u128()
+    );
+}
+
+#[test]
+fn proper_surplus_and_debt_auctions() {
+    let (mut app, contracts) = full_setup();
+    let cw20_code = ContractWrapper::new(cw20_execute, cw20_instantiate, cw20_query);
+    let cw20_code_id: u64 = app.store_code(Box::new(cw20_code));
+    let governance_token = app
+        .instantiate_contract(
+            cw20_code_id,
+            Addr::unchecked(OWNER),
+            &Cw20InstantiateMsg {
+                name: String::from("Governance Token"),
+                symbol: String::from("GOV"),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: String::from(OWNER),
+                    amount: GOVERNANCE_AMOUNT_MINTED_TO_OWNER,
+                }],
+                mint: Some(MinterResponse {
+                    minter: contracts.dsce.to_string(),
+                    cap: None,
+                }),
+                marketing: None,
+            },
+            &[],
+            "governance",
+            Some(String::from(OWNER)),
+        )
+        .unwrap();
+    let balance_of = |app: &App, token: &Addr, address: &str| -> Uint128 {
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: String::from(address),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    let query_uint = |app: &App, msg: &QueryMsg| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(contracts.dsce.clone(), msg)
+            .unwrap()
+    };
+    let execute = |app: &mut App, msg: &ExecuteMsg| {
+        app.execute_contract(Addr::unchecked(OWNER), contracts.dsce.clone(), msg, &[])
+    };
+    let send = |app: &mut App, sender: &str, token: &Addr, amount: Uint128, msg: &ReceiveMsg| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.dsce.to_string(),
+                amount,
+                msg: to_json_binary(msg).unwrap(),
+            },
+            &[],
+        )
+    };
+    let wait_bid_duration = |app: &mut App| {
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(BID_DURATION);
+            block.height += 1;
+        });
+    };
+    let params = BalanceSheetAuctionParams {
+        governance_token: governance_token.clone(),
+        surplus_buffer: SURPLUS_BUFFER,
+        surplus_lot: SURPLUS_LOT,
+        debt_lot: DEBT_LOT,
+        debt_initial_lot: DEBT_INITIAL_LOT,
+        min_bid_increment: MIN_BID_INCREMENT,
+        bid_duration: BID_DURATION,
+        auction_duration: BALANCE_SHEET_AUCTION_DURATION,
+    };
+
+    // 1 - Auctions need valid parameters
+
+    let err = execute(&mut app, &ExecuteMsg::KickSurplusAuction {}).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BalanceSheetAuctionsDisabled {}
+    );
+    let err = execute(
+        &mut app,
+        &ExecuteMsg::UpdateBalanceSheetAuctionParams {
+            params: BalanceSheetAuctionParams {
+                surplus_lot: Uint128::zero(),
+                ..params.clone()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidBalanceSheetAuctionParams {}
+    );
+    execute(
+        &mut app,
+        &ExecuteMsg::UpdateBalanceSheetAuctionParams {
+            params: params.clone(),
+        },
+    )
+    .unwrap();
+
+    // 2 - Open a position and the liquidator one, then fill the DSC surplus
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    send(
+        &mut app,
+        OWNER,
+        &contracts.dsc,
+        SURPLUS_DEPOSITED,
+        &ReceiveMsg::DepositSurplus {},
+    )
+    .unwrap();
+
+    // 3 - The surplus above the buffer is sold for governance tokens, each bid improving the last
+
+    execute(&mut app, &ExecuteMsg::KickSurplusAuction {}).unwrap();
+    let err = execute(&mut app, &ExecuteMsg::KickSurplusAuction {}).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientSurplusForAuction {}
+    );
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        governance_token.clone(),
+        &Cw20ExecuteMsg::Transfer {
+            recipient: String::from(LIQUIDATOR),
+            amount: GOVERNANCE_SENT_TO_LIQUIDATOR,
+        },
+        &[],
+    )
+    .unwrap();
+    send(
+        &mut app,
+        OWNER,
+        &governance_token,
+        FIRST_SURPLUS_BID,
+        &ReceiveMsg::BidSurplusAuction { id: 1 },
+    )
+    .unwrap();
+    let err = send(
+        &mut app,
+        LIQUIDATOR,
+        &governance_token,
+        WINNING_SURPLUS_BID - Uint128::one(),
+        &ReceiveMsg::BidSurplusAuction { id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BidNotImproved {}
+    );
+    send(
+        &mut app,
+        LIQUIDATOR,
+        &governance_token,
+        WINNING_SURPLUS_BID,
+        &ReceiveMsg::BidSurplusAuction { id: 1 },
+    )
+    .unwrap();
+    // the outbid governance tokens went back
+    assert_eq!(
+        balance_of(&app, &governance_token, OWNER),
+        GOVERNANCE_AMOUNT_MINTED_TO_OWNER - GOVERNANCE_SENT_TO_LIQUIDATOR
+    );
+
+    // 4 - Once the last bid expires, the winner gets the DSC lot and the bid is burnt
+
+    let err = execute(&mut app, &ExecuteMsg::SettleBalanceSheetAuction { id: 1 }).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BalanceSheetAuctionNotEnded { id: 1 }
+    );
+    wait_bid_duration(&mut app);
+    let err = send(
+        &mut app,
+        OWNER,
+        &governance_token,
+        WINNING_SURPLUS_BID + WINNING_SURPLUS_BID,
+        &ReceiveMsg::BidSurplusAuction { id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BalanceSheetAuctionEnded { id: 1 }
+    );
+    execute(&mut app, &ExecuteMsg::SettleBalanceSheetAuction { id: 1 }).unwrap();
+    assert_eq!(
+        balance_of(&app, &contracts.dsc, LIQUIDATOR),
+        AMOUNT_DSC_TO_MINT_OK + SURPLUS_LOT
+    );
+    let governance_info: TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(governance_token.clone(), &Cw20QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(
+        governance_info.total_supply,
+        GOVERNANCE_AMOUNT_MINTED_TO_OWNER - WINNING_SURPLUS_BID
+    );
+
+    // 5 - An underwater liquidation leaves bad debt, which blocks surplus auctions
+
+    let block_time = app.block_info().time.seconds() as i64;
+    for msg in [
+        MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: UNDERWATER_PRICE,
+        },
+        MockPythExecuteMsg::UpdateMockPublishTime {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            publish_time: block_time,
+        },
+    ] {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.mock_pyth.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::Liquidate {
+            collateral_asset: AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM)),
+            user: String::from(OWNER),
+            debt_to_cover: Decimal::one(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_uint(&app, &QueryMsg::TotalBadDebt {}), BAD_DEBT);
+    let err = execute(&mut app, &ExecuteMsg::KickSurplusAuction {}).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BadDebtOutstanding {}
+    );
+
+    // 6 - The surplus left settles bad debt first, then a lot of it is auctioned
+
+    execute(&mut app, &ExecuteMsg::KickDebtAuction {}).unwrap();
+    let bad_debt_after_surplus = BAD_DEBT - (SURPLUS_DEPOSITED - SURPLUS_LOT);
+    assert_eq!(
+        query_uint(&app, &QueryMsg::TotalBadDebt {}),
+        bad_debt_after_surplus
+    );
+    assert_eq!(query_uint(&app, &QueryMsg::DebtOnAuction {}), DEBT_LOT);
+    let err = execute(&mut app, &ExecuteMsg::KickDebtAuction {}).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientBadDebtForAuction {}
+    );
+
+    // 7 - Bidders pay the DSC lot for ever fewer governance tokens, the first bid settles bad debt
+
+    let err = send(
+        &mut app,
+        OWNER,
+        &contracts.dsc,
+        DEBT_LOT - Uint128::one(),
+        &ReceiveMsg::BidDebtAuction {
+            id: 2,
+            lot: FIRST_DEBT_LOT_BID,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDebtAuctionBid { bid: DEBT_LOT }
+    );
+    send(
+        &mut app,
+        OWNER,
+        &contracts.dsc,
+        DEBT_LOT,
+        &ReceiveMsg::BidDebtAuction {
+            id: 2,
+            lot: FIRST_DEBT_LOT_BID,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_uint(&app, &QueryMsg::TotalBadDebt {}),
+        bad_debt_after_surplus - DEBT_LOT
+    );
+    assert_eq!(
+        query_uint(&app, &QueryMsg::DebtOnAuction {}),
+        Uint128::zero()
+    );
+    let err = send(
+        &mut app,
+        LIQUIDATOR,
+        &contracts.dsc,
+        DEBT_LOT,
+        &ReceiveMsg::BidDebtAuction {
+            id: 2,
+            lot: TOO_HIGH_DEBT_LOT_BID,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BidNotImproved {}
+    );
+    send(
+        &mut app,
+        LIQUIDATOR,
+        &contracts.dsc,
+        DEBT_LOT,
+        &ReceiveMsg::BidDebtAuction {
+            id: 2,
+            lot: WINNING_DEBT_LOT_BID,
+        },
+    )
+    .unwrap();
+    // the outbid DSC went back
+    assert_eq!(
+        balance_of(&app, &contracts.dsc, OWNER),
+        AMOUNT_DSC_TO_MINT_OK - SURPLUS_DEPOSITED
+    );
+
+    // 8 - The winner gets the governance tokens minted for the debt raised
+
+    wait_bid_duration(&mut app);
+    execute(&mut app, &ExecuteMsg::SettleBalanceSheetAuction { id: 2 }).unwrap();
+    assert_eq!(
+        balance_of(&app, &governance_token, LIQUIDATOR),
+        GOVERNANCE_SENT_TO_LIQUIDATOR - WINNING_SURPLUS_BID + WINNING_DEBT_LOT_BID
+    );
+    let auctions: Vec<BalanceSheetAuctionResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::BalanceSheetAuctions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(auctions.is_empty());
+
+    // 9 - Balance sheet auctions stop once the system is shut down, live ones refund their bidder
+
+    execute(
+        &mut app,
+        &ExecuteMsg::UpdateBalanceSheetAuctionParams {
+            params: BalanceSheetAuctionParams {
+                debt_lot: YANKED_DEBT_LOT,
+                ..params.clone()
+            },
+        },
+    )
+    .unwrap();
+    execute(&mut app, &ExecuteMsg::KickDebtAuction {}).unwrap();
+    send(
+        &mut app,
+        OWNER,
+        &contracts.dsc,
+        YANKED_DEBT_LOT,
+        &ReceiveMsg::BidDebtAuction {
+            id: 3,
+            lot: FIRST_DEBT_LOT_BID,
+        },
+    )
+    .unwrap();
+    let err = execute(&mut app, &ExecuteMsg::YankBalanceSheetAuction { id: 3 }).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractNotShutdown {}
+    );
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPublishTime {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            publish_time: app.block_info().time.seconds() as i64,
+        },
+        &[],
+    )
+    .unwrap();
+    execute(&mut app, &ExecuteMsg::Shutdown {}).unwrap();
+    for msg in [
+        ExecuteMsg::UpdateBalanceSheetAuctionParams { params },
+        ExecuteMsg::KickSurplusAuction {},
+        ExecuteMsg::KickDebtAuction {},
+        ExecuteMsg::SettleBalanceSheetAuction { id: 3 },
+    ] {
+        let err = execute(&mut app, &msg).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::ContractShutdown {}
+        );
+    }
+    let err = send(
+        &mut app,
+        OWNER,
+        &contracts.dsc,
+        DEBT_LOT,
+        &ReceiveMsg::BidDebtAuction {
+            id: 3,
+            lot: WINNING_DEBT_LOT_BID,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractShutdown {}
+    );
+    execute(&mut app, &ExecuteMsg::YankBalanceSheetAuction { id: 3 }).unwrap();
+    assert_eq!(
+        balance_of(&app, &contracts.dsc, OWNER),
+        AMOUNT_DSC_TO_MINT_OK - SURPLUS_DEPOSITED
+    );
+    assert_eq!(
+        query_uint(&app, &QueryMsg::TotalBadDebt {}),
+        bad_debt_after_surplus - DEBT_LOT
+    );
+    let err = execute(&mut app, &ExecuteMsg::YankBalanceSheetAuction { id: 3 }).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BalanceSheetAuctionNotFound { id: 3 }
+    );
+}
+
+#[test]
+fn proper_dsc_redemption() {
+    let (mut app, contracts) = full_setup();
+    let redeem =
+        |app: &mut App, sender: &str, asset: AssetInfo, amount: Uint128, max_fee: Decimal| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                contracts.dsce.clone(),
+                &ExecuteMsg::RedeemDsc {
+                    amount,
+                    collateral_asset: asset,
+                    max_fee,
+                    price_update_data: None,
+                },
+                &[],
+            )
+        };
+    let native = || AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+    let fee_rate = |app: &App| -> Decimal {
+        app.wrap()
+            .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::RedemptionFeeRate {})
+            .unwrap()
+    };
+    let account = |app: &App, user: &str| -> (Uint128, Uint128) {
+        let info: AccountInfoResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::AccountInformation {
+                    user: String::from(user),
+                },
+            )
+            .unwrap();
+        let collateral: Uint128 = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::CollateralBalanceOfUser {
+                    user: String::from(user),
+                    collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+                },
+            )
+            .unwrap();
+        (info.total_dsc_minted, collateral)
+    };
+    let update_redemption_fee_floor = |app: &mut App, redemption_fee_floor: Decimal| {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            contracts.dsce.clone(),
+            &ExecuteMsg::UpdateConfig {
+                oracle_address: None,
+                liquidation_threshold: None,
+                liquidation_bonus: None,
+                min_health_factor: None,
+                stability_fee: None,
+                close_factor: None,
+                full_liquidation_health_factor: None,
+                liquidation_dust: None,
+                liquidation_fee_share: None,
+                stability_fee_share: None,
+                origination_fee: None,
+                redemption_fee_floor: Some(redemption_fee_floor),
+            },
+            &[],
+        )
+    };
+
+    // 1 - The redemption fee floor is validated
+
+    let err = update_redemption_fee_floor(&mut app, Decimal::percent(101)).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidProtocolFees {}
+    );
+    update_redemption_fee_floor(&mut app, REDEMPTION_FEE_FLOOR).unwrap();
+    assert_eq!(fee_rate(&app), REDEMPTION_FEE_FLOOR);
+
+    // 2 - Owner position holds 2 collateral per DSC of debt, liquidator position holds 4
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: native(),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::Transfer {
+            recipient: String::from(LIQUIDATOR),
+            amount: DSC_SENT_TO_REDEEMER,
+        },
+        &[],
+    )
+    .unwrap();
+    for (user, amount) in [(LIQUIDATOR, DSC_REDEEMED), (OWNER, SECOND_DSC_REDEEMED)] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsc.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: contracts.dsce.to_string(),
+                amount,
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // 3 - Redemptions need positions holding the collateral and a fee below max_fee
+
+    let err = redeem(
+        &mut app,
+        LIQUIDATOR,
+        AssetInfo::Cw20(contracts.cw20.clone()),
+        DSC_REDEEMED,
+        Decimal::one(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NothingToRedeem {
+            denom: contracts.cw20.to_string()
+        }
+    );
+    let err = redeem(
+        &mut app,
+        LIQUIDATOR,
+        native(),
+        DSC_REDEEMED,
+        Decimal::percent(30),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RedemptionFeeExceeded {
+            fee_rate: REDEMPTION_FEE_RATE,
+            max_fee: Decimal::percent(30),
+        }
+    );
+
+    // 4 - The owner position is fully repaid first, the rest comes from the liquidator position
+
+    let native_balance_before = app
+        .wrap()
+        .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+        .unwrap()
+        .amount;
+    redeem(
+        &mut app,
+        LIQUIDATOR,
+        native(),
+        DSC_REDEEMED,
+        REDEMPTION_FEE_RATE,
+    )
+    .unwrap();
+    assert_eq!(
+        account(&app, OWNER),
+        (
+            Uint128::zero(),
+            AMOUNT_COLLATERAL_OK - COLLATERAL_REDEEMED_FROM_OWNER
+        )
+    );
+    assert_eq!(
+        account(&app, LIQUIDATOR),
+        (
+            AMOUNT_DSC_TO_MINT_OK + AMOUNT_DSC_TO_MINT_OK - DSC_REDEEMED,
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR - COLLATERAL_REDEEMED_FROM_LIQUIDATOR
+        )
+    );
+    let collateral_drawn = COLLATERAL_REDEEMED_FROM_OWNER + COLLATERAL_REDEEMED_FROM_LIQUIDATOR;
+    assert_eq!(
+        app.wrap()
+            .query_balance(LIQUIDATOR, NATIVE_COLLATERAL_DENOM)
+            .unwrap()
+            .amount,
+        native_balance_before + collateral_drawn - REDEMPTION_FEE
+    );
+    let surplus: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.dsce.clone(),
+            &QueryMsg::Surplus {
+                asset: String::from(NATIVE_COLLATERAL_DENOM),
+            },
+        )
+        .unwrap();
+    assert_eq!(surplus, REDEMPTION_FEE);
+    let dsc_info: TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.dsc.clone(), &Cw20QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(
+        dsc_info.total_supply,
+        AMOUNT_DSC_TO_MINT_OK + AMOUNT_DSC_TO_MINT_OK - DSC_REDEEMED
+    );
+
+    // 5 - The base rate halves every 12 hours
+
+    assert_eq!(fee_rate(&app), REDEMPTION_FEE_RATE);
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(12 * 60 * 60);
+        block.height += 1;
+    });
+    let half_base_rate = (REDEMPTION_FEE_RATE - REDEMPTION_FEE_FLOOR) / Uint128::new(2);
+    assert!(fee_rate(&app).abs_diff(REDEMPTION_FEE_FLOOR + half_base_rate) < Decimal::permille(1));
+
+    // 6 - A repaid position is no longer redeemed from
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPublishTime {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            publish_time: app.block_info().time.seconds() as i64,
+        },
+        &[],
+    )
+    .unwrap();
+    redeem(
+        &mut app,
+        OWNER,
+        native(),
+        SECOND_DSC_REDEEMED,
+        Decimal::one(),
+    )
+    .unwrap();
+    assert_eq!(account(&app, OWNER).0, Uint128::zero());
+    assert_eq!(
+        account(&app, LIQUIDATOR).0,
+        AMOUNT_DSC_TO_MINT_OK + AMOUNT_DSC_TO_MINT_OK - DSC_REDEEMED - SECOND_DSC_REDEEMED
+    );
+}
+
+#[test]
+fn redemptions_rank_positions_by_the_debt_each_collateral_backs() {
+    let (mut app, contracts) = full_setup();
+    let native = || AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+    let dsc_minted = |app: &App, user: &str| -> Uint128 {
+        let info: AccountInfoResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::AccountInformation {
+                    user: String::from(user),
+                },
+            )
+            .unwrap();
+        info.total_dsc_minted
+    };
+
+    // 1 - Owner position holds 2 native and 4 cw20 per DSC of debt, liquidator position holds
+    // 4 native. The owner holds less native per DSC of its whole debt, but its native only backs
+    // a third of it.
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateral {
+            collateral_asset: native(),
+            amount_collateral: AMOUNT_COLLATERAL_OK,
+        },
+        &coins(AMOUNT_COLLATERAL_OK.u128(), NATIVE_COLLATERAL_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contracts.dsce.to_string(),
+            amount: AMOUNT_COLLATERAL_OK + AMOUNT_COLLATERAL_OK,
+            msg: to_json_binary(&ReceiveMsg::DepositCollateralAndMint {
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::DepositCollateralAndMintDsc {
+            collateral_asset: native(),
+            amount_collateral: FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR,
+            amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+            price_update_data: None,
+        },
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+
+    // 2 - Native is drawn from the liquidator position first
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: SECOND_DSC_REDEEMED,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemDsc {
+            amount: SECOND_DSC_REDEEMED,
+            collateral_asset: native(),
+            max_fee: Decimal::one(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(dsc_minted(&app, OWNER), AMOUNT_DSC_TO_MINT_OK);
+    assert_eq!(
+        dsc_minted(&app, LIQUIDATOR),
+        AMOUNT_DSC_TO_MINT_OK - SECOND_DSC_REDEEMED
+    );
+}
+
+#[test]
+fn redemptions_write_off_the_debt_left_by_underwater_positions() {
+    let (mut app, contracts) = full_setup();
+    let native = || AssetInfo::Native(String::from(NATIVE_COLLATERAL_DENOM));
+    let account = |app: &App, user: &str| -> (Uint128, Uint128) {
+        let info: AccountInfoResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::AccountInformation {
+                    user: String::from(user),
+                },
+            )
+            .unwrap();
+        let collateral: Uint128 = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.dsce.clone(),
+                &QueryMsg::CollateralBalanceOfUser {
+                    user: String::from(user),
+                    collateral_asset: String::from(NATIVE_COLLATERAL_DENOM),
+                },
+            )
+            .unwrap();
+        (info.total_dsc_minted, collateral)
+    };
+
+    // 1 - Owner position holds 2 collateral per DSC of debt, liquidator position holds 4, then
+    // the owner position is worth less than its debt
+
+    app.send_tokens(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(LIQUIDATOR),
+        &coins(
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR.u128(),
+            NATIVE_COLLATERAL_DENOM,
+        ),
+    )
+    .unwrap();
+    for (user, amount_collateral) in [
+        (OWNER, AMOUNT_COLLATERAL_OK),
+        (LIQUIDATOR, FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(user),
+            contracts.dsce.clone(),
+            &ExecuteMsg::DepositCollateralAndMintDsc {
+                collateral_asset: native(),
+                amount_collateral,
+                amount_dsc_to_mint: AMOUNT_DSC_TO_MINT_OK,
+                price_update_data: None,
+            },
+            &coins(amount_collateral.u128(), NATIVE_COLLATERAL_DENOM),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        contracts.mock_pyth.clone(),
+        &MockPythExecuteMsg::UpdateMockPrice {
+            id: PriceIdentifier::from_hex(PRICE_FEED_ID_1).unwrap(),
+            price: UNDERWATER_REDEMPTION_PRICE,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 2 - Redeeming drains the owner position and writes off the debt it leaves
+
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsc.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: contracts.dsce.to_string(),
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(LIQUIDATOR),
+        contracts.dsce.clone(),
+        &ExecuteMsg::RedeemDsc {
+            amount: AMOUNT_DSC_TO_MINT_OK,
+            collateral_asset: native(),
+            max_fee: Decimal::one(),
+            price_update_data: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(account(&app, OWNER), (Uint128::zero(), Uint128::zero()));
+    let bad_debt: Uint128 = app
+        .wrap()
+        .query_wasm_smart(contracts.dsce.clone(), &QueryMsg::TotalBadDebt {})
+        .unwrap();
+    assert_eq!(bad_debt, REDEMPTION_BAD_DEBT);
+    assert_eq!(
+        account(&app, LIQUIDATOR),
+        (
+            AMOUNT_DSC_TO_MINT_OK - REDEMPTION_BAD_DEBT,
+            FINAL_COLLATERAL_BALANCE_OF_LIQUIDATOR - COLLATERAL_REDEEMED_AFTER_BAD_DEBT
+        )
+    );
+}
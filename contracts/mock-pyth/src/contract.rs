@@ -1,20 +1,31 @@
 use crate::error::ContractError;
-use crate::msg::ExecuteMsg;
-use crate::state::PRICE;
+use crate::msg::{ExecuteMsg, InstantiateMsg};
+use crate::state::PRICE_FEEDS;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+    from_json, to_json_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage, Uint128,
 };
 use pyth_sdk_cw::{Price, PriceFeed, PriceFeedResponse, PriceIdentifier, QueryMsg};
+use std::time::Duration;
+
+/// Fee paid for each price update
+pub const UPDATE_FEE_DENOM: &str = "untrn";
+pub const UPDATE_FEE_PER_UPDATE: u128 = 1;
+/// Returned by GetValidTimePeriod, same as the Pyth contract on Neutron
+pub const VALID_TIME_PERIOD_SECS: u64 = 60;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
-    _msg: Empty,
+    msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    for price_feed in msg.price_feeds {
+        PRICE_FEEDS.save(deps.storage, price_feed.id.to_hex(), &price_feed)?;
+    }
     Ok(Response::default())
 }
 
@@ -22,53 +33,101 @@ pub fn instantiate(
 pub fn execute(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateMockPrice { price } => update_mock_price(deps, price),
+        ExecuteMsg::SetPriceFeed { price_feed } => {
+            PRICE_FEEDS.save(deps.storage, price_feed.id.to_hex(), &price_feed)?;
+            Ok(Response::default())
+        }
+        ExecuteMsg::UpdateMockPrice { id, price } => {
+            update_mock_feed(deps.storage, id, |spot, _| spot.price = price)
+        }
+        ExecuteMsg::UpdateMockConf { id, conf } => {
+            update_mock_feed(deps.storage, id, |spot, _| spot.conf = conf)
+        }
+        ExecuteMsg::UpdateMockExpo { id, expo } => {
+            update_mock_feed(deps.storage, id, |spot, ema| {
+                spot.expo = expo;
+                ema.expo = expo;
+            })
+        }
+        ExecuteMsg::UpdateMockEmaPrice { id, price } => {
+            update_mock_feed(deps.storage, id, |_, ema| ema.price = price)
+        }
+        ExecuteMsg::UpdateMockPublishTime { id, publish_time } => {
+            update_mock_feed(deps.storage, id, |spot, ema| {
+                spot.publish_time = publish_time;
+                ema.publish_time = publish_time;
+            })
+        }
+        ExecuteMsg::UpdatePriceFeeds { data } => update_price_feeds(deps, info, data),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, _: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
-        QueryMsg::PriceFeed { id } => to_json_binary(&mocked_price_feed(deps, id)?),
-        _ => panic!("Unsupported query!"),
+        QueryMsg::PriceFeed { id } => Ok(to_json_binary(&PriceFeedResponse {
+            price_feed: load_price_feed(deps.storage, id)?,
+        })?),
+        QueryMsg::GetUpdateFee { vaas } => Ok(to_json_binary(&update_fee(&vaas))?),
+        QueryMsg::GetValidTimePeriod => Ok(to_json_binary(&Duration::from_secs(
+            VALID_TIME_PERIOD_SECS,
+        ))?),
     }
 }
 
-fn mocked_price_feed(deps: Deps, id: PriceIdentifier) -> StdResult<PriceFeedResponse> {
-    let default_price = 680000;
-    let state_price = PRICE.may_load(deps.storage)?;
-    let price = match state_price {
-        Some(price) => price,
-        None => default_price,
-    };
-    let ema_price = price + 100;
+fn load_price_feed(storage: &dyn Storage, id: PriceIdentifier) -> Result<PriceFeed, ContractError> {
+    PRICE_FEEDS
+        .may_load(storage, id.to_hex())?
+        .ok_or(ContractError::PriceFeedNotFound { id: id.to_hex() })
+}
 
-    let price_feed_response = PriceFeedResponse {
-        price_feed: PriceFeed::new(
-            id,
-            Price {
-                price: price,
-                conf: 510000,
-                expo: -5,
-                publish_time: 1571797419,
-            },
-            Price {
-                price: ema_price,
-                conf: 400000,
-                expo: -5,
-                publish_time: 1571797419,
-            },
-        ),
-    };
+/// Applies `update` to the price and EMA price of a stored feed
+fn update_mock_feed(
+    storage: &mut dyn Storage,
+    id: PriceIdentifier,
+    update: impl FnOnce(&mut Price, &mut Price),
+) -> Result<Response, ContractError> {
+    let price_feed = load_price_feed(storage, id)?;
+    let mut price = price_feed.get_price_unchecked();
+    let mut ema_price = price_feed.get_ema_price_unchecked();
+    update(&mut price, &mut ema_price);
+    PRICE_FEEDS.save(storage, id.to_hex(), &PriceFeed::new(id, price, ema_price))?;
+    Ok(Response::default())
+}
 
-    Ok(price_feed_response)
+fn update_fee(data: &[Binary]) -> Coin {
+    Coin {
+        denom: String::from(UPDATE_FEE_DENOM),
+        amount: Uint128::new(UPDATE_FEE_PER_UPDATE * data.len() as u128),
+    }
 }
 
-fn update_mock_price(deps: DepsMut, price: i64) -> Result<Response, ContractError> {
-    PRICE.save(deps.storage, &price)?;
+fn update_price_feeds(
+    deps: DepsMut,
+    info: MessageInfo,
+    data: Vec<Binary>,
+) -> Result<Response, ContractError> {
+    let fee = update_fee(&data);
+    let paid = info
+        .funds
+        .iter()
+        .any(|coin| coin.denom == fee.denom && coin.amount >= fee.amount);
+    if !paid {
+        return Err(ContractError::InsufficientFee {});
+    }
+    for update in data {
+        let update: PriceFeed = from_json(&update)?;
+        let stored = PRICE_FEEDS.may_load(deps.storage, update.id.to_hex())?;
+        let is_newer = stored.is_none_or(|stored| {
+            update.get_price_unchecked().publish_time > stored.get_price_unchecked().publish_time
+        });
+        if is_newer {
+            PRICE_FEEDS.save(deps.storage, update.id.to_hex(), &update)?;
+        }
+    }
     Ok(Response::default())
 }
@@ -5,4 +5,10 @@ use thiserror::Error;
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
+
+    #[error("Insufficient update fee")]
+    InsufficientFee {},
+
+    #[error("Price feed {id} not found")]
+    PriceFeedNotFound { id: String },
 }
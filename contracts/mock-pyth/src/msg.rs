@@ -1,6 +1,45 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Binary;
+use pyth_sdk_cw::{PriceFeed, PriceIdentifier};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Feeds served from instantiation, others can be added with SetPriceFeed
+    #[serde(default)]
+    pub price_feeds: Vec<PriceFeed>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    UpdateMockPrice { price: i64 },
+    /// Adds a feed or replaces it
+    SetPriceFeed {
+        price_feed: PriceFeed,
+    },
+    UpdateMockPrice {
+        id: PriceIdentifier,
+        price: i64,
+    },
+    UpdateMockConf {
+        id: PriceIdentifier,
+        conf: u64,
+    },
+    /// Sets the exponent of both the price and the EMA price
+    UpdateMockExpo {
+        id: PriceIdentifier,
+        expo: i32,
+    },
+    UpdateMockEmaPrice {
+        id: PriceIdentifier,
+        price: i64,
+    },
+    /// Sets the publish time of both the price and the EMA price
+    UpdateMockPublishTime {
+        id: PriceIdentifier,
+        publish_time: i64,
+    },
+    /// Same message as Pyth, each update is a json encoded PriceFeed. Like Pyth, updates older
+    /// than the stored feed are ignored
+    UpdatePriceFeeds {
+        data: Vec<Binary>,
+    },
 }
@@ -1,3 +1,5 @@
-use cw_storage_plus::Item;
+use cw_storage_plus::Map;
+use pyth_sdk_cw::PriceFeed;
 
-pub const PRICE: Item<i64> = Item::new("price");
+/// Price feeds served by the mock, keyed by the hex encoded price feed id
+pub const PRICE_FEEDS: Map<String, PriceFeed> = Map::new("price_feeds");
@@ -1,94 +1,366 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use {
-    crate::msg::{ExecuteMsg, FetchPriceResponse, InstantiateMsg, MigrateMsg, QueryMsg},
+    crate::{
+        error::ContractError,
+        msg::{
+            AssetPriceResponse, ConfigResponse, ExecuteMsg, FeedResponse, FetchPriceResponse,
+            InstantiateMsg, MigrateMsg, PriceSourceResponse, PriceSourcesResponse, QueryMsg,
+            UsdPriceResponse,
+        },
+        state::{
+            AssetSources, CachedPrice, FeedSettings, PriceSource, RelayedPrice,
+            DEFAULT_MAX_CACHE_AGE, FEEDS, FEED_SETTINGS, OWNER, PRICE_CACHE, PRICE_SOURCES,
+            PYTH_CONTRACT, RELAYED_PRICES,
+        },
+    },
     cosmwasm_std::{
-        to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+        to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response,
+        StdError, StdResult, Uint128,
     },
-    pyth_sdk_cw::{get_valid_time_period, query_price_feed, PriceFeedResponse, PriceIdentifier},
-    std::time::Duration,
+    pyth_sdk_cw::{
+        get_valid_time_period, query_price_feed, Price, PriceFeedResponse, PriceIdentifier,
+    },
+    std::{collections::HashMap, time::Duration},
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    if let Some(owner) = msg.owner {
+        OWNER.save(deps.storage, &deps.api.addr_validate(&owner)?)?;
+    }
+    if let Some(pyth_contract_addr) = msg.pyth_contract_addr {
+        PYTH_CONTRACT.save(deps.storage, &deps.api.addr_validate(&pyth_contract_addr)?)?;
+    }
     Ok(Response::new().add_attribute("method", "migrate"))
 }
 
 /// The instantiate function is invoked when the contract is first deployed.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    OWNER.save(deps.storage, &owner)?;
+    let pyth_contract_addr = deps.api.addr_validate(&msg.pyth_contract_addr)?;
+    PYTH_CONTRACT.save(deps.storage, &pyth_contract_addr)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", owner)
+        .add_attribute("pyth_contract_addr", pyth_contract_addr))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // prices are pushed by relayers and cached by anyone, every other message is owner only
+    if !matches!(
+        msg,
+        ExecuteMsg::PushPrice { .. } | ExecuteMsg::UpdatePriceCache { .. }
+    ) {
+        assert_owner(&deps, &info)?;
+    }
+    match msg {
+        ExecuteMsg::SetFeedSettings {
+            price_feed_id,
+            settings,
+        } => {
+            validate_feed_settings(&settings)?;
+            FEED_SETTINGS.save(deps.storage, price_feed_id.to_hex(), &settings)?;
+            Ok(Response::new()
+                .add_attribute("method", "set_feed_settings")
+                .add_attribute("price_feed_id", price_feed_id.to_hex()))
+        }
+        ExecuteMsg::RemoveFeedSettings { price_feed_id } => {
+            FEED_SETTINGS.remove(deps.storage, price_feed_id.to_hex());
+            Ok(Response::new()
+                .add_attribute("method", "remove_feed_settings")
+                .add_attribute("price_feed_id", price_feed_id.to_hex()))
+        }
+        ExecuteMsg::UpdateOwner { owner } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            OWNER.save(deps.storage, &owner)?;
+            Ok(Response::new()
+                .add_attribute("method", "update_owner")
+                .add_attribute("owner", owner))
+        }
+        ExecuteMsg::UpdatePythContract { pyth_contract_addr } => {
+            let pyth_contract_addr = deps.api.addr_validate(&pyth_contract_addr)?;
+            PYTH_CONTRACT.save(deps.storage, &pyth_contract_addr)?;
+            Ok(Response::new()
+                .add_attribute("method", "update_pyth_contract")
+                .add_attribute("pyth_contract_addr", pyth_contract_addr))
+        }
+        ExecuteMsg::RegisterFeed {
+            asset,
+            price_feed_id,
+        } => {
+            FEEDS.save(deps.storage, asset.clone(), &price_feed_id)?;
+            Ok(Response::new()
+                .add_attribute("method", "register_feed")
+                .add_attribute("asset", asset)
+                .add_attribute("price_feed_id", price_feed_id.to_hex()))
+        }
+        ExecuteMsg::RemoveFeed { asset } => {
+            if !FEEDS.has(deps.storage, asset.clone()) {
+                return Err(ContractError::FeedNotRegistered { asset });
+            }
+            FEEDS.remove(deps.storage, asset.clone());
+            Ok(Response::new()
+                .add_attribute("method", "remove_feed")
+                .add_attribute("asset", asset))
+        }
+        ExecuteMsg::SetPriceSources {
+            asset,
+            sources,
+            max_deviation,
+        } => {
+            let asset_sources = AssetSources {
+                sources,
+                max_deviation,
+            };
+            validate_price_sources(&deps, &asset_sources)?;
+            PRICE_SOURCES.save(deps.storage, asset.clone(), &asset_sources)?;
+            Ok(Response::new()
+                .add_attribute("method", "set_price_sources")
+                .add_attribute("asset", asset))
+        }
+        ExecuteMsg::RemovePriceSources { asset } => {
+            PRICE_SOURCES.remove(deps.storage, asset.clone());
+            Ok(Response::new()
+                .add_attribute("method", "remove_price_sources")
+                .add_attribute("asset", asset))
+        }
+        ExecuteMsg::PushPrice { asset, price } => push_price(deps, env, info, asset, price),
+        ExecuteMsg::UpdatePriceCache { assets } => update_price_cache(deps, env, assets),
+    }
+}
+
+fn push_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+    price: Decimal,
+) -> Result<Response, ContractError> {
+    let asset_sources = load_price_sources(deps.as_ref(), &asset)?;
+    let is_relayer = asset_sources.sources.iter().any(
+        |source| matches!(source, PriceSource::Relayer { relayer, .. } if *relayer == info.sender),
+    );
+    if !is_relayer {
+        return Err(ContractError::Unauthorized {});
+    }
+    if price.is_zero() {
+        return Err(ContractError::NonPositiveRelayedPrice {});
+    }
+    RELAYED_PRICES.save(
+        deps.storage,
+        (asset.clone(), info.sender.clone()),
+        &RelayedPrice {
+            price,
+            publish_time: env.block.time.seconds(),
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("method", "push_price")
+        .add_attribute("asset", asset)
+        .add_attribute("relayer", info.sender)
+        .add_attribute("price", price.to_string()))
+}
+
+fn assert_owner(deps: &DepsMut, info: &MessageInfo) -> Result<(), ContractError> {
+    match OWNER.may_load(deps.storage)? {
+        Some(owner) if owner == info.sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+fn validate_feed_settings(settings: &FeedSettings) -> Result<(), ContractError> {
+    let inverted_bounds = matches!(
+        (settings.min_price, settings.max_price),
+        (Some(min_price), Some(max_price)) if min_price > max_price
+    );
+    if settings.max_age == 0
+        || settings.max_cache_age == 0
+        || settings.max_conf_ratio == Some(Decimal::zero())
+        || inverted_bounds
+    {
+        return Err(ContractError::InvalidFeedSettings {});
+    }
+    Ok(())
+}
+
+/// Saves the current price of each asset as its last good one, fails if any has no valid price
+fn update_price_cache(
+    deps: DepsMut,
+    env: Env,
+    assets: Vec<String>,
+) -> Result<Response, ContractError> {
+    let prices = query_prices(deps.as_ref(), env.clone(), assets)?;
+    for asset_price in &prices {
+        PRICE_CACHE.save(
+            deps.storage,
+            asset_price.asset.clone(),
+            &CachedPrice {
+                price: asset_price.price.clone(),
+                cached_at: env.block.time.seconds(),
+            },
+        )?;
+    }
+    Ok(Response::new()
+        .add_attribute("method", "update_price_cache")
+        .add_attributes(
+            prices
+                .into_iter()
+                .map(|asset_price| ("asset", asset_price.asset)),
+        ))
+}
+
+/// Requires at least one source, Pyth at most once and each relayer at most once
+fn validate_price_sources(
+    deps: &DepsMut,
+    asset_sources: &AssetSources,
+) -> Result<(), ContractError> {
+    let invalid = || ContractError::InvalidPriceSources {};
+    if asset_sources.sources.is_empty() || asset_sources.max_deviation == Some(Decimal::zero()) {
+        return Err(invalid());
+    }
+    let mut has_pyth = false;
+    let mut relayers: Vec<&Addr> = vec![];
+    for source in &asset_sources.sources {
+        match source {
+            PriceSource::Pyth {} if has_pyth => return Err(invalid()),
+            PriceSource::Pyth {} => has_pyth = true,
+            PriceSource::Relayer { relayer, max_age } => {
+                deps.api.addr_validate(relayer.as_str())?;
+                if *max_age == 0 || relayers.contains(&relayer) {
+                    return Err(invalid());
+                }
+                relayers.push(relayer);
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Query the Pyth contract the current price of the configured price feed.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
-        QueryMsg::FetchPrice {
-            pyth_contract_addr,
-            price_feed_id,
-        } => to_json_binary(&query_fetch_price(
+        QueryMsg::Price { asset } => Ok(to_json_binary(&query_price(deps, env, asset)?)?),
+        QueryMsg::UsdPrice { asset } => Ok(to_json_binary(&query_usd_price(deps, env, asset)?)?),
+        QueryMsg::Prices { assets } => Ok(to_json_binary(&query_prices(deps, env, assets)?)?),
+        QueryMsg::Feed { asset } => Ok(to_json_binary(&load_feed(deps, asset)?)?),
+        QueryMsg::Feeds {} => Ok(to_json_binary(&query_feeds(deps)?)?),
+        QueryMsg::FetchPrice { price_feed_id } => Ok(to_json_binary(&query_fetch_price(
             deps,
             env,
-            pyth_contract_addr,
             price_feed_id,
-        )?),
-        QueryMsg::FetchValidTimePeriod { pyth_contract_addr } => {
-            to_json_binary(&query_fetch_valid_time_period(deps, pyth_contract_addr)?)
+        )?)?),
+        QueryMsg::FetchValidTimePeriod {} => {
+            Ok(to_json_binary(&query_fetch_valid_time_period(deps)?)?)
+        }
+        QueryMsg::Config {} => Ok(to_json_binary(&ConfigResponse {
+            owner: OWNER.load(deps.storage)?,
+            pyth_contract_addr: PYTH_CONTRACT.load(deps.storage)?,
+        })?),
+        QueryMsg::FeedSettings { price_feed_id } => {
+            Ok(to_json_binary(&load_feed_settings(deps, &price_feed_id)?)?)
+        }
+        QueryMsg::LastGoodPrice { asset } => Ok(to_json_binary(&read_last_good_price(
+            deps,
+            &env,
+            &asset,
+            &mut HashMap::new(),
+        )?)?),
+        QueryMsg::LastGoodPrices { assets } => {
+            Ok(to_json_binary(&query_last_good_prices(deps, env, assets)?)?)
+        }
+        QueryMsg::PriceSources { asset } => {
+            Ok(to_json_binary(&query_price_sources(deps, env, asset)?)?)
         }
     }
 }
 
+fn load_feed_settings(deps: Deps, price_feed_id: &PriceIdentifier) -> StdResult<FeedSettings> {
+    Ok(FEED_SETTINGS
+        .may_load(deps.storage, price_feed_id.to_hex())?
+        .unwrap_or_default())
+}
+
+fn load_feed(deps: Deps, asset: String) -> Result<PriceIdentifier, ContractError> {
+    FEEDS
+        .may_load(deps.storage, asset.clone())?
+        .ok_or(ContractError::FeedNotRegistered { asset })
+}
+
+fn query_feeds(deps: Deps) -> StdResult<Vec<FeedResponse>> {
+    FEEDS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (asset, price_feed_id) = item?;
+            Ok(FeedResponse {
+                asset,
+                price_feed_id,
+            })
+        })
+        .collect()
+}
+
+/// Prices of an asset, read from the registered feed of the stored Pyth contract
+fn query_price(deps: Deps, env: Env, asset: String) -> Result<FetchPriceResponse, ContractError> {
+    let price_feed_id = load_feed(deps, asset)?;
+    query_fetch_price(deps, env, price_feed_id)
+}
+
+/// Prices of a feed of the stored Pyth contract
 fn query_fetch_price(
     deps: Deps,
     env: Env,
-    pyth_contract_addr: String,
     price_feed_id: PriceIdentifier,
-) -> StdResult<FetchPriceResponse> {
+) -> Result<FetchPriceResponse, ContractError> {
+    let pyth_contract_addr = PYTH_CONTRACT.load(deps.storage)?;
     // query_price_feed is the standard way to read the current price from a Pyth price feed.
     // It takes the address of the Pyth contract (which is fixed for each network) and the id of the
     // price feed. The result is a PriceFeed object with fields for the current price and other
     // useful information. The function will fail if the contract address or price feed id are
     // invalid.
-    let price_feed_response: PriceFeedResponse = query_price_feed(
-        &deps.querier,
-        deps.api.addr_validate(&pyth_contract_addr).unwrap(),
-        price_feed_id,
-    )?;
+    let price_feed_response: PriceFeedResponse =
+        query_price_feed(&deps.querier, pyth_contract_addr, price_feed_id)?;
     let price_feed = price_feed_response.price_feed;
+    let settings = load_feed_settings(deps, &price_feed_id)?;
+    let stale_price = || ContractError::StalePrice {
+        price_feed_id: price_feed_id.to_hex(),
+        max_age: settings.max_age,
+    };
 
     // Get the current price and confidence interval from the price feed.
     // This function returns None if the price is not currently available.
     // This condition can happen for various reasons. For example, some products only trade at
     // specific times, or network outages may prevent the price feed from updating.
-    //
-    // The example code below throws an error if the price is not available. It is recommended that
-    // you handle this scenario more carefully. Consult the [consumer best practices](https://docs.pyth.network/documentation/pythnet-price-feeds/best-practices)
-    // for recommendations.
+    // See the [consumer best practices](https://docs.pyth.network/documentation/pythnet-price-feeds/best-practices)
+    // for the checks applied below.
     let current_price = price_feed
-        .get_price_no_older_than(env.block.time.seconds() as i64, 60)
-        .ok_or_else(|| StdError::not_found("Current price is not available"))?;
+        .get_price_no_older_than(env.block.time.seconds() as i64, settings.max_age)
+        .ok_or_else(stale_price)?;
+    validate_price(&price_feed_id, &current_price, &settings)?;
 
     // Get an exponentially-weighted moving average price and confidence interval.
     // The same notes about availability apply to this price.
     let ema_price = price_feed
-        .get_ema_price_no_older_than(env.block.time.seconds() as i64, 60)
-        .ok_or_else(|| StdError::not_found("EMA price is not available"))?;
+        .get_ema_price_no_older_than(env.block.time.seconds() as i64, settings.max_age)
+        .ok_or_else(stale_price)?;
+    validate_price(&price_feed_id, &ema_price, &settings)?;
 
     Ok(FetchPriceResponse {
         current_price,
@@ -96,11 +368,296 @@ fn query_fetch_price(
     })
 }
 
-fn query_fetch_valid_time_period(deps: Deps, pyth_contract_addr: String) -> StdResult<Duration> {
-    let duration = get_valid_time_period(
-        &deps.querier,
-        deps.api.addr_validate(&pyth_contract_addr).unwrap(),
-    )?;
+/// Rejects non-positive, low-confidence or implausible prices
+fn validate_price(
+    price_feed_id: &PriceIdentifier,
+    price: &Price,
+    settings: &FeedSettings,
+) -> Result<(), ContractError> {
+    let usd_price = price_to_decimal(price)?;
+    if let Some(max_conf_ratio) = settings.max_conf_ratio {
+        // conf shares the exponent of price
+        let conf_ratio = Decimal::from_ratio(price.conf, price.price as u64);
+        if conf_ratio > max_conf_ratio {
+            return Err(ContractError::LowConfidencePrice {
+                price_feed_id: price_feed_id.to_hex(),
+                conf_ratio,
+                max_conf_ratio,
+            });
+        }
+    }
+    if settings.min_price.is_some_and(|min| usd_price < min)
+        || settings.max_price.is_some_and(|max| usd_price > max)
+    {
+        return Err(ContractError::ImplausiblePrice {
+            price_feed_id: price_feed_id.to_hex(),
+            price: usd_price,
+        });
+    }
+    Ok(())
+}
+
+/// Converts a positive Pyth price to a Decimal, handling positive and negative exponents
+pub fn price_to_decimal(price: &Price) -> Result<Decimal, ContractError> {
+    if price.price <= 0 {
+        return Err(ContractError::NonPositivePrice { price: price.price });
+    }
+    let decimal_price = scale_by_expo(price.price as u64, price.expo)?;
+    // digits beyond 18 decimal places are truncated, a price rounded down to zero is rejected
+    if decimal_price.is_zero() {
+        return Err(ContractError::InvalidPriceExponent { expo: price.expo });
+    }
+    Ok(decimal_price)
+}
+
+/// Converts a Pyth integer value to a Decimal: value * 10^expo
+fn scale_by_expo(value: u64, expo: i32) -> Result<Decimal, ContractError> {
+    let invalid_expo = || ContractError::InvalidPriceExponent { expo };
+    let value = Uint128::from(value);
+    if expo <= 0 {
+        Decimal::from_atomics(value, expo.unsigned_abs()).map_err(|_| invalid_expo())
+    } else {
+        let scale = Uint128::new(10)
+            .checked_pow(expo.unsigned_abs())
+            .map_err(|_| invalid_expo())?;
+        let value = value.checked_mul(scale).map_err(|_| invalid_expo())?;
+        Decimal::from_atomics(value, 0).map_err(|_| invalid_expo())
+    }
+}
+
+/// Current and EMA usd prices of an asset with the confidence interval, normalized to Decimals
+fn query_usd_price(deps: Deps, env: Env, asset: String) -> Result<UsdPriceResponse, ContractError> {
+    read_price(deps, &env, &asset, &mut HashMap::new())
+}
+
+/// Current usd prices of several assets. Pyth has no multi-feed query, so each distinct feed is
+/// read once and shared by the assets registered to it.
+fn query_prices(
+    deps: Deps,
+    env: Env,
+    assets: Vec<String>,
+) -> Result<Vec<AssetPriceResponse>, ContractError> {
+    let mut prices_by_feed: HashMap<String, UsdPriceResponse> = HashMap::new();
+    assets
+        .into_iter()
+        .map(|asset| {
+            let price = read_price(deps, &env, &asset, &mut prices_by_feed)?;
+            Ok(AssetPriceResponse { asset, price })
+        })
+        .collect()
+}
+
+fn query_last_good_prices(
+    deps: Deps,
+    env: Env,
+    assets: Vec<String>,
+) -> Result<Vec<AssetPriceResponse>, ContractError> {
+    let mut prices_by_feed: HashMap<String, UsdPriceResponse> = HashMap::new();
+    assets
+        .into_iter()
+        .map(|asset| {
+            let price = read_last_good_price(deps, &env, &asset, &mut prices_by_feed)?;
+            Ok(AssetPriceResponse { asset, price })
+        })
+        .collect()
+}
+
+/// Current usd price of an asset read from its sources
+fn read_price(
+    deps: Deps,
+    env: &Env,
+    asset: &str,
+    pyth_prices: &mut HashMap<String, UsdPriceResponse>,
+) -> Result<UsdPriceResponse, ContractError> {
+    let asset_sources = load_price_sources(deps, asset)?;
+    let readings = read_sources(deps, env, asset, &asset_sources, pyth_prices)?;
+    Ok(select_price(asset, &asset_sources, readings)?.1)
+}
+
+/// Current usd price of an asset, its cached last good price when every source only has a stale
+/// or missing price. Prices rejected by a sanity check or a disagreement are never hidden by the
+/// cache, nor are cached prices older than the max cache age of the feed served.
+fn read_last_good_price(
+    deps: Deps,
+    env: &Env,
+    asset: &str,
+    pyth_prices: &mut HashMap<String, UsdPriceResponse>,
+) -> Result<UsdPriceResponse, ContractError> {
+    let asset_sources = load_price_sources(deps, asset)?;
+    let readings = read_sources(deps, env, asset, &asset_sources, pyth_prices)?;
+    let unavailable = readings
+        .iter()
+        .all(|reading| reading.as_ref().is_err_and(is_price_unavailable));
+    let err = match select_price(asset, &asset_sources, readings) {
+        Ok((_, price)) => return Ok(price),
+        Err(err) if unavailable => err,
+        Err(err) => return Err(err),
+    };
+    let max_cache_age = match FEEDS.may_load(deps.storage, asset.to_string())? {
+        Some(price_feed_id) => load_feed_settings(deps, &price_feed_id)?.max_cache_age,
+        None => DEFAULT_MAX_CACHE_AGE,
+    };
+    match PRICE_CACHE.may_load(deps.storage, asset.to_string())? {
+        Some(cached) if cached.cached_at + max_cache_age >= env.block.time.seconds() => {
+            Ok(cached.price)
+        }
+        _ => Err(err),
+    }
+}
+
+/// Stale or missing prices, as opposed to prices rejected by a sanity check
+fn is_price_unavailable(err: &ContractError) -> bool {
+    matches!(
+        err,
+        ContractError::StalePrice { .. }
+            | ContractError::StaleRelayedPrice { .. }
+            | ContractError::FeedNotRegistered { .. }
+            | ContractError::Std(StdError::NotFound { .. })
+    )
+}
+
+fn query_price_sources(
+    deps: Deps,
+    env: Env,
+    asset: String,
+) -> Result<PriceSourcesResponse, ContractError> {
+    let asset_sources = load_price_sources(deps, &asset)?;
+    let readings = read_sources(deps, &env, &asset, &asset_sources, &mut HashMap::new())?;
+    let sources = asset_sources
+        .sources
+        .iter()
+        .zip(&readings)
+        .map(|(source, reading)| PriceSourceResponse {
+            source: source.clone(),
+            price: reading.as_ref().ok().map(|price| price.price),
+            error: reading.as_ref().err().map(ToString::to_string),
+        })
+        .collect();
+    let used_source = select_price(&asset, &asset_sources, readings)
+        .ok()
+        .map(|(index, _)| asset_sources.sources[index].clone());
+    Ok(PriceSourcesResponse {
+        asset,
+        sources,
+        max_deviation: asset_sources.max_deviation,
+        used_source,
+    })
+}
+
+fn load_price_sources(deps: Deps, asset: &str) -> StdResult<AssetSources> {
+    Ok(PRICE_SOURCES
+        .may_load(deps.storage, asset.to_string())?
+        .unwrap_or_default())
+}
+
+/// Price of an asset read from each of its sources, by priority. Pyth prices already read are
+/// taken from pyth_prices, keyed by hex feed id.
+fn read_sources(
+    deps: Deps,
+    env: &Env,
+    asset: &str,
+    asset_sources: &AssetSources,
+    pyth_prices: &mut HashMap<String, UsdPriceResponse>,
+) -> StdResult<Vec<Result<UsdPriceResponse, ContractError>>> {
+    Ok(asset_sources
+        .sources
+        .iter()
+        .map(|source| match source {
+            PriceSource::Pyth {} => {
+                let price_feed_id = load_feed(deps, asset.to_string())?;
+                if let Some(price) = pyth_prices.get(&price_feed_id.to_hex()) {
+                    return Ok(price.clone());
+                }
+                let price = to_usd_price(&query_fetch_price(deps, env.clone(), price_feed_id)?)?;
+                pyth_prices.insert(price_feed_id.to_hex(), price.clone());
+                Ok(price)
+            }
+            PriceSource::Relayer { relayer, max_age } => {
+                read_relayed_price(deps, env, asset, relayer, *max_age)
+            }
+        })
+        .collect())
+}
+
+/// Relayers push no EMA nor confidence interval, their price stands for the EMA
+fn read_relayed_price(
+    deps: Deps,
+    env: &Env,
+    asset: &str,
+    relayer: &Addr,
+    max_age: u64,
+) -> Result<UsdPriceResponse, ContractError> {
+    let relayed_price =
+        RELAYED_PRICES.may_load(deps.storage, (asset.to_string(), relayer.clone()))?;
+    match relayed_price {
+        Some(relayed_price) if relayed_price.publish_time + max_age >= env.block.time.seconds() => {
+            Ok(UsdPriceResponse {
+                price: relayed_price.price,
+                conf: Decimal::zero(),
+                publish_time: relayed_price.publish_time as i64,
+                ema_price: relayed_price.price,
+            })
+        }
+        _ => Err(ContractError::StaleRelayedPrice {
+            asset: asset.to_string(),
+            relayer: relayer.to_string(),
+            max_age,
+        }),
+    }
+}
+
+/// Picks the first valid price with the index of its source. The price is rejected when any other
+/// valid source deviates from it by more than the max deviation, and the error of the first source
+/// is returned when none is valid.
+fn select_price(
+    asset: &str,
+    asset_sources: &AssetSources,
+    readings: Vec<Result<UsdPriceResponse, ContractError>>,
+) -> Result<(usize, UsdPriceResponse), ContractError> {
+    let mut selected: Option<(usize, UsdPriceResponse)> = None;
+    let mut other_prices = vec![];
+    let mut first_error = None;
+    for (index, reading) in readings.into_iter().enumerate() {
+        match reading {
+            Ok(price) if selected.is_none() => selected = Some((index, price)),
+            Ok(price) => other_prices.push(price.price),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    let (index, price) = match selected {
+        Some(selected) => selected,
+        None => return Err(first_error.unwrap_or(ContractError::InvalidPriceSources {})),
+    };
+    if let Some(max_deviation) = asset_sources.max_deviation {
+        for other_price in other_prices {
+            let deviation = price.price.abs_diff(other_price) / price.price;
+            if deviation > max_deviation {
+                return Err(ContractError::PriceSourcesDisagree {
+                    asset: asset.to_string(),
+                    deviation,
+                    max_deviation,
+                });
+            }
+        }
+    }
+    Ok((index, price))
+}
+
+fn to_usd_price(prices: &FetchPriceResponse) -> Result<UsdPriceResponse, ContractError> {
+    let price = &prices.current_price;
+    Ok(UsdPriceResponse {
+        price: price_to_decimal(price)?,
+        conf: scale_by_expo(price.conf, price.expo)?,
+        publish_time: price.publish_time,
+        ema_price: price_to_decimal(&prices.ema_price)?,
+    })
+}
+
+fn query_fetch_valid_time_period(deps: Deps) -> StdResult<Duration> {
+    let pyth_contract_addr = PYTH_CONTRACT.load(deps.storage)?;
+    let duration = get_valid_time_period(&deps.querier, pyth_contract_addr)?;
     Ok(duration)
 }
 
@@ -110,7 +667,7 @@ mod test {
         super::*,
         cosmwasm_std::{
             from_json,
-            testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+            testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
             Coin, OwnedDeps, QuerierResult, SystemError, SystemResult, Timestamp, WasmQuery,
         },
         pyth_sdk_cw::{testing::MockPyth, Price, PriceFeed, PriceIdentifier, UnixTimestamp},
@@ -122,6 +679,7 @@ mod test {
     const PYTH_CONTRACT_ADDR: &str = "pyth_contract_addr";
     // For real deployments, see list of price feed ids here https://pyth.network/developers/price-feed-ids
     const PRICE_FEED_ID: &str = "63f341689d98a12ef60a5cff1d7f85c70a9e17bf1575f0e7c0b2512d48b1c8b3";
+    const OWNER: &str = "owner";
 
     fn setup_test(
         mock_pyth: &MockPyth,
@@ -134,6 +692,13 @@ mod test {
             .querier
             .update_wasm(move |x| handle_wasm_query(&mock_pyth_copy, x));
 
+        PYTH_CONTRACT
+            .save(
+                dependencies.as_mut().storage,
+                &Addr::unchecked(PYTH_CONTRACT_ADDR),
+            )
+            .unwrap();
+
         let mut env = mock_env();
         env.block.time = Timestamp::from_seconds(u64::try_from(block_timestamp).unwrap());
 
@@ -193,11 +758,10 @@ mod test {
         let (deps, env) = setup_test(&mock_pyth, current_unix_time);
 
         let msg = QueryMsg::FetchPrice {
-            pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
             price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
         };
         let result = query(deps.as_ref(), env, msg)
-            .and_then(|binary| from_json::<FetchPriceResponse>(&binary));
+            .map(|binary| from_json::<FetchPriceResponse>(&binary).unwrap());
 
         // println!("PRICE RESULT: {:?}", result);
 
@@ -212,12 +776,786 @@ mod test {
         let mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
         let (deps, env) = setup_test(&mock_pyth, current_unix_time);
 
-        let msg = QueryMsg::FetchValidTimePeriod {
-            pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
-        };
+        let msg = QueryMsg::FetchValidTimePeriod {};
         let result =
-            query(deps.as_ref(), env, msg).and_then(|binary| from_json::<Duration>(&binary));
+            query(deps.as_ref(), env, msg).map(|binary| from_json::<Duration>(&binary).unwrap());
 
         assert_eq!(result.map(|r| r.as_secs()), Ok(60));
     }
+
+    fn mock_price_feed(price: i64, conf: u64, publish_time: UnixTimestamp) -> PriceFeed {
+        let price = Price {
+            price,
+            conf,
+            expo: -1,
+            publish_time,
+        };
+        PriceFeed::new(
+            PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            price,
+            price,
+        )
+    }
+
+    fn fetch_price(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: Env,
+    ) -> Result<FetchPriceResponse, ContractError> {
+        let msg = QueryMsg::FetchPrice {
+            price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+        };
+        query(deps.as_ref(), env, msg).map(|binary| from_json(&binary).unwrap())
+    }
+
+    fn set_feed_settings(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        sender: &str,
+        settings: FeedSettings,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::SetFeedSettings {
+            price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            settings,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn test_feed_settings_by_owner_only() {
+        let mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        let (mut deps, env) = setup_test(&mock_pyth, 10_000_000);
+        instantiate(
+            deps.as_mut(),
+            env,
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+
+        let settings = FeedSettings {
+            max_age: 120,
+            ..FeedSettings::default()
+        };
+        assert_eq!(
+            set_feed_settings(&mut deps, "not_owner", settings.clone()),
+            Err(ContractError::Unauthorized {})
+        );
+        assert_eq!(
+            set_feed_settings(
+                &mut deps,
+                OWNER,
+                FeedSettings {
+                    min_price: Some(Decimal::percent(200)),
+                    max_price: Some(Decimal::percent(100)),
+                    ..FeedSettings::default()
+                }
+            ),
+            Err(ContractError::InvalidFeedSettings {})
+        );
+        set_feed_settings(&mut deps, OWNER, settings.clone()).unwrap();
+
+        let msg = QueryMsg::FeedSettings {
+            price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+        };
+        let result = query(deps.as_ref(), mock_env(), msg)
+            .map(|binary| from_json::<FeedSettings>(&binary).unwrap());
+        assert_eq!(result, Ok(settings));
+    }
+
+    #[test]
+    fn test_price_of_registered_asset() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+
+        let (mut deps, env) = setup_test(&mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+        let price_of = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, asset: &str| {
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Price {
+                    asset: String::from(asset),
+                },
+            )
+            .map(|binary| from_json::<FetchPriceResponse>(&binary).unwrap())
+        };
+
+        assert_eq!(
+            price_of(&deps, "untrn"),
+            Err(ContractError::FeedNotRegistered {
+                asset: String::from("untrn")
+            })
+        );
+
+        let register_msg = ExecuteMsg::RegisterFeed {
+            asset: String::from("untrn"),
+            price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+        };
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("not_owner", &[]),
+                register_msg.clone()
+            ),
+            Err(ContractError::Unauthorized {})
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            register_msg,
+        )
+        .unwrap();
+
+        assert_eq!(
+            price_of(&deps, "untrn").map(|r| r.current_price.price),
+            Ok(100)
+        );
+        let feeds = query(deps.as_ref(), mock_env(), QueryMsg::Feeds {})
+            .map(|binary| from_json::<Vec<FeedResponse>>(&binary).unwrap());
+        assert_eq!(
+            feeds,
+            Ok(vec![FeedResponse {
+                asset: String::from("untrn"),
+                price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            }])
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::RemoveFeed {
+                asset: String::from("untrn"),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            price_of(&deps, "untrn"),
+            Err(ContractError::FeedNotRegistered {
+                asset: String::from("untrn")
+            })
+        );
+    }
+
+    #[test]
+    fn test_usd_price() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        let price = Price {
+            price: 68,
+            conf: 1,
+            expo: 2,
+            publish_time: current_unix_time,
+        };
+        mock_pyth.add_feed(PriceFeed::new(
+            PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            price,
+            price,
+        ));
+
+        let (mut deps, env) = setup_test(&mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::RegisterFeed {
+                asset: String::from("untrn"),
+                price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            },
+        )
+        .unwrap();
+
+        let msg = QueryMsg::UsdPrice {
+            asset: String::from("untrn"),
+        };
+        let result = query(deps.as_ref(), env, msg)
+            .map(|binary| from_json::<UsdPriceResponse>(&binary).unwrap());
+        assert_eq!(
+            result,
+            Ok(UsdPriceResponse {
+                price: Decimal::from_atomics(6800u128, 0).unwrap(),
+                conf: Decimal::from_atomics(100u128, 0).unwrap(),
+                publish_time: current_unix_time,
+                ema_price: Decimal::from_atomics(6800u128, 0).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_prices_of_several_assets() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 10, current_unix_time));
+
+        let (mut deps, env) = setup_test(&mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+        for asset in ["untrn", "uatom"] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(OWNER, &[]),
+                ExecuteMsg::RegisterFeed {
+                    asset: String::from(asset),
+                    price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+                },
+            )
+            .unwrap();
+        }
+        let prices_of = |assets: &[&str]| {
+            let msg = QueryMsg::Prices {
+                assets: assets.iter().map(|asset| asset.to_string()).collect(),
+            };
+            query(deps.as_ref(), env.clone(), msg)
+                .map(|binary| from_json::<Vec<AssetPriceResponse>>(&binary).unwrap())
+        };
+
+        let usd_price = UsdPriceResponse {
+            price: Decimal::percent(1000),
+            conf: Decimal::one(),
+            publish_time: current_unix_time,
+            ema_price: Decimal::percent(1000),
+        };
+        assert_eq!(
+            prices_of(&["uatom", "untrn"]),
+            Ok(vec![
+                AssetPriceResponse {
+                    asset: String::from("uatom"),
+                    price: usd_price.clone(),
+                },
+                AssetPriceResponse {
+                    asset: String::from("untrn"),
+                    price: usd_price,
+                },
+            ])
+        );
+        assert_eq!(
+            prices_of(&["untrn", "uosmo"]),
+            Err(ContractError::FeedNotRegistered {
+                asset: String::from("uosmo")
+            })
+        );
+    }
+
+    #[test]
+    fn test_stale_price() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+
+        let (mut deps, mut env) = setup_test(&mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(61);
+
+        assert_eq!(
+            fetch_price(&deps, env.clone()),
+            Err(ContractError::StalePrice {
+                price_feed_id: String::from(PRICE_FEED_ID),
+                max_age: 60
+            })
+        );
+
+        set_feed_settings(
+            &mut deps,
+            OWNER,
+            FeedSettings {
+                max_age: 120,
+                ..FeedSettings::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            fetch_price(&deps, env).map(|r| r.current_price.price),
+            Ok(100)
+        );
+    }
+
+    #[test]
+    fn test_low_confidence_and_implausible_price() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        // 10 usd +/- 1 usd
+        mock_pyth.add_feed(mock_price_feed(100, 10, current_unix_time));
+
+        let (mut deps, env) = setup_test(&mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+
+        set_feed_settings(
+            &mut deps,
+            OWNER,
+            FeedSettings {
+                max_conf_ratio: Some(Decimal::percent(5)),
+                ..FeedSettings::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            fetch_price(&deps, env.clone()),
+            Err(ContractError::LowConfidencePrice {
+                price_feed_id: String::from(PRICE_FEED_ID),
+                conf_ratio: Decimal::percent(10),
+                max_conf_ratio: Decimal::percent(5),
+            })
+        );
+
+        set_feed_settings(
+            &mut deps,
+            OWNER,
+            FeedSettings {
+                max_conf_ratio: Some(Decimal::percent(10)),
+                min_price: Some(Decimal::percent(2000)),
+                ..FeedSettings::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            fetch_price(&deps, env.clone()),
+            Err(ContractError::ImplausiblePrice {
+                price_feed_id: String::from(PRICE_FEED_ID),
+                price: Decimal::percent(1000),
+            })
+        );
+
+        set_feed_settings(
+            &mut deps,
+            OWNER,
+            FeedSettings {
+                max_conf_ratio: Some(Decimal::percent(10)),
+                min_price: Some(Decimal::percent(500)),
+                max_price: Some(Decimal::percent(2000)),
+                ..FeedSettings::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            fetch_price(&deps, env).map(|r| r.current_price.price),
+            Ok(100)
+        );
+    }
+
+    #[test]
+    fn test_non_positive_price() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(-100, 10, current_unix_time));
+
+        let (deps, env) = setup_test(&mock_pyth, current_unix_time);
+
+        assert_eq!(
+            fetch_price(&deps, env),
+            Err(ContractError::NonPositivePrice { price: -100 })
+        );
+    }
+
+    const RELAYER: &str = "relayer";
+
+    /// Instantiates the oracle, registers the feed of untrn and sets Pyth then RELAYER as its
+    /// sources, 10% apart at most
+    fn setup_price_sources(
+        mock_pyth: &MockPyth,
+        current_unix_time: UnixTimestamp,
+    ) -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env) {
+        let (mut deps, env) = setup_test(mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+        for msg in [
+            ExecuteMsg::RegisterFeed {
+                asset: String::from("untrn"),
+                price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            },
+            ExecuteMsg::SetPriceSources {
+                asset: String::from("untrn"),
+                sources: relayed_sources(),
+                max_deviation: Some(Decimal::percent(10)),
+            },
+        ] {
+            execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        }
+        (deps, env)
+    }
+
+    fn relayed_sources() -> Vec<PriceSource> {
+        vec![
+            PriceSource::Pyth {},
+            PriceSource::Relayer {
+                relayer: Addr::unchecked(RELAYER),
+                max_age: 60,
+            },
+        ]
+    }
+
+    fn push_price(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: Env,
+        sender: &str,
+        price: Decimal,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::PushPrice {
+            asset: String::from("untrn"),
+            price,
+        };
+        execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+    }
+
+    fn usd_price(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: Env,
+    ) -> Result<UsdPriceResponse, ContractError> {
+        let msg = QueryMsg::UsdPrice {
+            asset: String::from("untrn"),
+        };
+        query(deps.as_ref(), env, msg).map(|binary| from_json(&binary).unwrap())
+    }
+
+    fn price_sources(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: Env,
+    ) -> PriceSourcesResponse {
+        let msg = QueryMsg::PriceSources {
+            asset: String::from("untrn"),
+        };
+        from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_relayed_price_is_used_when_pyth_is_stale() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+        let (mut deps, mut env) = setup_price_sources(&mock_pyth, current_unix_time);
+
+        env.block.time = env.block.time.plus_seconds(61);
+        push_price(&mut deps, env.clone(), RELAYER, Decimal::percent(950)).unwrap();
+
+        assert_eq!(
+            usd_price(&deps, env.clone()),
+            Ok(UsdPriceResponse {
+                price: Decimal::percent(950),
+                conf: Decimal::zero(),
+                publish_time: current_unix_time + 61,
+                ema_price: Decimal::percent(950),
+            })
+        );
+        let stale_pyth_price = ContractError::StalePrice {
+            price_feed_id: String::from(PRICE_FEED_ID),
+            max_age: 60,
+        };
+        let sources = relayed_sources();
+        assert_eq!(
+            price_sources(&deps, env.clone()),
+            PriceSourcesResponse {
+                asset: String::from("untrn"),
+                sources: vec![
+                    PriceSourceResponse {
+                        source: sources[0].clone(),
+                        price: None,
+                        error: Some(stale_pyth_price.to_string()),
+                    },
+                    PriceSourceResponse {
+                        source: sources[1].clone(),
+                        price: Some(Decimal::percent(950)),
+                        error: None,
+                    },
+                ],
+                max_deviation: Some(Decimal::percent(10)),
+                used_source: Some(sources[1].clone()),
+            }
+        );
+
+        // once every source is stale the error of the primary one is returned
+        env.block.time = env.block.time.plus_seconds(61);
+        assert_eq!(usd_price(&deps, env), Err(stale_pyth_price));
+    }
+
+    #[test]
+    fn test_disagreeing_price_sources_are_rejected() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+        let (mut deps, env) = setup_price_sources(&mock_pyth, current_unix_time);
+
+        push_price(&mut deps, env.clone(), RELAYER, Decimal::percent(1200)).unwrap();
+        assert_eq!(
+            usd_price(&deps, env.clone()),
+            Err(ContractError::PriceSourcesDisagree {
+                asset: String::from("untrn"),
+                deviation: Decimal::percent(20),
+                max_deviation: Decimal::percent(10),
+            })
+        );
+        assert_eq!(price_sources(&deps, env.clone()).used_source, None);
+
+        // the primary source is used while the others stay close enough
+        push_price(&mut deps, env.clone(), RELAYER, Decimal::percent(1050)).unwrap();
+        assert_eq!(
+            usd_price(&deps, env.clone()).map(|price| price.price),
+            Ok(Decimal::percent(1000))
+        );
+        assert_eq!(
+            price_sources(&deps, env).used_source,
+            Some(PriceSource::Pyth {})
+        );
+    }
+
+    #[test]
+    fn test_price_sources_by_owner_and_prices_by_relayers_only() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+        let (mut deps, env) = setup_price_sources(&mock_pyth, current_unix_time);
+
+        let mut set_price_sources = |sender: &str, sources, max_deviation| {
+            let msg = ExecuteMsg::SetPriceSources {
+                asset: String::from("untrn"),
+                sources,
+                max_deviation,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+        };
+        assert_eq!(
+            set_price_sources(RELAYER, relayed_sources(), None),
+            Err(ContractError::Unauthorized {})
+        );
+        for (sources, max_deviation) in [
+            (vec![], None),
+            (vec![PriceSource::Pyth {}, PriceSource::Pyth {}], None),
+            (relayed_sources(), Some(Decimal::zero())),
+        ] {
+            assert_eq!(
+                set_price_sources(OWNER, sources, max_deviation),
+                Err(ContractError::InvalidPriceSources {})
+            );
+        }
+
+        assert_eq!(
+            push_price(&mut deps, env.clone(), OWNER, Decimal::percent(1000)),
+            Err(ContractError::Unauthorized {})
+        );
+        assert_eq!(
+            push_price(&mut deps, env, RELAYER, Decimal::zero()),
+            Err(ContractError::NonPositiveRelayedPrice {})
+        );
+    }
+
+    #[test]
+    fn test_last_good_price_is_cached_by_anyone() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+
+        let (mut deps, mut env) = setup_test(&mock_pyth, current_unix_time);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                owner: None,
+                pyth_contract_addr: String::from(PYTH_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::RegisterFeed {
+                asset: String::from("untrn"),
+                price_feed_id: PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(),
+            },
+        )
+        .unwrap();
+        let update_price_cache = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+                                  env: Env| {
+            let msg = ExecuteMsg::UpdatePriceCache {
+                assets: vec![String::from("untrn")],
+            };
+            execute(deps.as_mut(), env, mock_info("anyone", &[]), msg)
+        };
+        let last_good_prices =
+            |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, env: Env, assets: &[&str]| {
+                let msg = QueryMsg::LastGoodPrices {
+                    assets: assets.iter().map(|asset| asset.to_string()).collect(),
+                };
+                query(deps.as_ref(), env, msg)
+                    .map(|binary| from_json::<Vec<AssetPriceResponse>>(&binary).unwrap())
+            };
+        let stale_price = || ContractError::StalePrice {
+            price_feed_id: String::from(PRICE_FEED_ID),
+            max_age: 60,
+        };
+        let cached_price = UsdPriceResponse {
+            price: Decimal::percent(1000),
+            conf: Decimal::percent(10),
+            publish_time: current_unix_time,
+            ema_price: Decimal::percent(1000),
+        };
+
+        update_price_cache(&mut deps, env.clone()).unwrap();
+
+        // once Pyth is stale only the last good price is served
+        env.block.time = env.block.time.plus_seconds(61);
+        assert_eq!(usd_price(&deps, env.clone()), Err(stale_price()));
+        let msg = QueryMsg::LastGoodPrice {
+            asset: String::from("untrn"),
+        };
+        let result = query(deps.as_ref(), env.clone(), msg)
+            .map(|binary| from_json::<UsdPriceResponse>(&binary).unwrap());
+        assert_eq!(result, Ok(cached_price.clone()));
+        assert_eq!(
+            last_good_prices(&deps, env.clone(), &["untrn"]),
+            Ok(vec![AssetPriceResponse {
+                asset: String::from("untrn"),
+                price: cached_price,
+            }])
+        );
+
+        // stale prices are not cached, assets never cached have no last good price
+        assert_eq!(
+            update_price_cache(&mut deps, env.clone()),
+            Err(stale_price())
+        );
+        assert_eq!(
+            last_good_prices(&deps, env.clone(), &["untrn", "uatom"]),
+            Err(ContractError::FeedNotRegistered {
+                asset: String::from("uatom")
+            })
+        );
+
+        // last good prices older than the max cache age of the feed are not served
+        assert_eq!(
+            set_feed_settings(
+                &mut deps,
+                OWNER,
+                FeedSettings {
+                    max_cache_age: 0,
+                    ..FeedSettings::default()
+                }
+            ),
+            Err(ContractError::InvalidFeedSettings {})
+        );
+        set_feed_settings(
+            &mut deps,
+            OWNER,
+            FeedSettings {
+                max_cache_age: 60,
+                ..FeedSettings::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(last_good_prices(&deps, env, &["untrn"]), Err(stale_price()));
+    }
+
+    #[test]
+    fn test_last_good_price_does_not_hide_disagreeing_sources() {
+        let current_unix_time = 10_000_000;
+        let mut mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        mock_pyth.add_feed(mock_price_feed(100, 1, current_unix_time));
+        let (mut deps, env) = setup_price_sources(&mock_pyth, current_unix_time);
+
+        push_price(&mut deps, env.clone(), RELAYER, Decimal::percent(1050)).unwrap();
+        let msg = ExecuteMsg::UpdatePriceCache {
+            assets: vec![String::from("untrn")],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+
+        // the relayer now disagrees with Pyth, the cached price is not served instead
+        push_price(&mut deps, env.clone(), RELAYER, Decimal::percent(1200)).unwrap();
+        let msg = QueryMsg::LastGoodPrice {
+            asset: String::from("untrn"),
+        };
+        let result = query(deps.as_ref(), env, msg)
+            .map(|binary| from_json::<UsdPriceResponse>(&binary).unwrap());
+        assert_eq!(
+            result,
+            Err(ContractError::PriceSourcesDisagree {
+                asset: String::from("untrn"),
+                deviation: Decimal::percent(20),
+                max_deviation: Decimal::percent(10),
+            })
+        );
+    }
+
+    #[test]
+    fn test_price_to_decimal() {
+        let price = |price, expo| Price {
+            price,
+            conf: 0,
+            expo,
+            publish_time: 0,
+        };
+        assert_eq!(
+            price_to_decimal(&price(680_000, -5)),
+            Ok(Decimal::percent(680))
+        );
+        assert_eq!(
+            price_to_decimal(&price(68, 2)),
+            Ok(Decimal::percent(680_000))
+        );
+        assert_eq!(
+            price_to_decimal(&price(0, -5)),
+            Err(ContractError::NonPositivePrice { price: 0 })
+        );
+        assert_eq!(
+            price_to_decimal(&price(1, -19)),
+            Err(ContractError::InvalidPriceExponent { expo: -19 })
+        );
+    }
 }
@@ -0,0 +1,61 @@
+use cosmwasm_std::{Decimal, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Price of feed {price_feed_id} is older than {max_age} seconds")]
+    StalePrice { price_feed_id: String, max_age: u64 },
+
+    #[error("Price is not positive: {price}")]
+    NonPositivePrice { price: i64 },
+
+    #[error(
+        "Confidence of feed {price_feed_id} is too low: conf/price {conf_ratio} > {max_conf_ratio}"
+    )]
+    LowConfidencePrice {
+        price_feed_id: String,
+        conf_ratio: Decimal,
+        max_conf_ratio: Decimal,
+    },
+
+    #[error("Price of feed {price_feed_id} is out of its plausible range: {price}")]
+    ImplausiblePrice {
+        price_feed_id: String,
+        price: Decimal,
+    },
+
+    #[error("Price exponent cannot be represented: {expo}")]
+    InvalidPriceExponent { expo: i32 },
+
+    #[error("No price feed registered for asset {asset}")]
+    FeedNotRegistered { asset: String },
+
+    #[error("Invalid feed settings")]
+    InvalidFeedSettings {},
+
+    #[error("Invalid price sources")]
+    InvalidPriceSources {},
+
+    #[error("Price of {asset} relayed by {relayer} is missing or older than {max_age} seconds")]
+    StaleRelayedPrice {
+        asset: String,
+        relayer: String,
+        max_age: u64,
+    },
+
+    #[error("Relayed price must be positive")]
+    NonPositiveRelayedPrice {},
+
+    #[error("Price sources of {asset} disagree: deviation {deviation} > {max_deviation}")]
+    PriceSourcesDisagree {
+        asset: String,
+        deviation: Decimal,
+        max_deviation: Decimal,
+    },
+}
@@ -1,3 +1,6 @@
 pub mod contract;
-// mod error;
+pub mod error;
 pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -1,28 +1,130 @@
 use std::time::Duration;
 
+use cosmwasm_std::{Addr, Decimal};
 use pyth_sdk_cw::{Price, PriceIdentifier};
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
+use crate::state::{FeedSettings, PriceSource};
+
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Sets the owner of an oracle deployed before it had one
+    pub owner: Option<String>,
+    /// Sets the Pyth contract of an oracle deployed before it stored one
+    pub pyth_contract_addr: Option<String>,
+}
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// Defaults to the instantiator
+    pub owner: Option<String>,
+    /// Pyth contract all prices are read from
+    pub pyth_contract_addr: String,
+}
 
 #[cw_serde]
-pub enum ExecuteMsg {}
+pub enum ExecuteMsg {
+    /// Sets the sanity checks of a price feed, owner only
+    SetFeedSettings {
+        price_feed_id: PriceIdentifier,
+        settings: FeedSettings,
+    },
+    /// Resets the sanity checks of a price feed to the defaults, owner only
+    RemoveFeedSettings { price_feed_id: PriceIdentifier },
+    /// Transfers the ownership of the oracle, owner only
+    UpdateOwner { owner: String },
+    /// Changes the Pyth contract all prices are read from, owner only
+    UpdatePythContract { pyth_contract_addr: String },
+    /// Sets the price feed of an asset (native denom or cw20 address), owner only
+    RegisterFeed {
+        asset: String,
+        price_feed_id: PriceIdentifier,
+    },
+    /// Removes the price feed of an asset, owner only
+    RemoveFeed { asset: String },
+    /// Sets the price sources of an asset by priority, owner only
+    SetPriceSources {
+        asset: String,
+        sources: Vec<PriceSource>,
+        max_deviation: Option<Decimal>,
+    },
+    /// Prices the asset by its Pyth feed only again, owner only
+    RemovePriceSources { asset: String },
+    /// Pushes the usd price of an asset, relayers of the asset only
+    PushPrice { asset: String, price: Decimal },
+    /// Caches the current usd price of each asset as its last good one, anyone can call it
+    UpdatePriceCache { assets: Vec<String> },
+}
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    /// Current and EMA prices of an asset (native denom or cw20 address) from its registered feed
     #[returns(FetchPriceResponse)]
-    FetchPrice {
-        pyth_contract_addr: String,
-        price_feed_id: PriceIdentifier,
-    },
+    Price { asset: String },
+    /// Current usd price of one whole token of an asset read from its sources, consumers need no
+    /// Pyth types
+    #[returns(UsdPriceResponse)]
+    UsdPrice { asset: String },
+    /// Current usd prices of several assets in one query, in the order requested
+    #[returns(Vec<AssetPriceResponse>)]
+    Prices { assets: Vec<String> },
+    #[returns(PriceIdentifier)]
+    Feed { asset: String },
+    #[returns(Vec<FeedResponse>)]
+    Feeds {},
+    /// Reads any feed of the stored Pyth contract, consumers should rely on Price instead
+    #[returns(FetchPriceResponse)]
+    FetchPrice { price_feed_id: PriceIdentifier },
+    /// Valid time period of the stored Pyth contract
     #[returns(Duration)]
-    FetchValidTimePeriod { pyth_contract_addr: String },
+    FetchValidTimePeriod {},
+    #[returns(ConfigResponse)]
+    Config {},
+    #[returns(FeedSettings)]
+    FeedSettings { price_feed_id: PriceIdentifier },
+    /// Current usd price of an asset, or its last good price cached by UpdatePriceCache when its
+    /// sources only have stale or missing prices and the cache is younger than the max cache age
+    /// of the feed. publish_time tells how old the price is
+    #[returns(UsdPriceResponse)]
+    LastGoodPrice { asset: String },
+    /// LastGoodPrice of several assets in one query, in the order requested
+    #[returns(Vec<AssetPriceResponse>)]
+    LastGoodPrices { assets: Vec<String> },
+    /// Price read from each source of an asset and the source its price is read from
+    #[returns(PriceSourcesResponse)]
+    PriceSources { asset: String },
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub owner: Addr,
+    pub pyth_contract_addr: Addr,
+}
+
+#[cw_serde]
+pub struct FeedResponse {
+    pub asset: String,
+    pub price_feed_id: PriceIdentifier,
+}
+
+#[cw_serde]
+pub struct UsdPriceResponse {
+    /// Usd price, always positive
+    pub price: Decimal,
+    /// Confidence interval around price, in usd
+    pub conf: Decimal,
+    /// Unix timestamp of the price, in seconds
+    pub publish_time: i64,
+    /// Exponentially-weighted moving average of the usd price
+    pub ema_price: Decimal,
+}
+
+#[cw_serde]
+pub struct AssetPriceResponse {
+    pub asset: String,
+    pub price: UsdPriceResponse,
 }
 
 #[cw_serde]
@@ -30,3 +132,21 @@ pub struct FetchPriceResponse {
     pub current_price: Price,
     pub ema_price: Price,
 }
+
+#[cw_serde]
+pub struct PriceSourcesResponse {
+    pub asset: String,
+    pub sources: Vec<PriceSourceResponse>,
+    pub max_deviation: Option<Decimal>,
+    /// Source the price is read from, None when no source has a valid price or they disagree
+    pub used_source: Option<PriceSource>,
+}
+
+#[cw_serde]
+pub struct PriceSourceResponse {
+    pub source: PriceSource,
+    /// Usd price read from the source, None when invalid
+    pub price: Option<Decimal>,
+    /// Why the price of the source is invalid
+    pub error: Option<String>,
+}
@@ -0,0 +1,109 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal};
+use cw_storage_plus::{Item, Map};
+use pyth_sdk_cw::PriceIdentifier;
+
+use crate::msg::UsdPriceResponse;
+
+/// Max age of a price when its feed has no settings, in seconds
+pub const DEFAULT_MAX_AGE: u64 = 60;
+
+/// Max age of a cached last good price when its feed has no settings, in seconds
+pub const DEFAULT_MAX_CACHE_AGE: u64 = 86_400;
+
+/// Sanity checks applied to the prices of a Pyth feed
+#[cw_serde]
+pub struct FeedSettings {
+    /// Max age of a price, in seconds
+    pub max_age: u64,
+    /// Max confidence interval over price ratio, unchecked when None
+    pub max_conf_ratio: Option<Decimal>,
+    /// Lowest plausible usd price, unchecked when None
+    pub min_price: Option<Decimal>,
+    /// Highest plausible usd price, unchecked when None
+    pub max_price: Option<Decimal>,
+    /// Max age of the last good price cached for the assets of the feed, in seconds
+    #[serde(default = "default_max_cache_age")]
+    pub max_cache_age: u64,
+}
+
+fn default_max_cache_age() -> u64 {
+    DEFAULT_MAX_CACHE_AGE
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        FeedSettings {
+            max_age: DEFAULT_MAX_AGE,
+            max_conf_ratio: None,
+            min_price: None,
+            max_price: None,
+            max_cache_age: DEFAULT_MAX_CACHE_AGE,
+        }
+    }
+}
+
+pub const OWNER: Item<Addr> = Item::new("owner");
+
+/// Pyth contract all prices are read from
+pub const PYTH_CONTRACT: Item<Addr> = Item::new("pyth_contract");
+
+/// Price feed id by asset key: native denom or cw20 address
+pub const FEEDS: Map<String, PriceIdentifier> = Map::new("feeds");
+
+/// Settings by hex price feed id, feeds without settings use FeedSettings::default()
+pub const FEED_SETTINGS: Map<String, FeedSettings> = Map::new("feed_settings");
+
+/// Where a price can be read from
+#[cw_serde]
+pub enum PriceSource {
+    /// Feed registered for the asset on the stored Pyth contract
+    Pyth {},
+    /// Price pushed through PushPrice by a relayer, valid for max_age seconds
+    Relayer { relayer: Addr, max_age: u64 },
+}
+
+/// Price sources of an asset
+#[cw_serde]
+pub struct AssetSources {
+    /// Sources by priority, the price is read from the first one with a valid price
+    pub sources: Vec<PriceSource>,
+    /// Max deviation of any other valid source from the price read, relative to it. When
+    /// exceeded the price is rejected. Unchecked when None
+    pub max_deviation: Option<Decimal>,
+}
+
+impl Default for AssetSources {
+    fn default() -> Self {
+        AssetSources {
+            sources: vec![PriceSource::Pyth {}],
+            max_deviation: None,
+        }
+    }
+}
+
+/// Price sources by asset key, assets without sources are priced by their Pyth feed only
+pub const PRICE_SOURCES: Map<String, AssetSources> = Map::new("price_sources");
+
+/// Usd price pushed by a relayer
+#[cw_serde]
+pub struct RelayedPrice {
+    pub price: Decimal,
+    /// Block time of the push, in seconds
+    pub publish_time: u64,
+}
+
+/// Relayed prices by asset key and relayer
+pub const RELAYED_PRICES: Map<(String, Addr), RelayedPrice> = Map::new("relayed_prices");
+
+/// Last good usd price of an asset
+#[cw_serde]
+pub struct CachedPrice {
+    pub price: UsdPriceResponse,
+    /// Block time of the caching, in seconds
+    pub cached_at: u64,
+}
+
+/// Last good price by asset key, saved by UpdatePriceCache. It is served when the sources of the
+/// asset only have stale or missing prices, until it is older than the max cache age of the feed
+pub const PRICE_CACHE: Map<String, CachedPrice> = Map::new("cached_prices");
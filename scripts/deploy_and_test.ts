@@ -17,6 +17,14 @@ export default async function run () {
   const liq_thresold = "50"; // 200% collaterized
   const liq_bonus = "10" // 10% 
   const min_health_factor = "1.0"
+  const stability_fee = "0.02" // 2% a year
+  const close_factor = "0.5" // half of a debt per liquidation
+  const full_liquidation_health_factor = "0.95"
+  const liquidation_dust = "1000000" // 1 DSC
+  const liquidation_fee_share = "0.1" // a tenth of the liquidation bonus
+  const stability_fee_share = "1" // every stability fee
+  const origination_fee = "0.005" // 0.5% of minted DSC
+  const redemption_fee_floor = "0.005" // 0.5% of the collateral redeemed
 
   const stable_cw20_contract = new DscContract();
   await stable_cw20_contract.setupClient();
@@ -76,7 +84,9 @@ export default async function run () {
   console.log();
 
   const oracle_info = await oracle_contract.instantiate(
-    {},
+    {
+      "pyth_contract_addr": pyth_oracle_addr,
+    },
     `deploy oracle ${runTs}`,
     contract_owner,
     undefined, // tokens to tranfer
@@ -91,6 +101,15 @@ export default async function run () {
   const oracle_addr = oracle_info.contractAddress;
   console.log("ORACLE ADDR: ", oracle_addr)
 
+  const register_feed_res = await oracle_contract.registerFeed(
+    {account: contract_owner},
+    {asset: native_ntrn_denom, priceFeedId: ntrn_usd_price_feed_id}
+  )
+
+  console.log("REGISTER FEED RES:");
+  console.log(register_feed_res);
+  console.log();
+
 
   // 3 - Deploy DSCE
 
@@ -110,12 +129,19 @@ export default async function run () {
         }
       ],
       "oracle_address": oracle_addr,
-      "pyth_oracle_address": pyth_oracle_addr,
-      "price_feed_ids": [ntrn_usd_price_feed_id],
+      "native_assets_decimals": { [native_ntrn_denom]: 6 },
       "dsc_address": dsc_addr,
       "liquidation_threshold": liq_thresold,
       "liquidation_bonus": liq_bonus,
-      "min_health_factor": min_health_factor
+      "min_health_factor": min_health_factor,
+      "stability_fee": stability_fee,
+      "close_factor": close_factor,
+      "full_liquidation_health_factor": full_liquidation_health_factor,
+      "liquidation_dust": liquidation_dust,
+      "liquidation_fee_share": liquidation_fee_share,
+      "stability_fee_share": stability_fee_share,
+      "origination_fee": origination_fee,
+      "redemption_fee_floor": redemption_fee_floor
     },
     `deploy dsce ${runTs}`,
     contract_owner,